@@ -0,0 +1,587 @@
+use crate::shell::{self, SimpleCommand, Unwrapped};
+use crate::sinks::Sinks;
+use crate::{t, text};
+use std::path::{Component, Path, PathBuf};
+
+/// 理由に表示するコマンドの最大幅
+const SNIPPET_WIDTH: usize = 60;
+
+/// `sh -c` や `$(...)` を再帰的に解析する深さの上限
+const MAX_DEPTH: usize = 4;
+
+// ===== リスク分類 =====
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl RiskLevel {
+    pub fn emoji(self) -> &'static str {
+        match self {
+            RiskLevel::Low => "🟢",
+            RiskLevel::Medium => "🟡",
+            RiskLevel::High => "🟠",
+            RiskLevel::Critical => "🔴",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RiskLevel::Low => t("risk.level.low"),
+            RiskLevel::Medium => t("risk.level.medium"),
+            RiskLevel::High => t("risk.level.high"),
+            RiskLevel::Critical => t("risk.level.critical"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskCategory {
+    ReadOnly,
+    WritesInRepo,
+    WritesOutsideRepo,
+    Network,
+    PackageInstall,
+    GitHistoryRewrite,
+    PrivilegeEscalation,
+    DestructiveDelete,
+    /// 解析できない・種類を判別できないコマンド
+    Unknown,
+}
+
+impl RiskCategory {
+    /// カテゴリの既定のリスクレベル
+    pub fn level(self) -> RiskLevel {
+        match self {
+            RiskCategory::ReadOnly => RiskLevel::Low,
+            RiskCategory::WritesInRepo | RiskCategory::Network | RiskCategory::Unknown => RiskLevel::Medium,
+            RiskCategory::WritesOutsideRepo | RiskCategory::PackageInstall | RiskCategory::GitHistoryRewrite => {
+                RiskLevel::High
+            }
+            RiskCategory::PrivilegeEscalation | RiskCategory::DestructiveDelete => RiskLevel::Critical,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RiskCategory::ReadOnly => t("risk.category.read_only"),
+            RiskCategory::WritesInRepo => t("risk.category.writes_in_repo"),
+            RiskCategory::WritesOutsideRepo => t("risk.category.writes_outside_repo"),
+            RiskCategory::Network => t("risk.category.network"),
+            RiskCategory::PackageInstall => t("risk.category.package_install"),
+            RiskCategory::GitHistoryRewrite => t("risk.category.git_history_rewrite"),
+            RiskCategory::PrivilegeEscalation => t("risk.category.privilege_escalation"),
+            RiskCategory::DestructiveDelete => t("risk.category.destructive_delete"),
+            RiskCategory::Unknown => t("risk.category.unknown"),
+        }
+    }
+}
+
+/// 1つの理由（どのコマンドがどのカテゴリに当たるか）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Risk {
+    pub category: RiskCategory,
+    pub level: RiskLevel,
+    /// 該当したコマンド（短縮済み）
+    pub command: String,
+}
+
+impl Risk {
+    fn new(category: RiskCategory, command: &str) -> Risk {
+        Risk::with_level(category, category.level(), command)
+    }
+
+    fn with_level(category: RiskCategory, level: RiskLevel, command: &str) -> Risk {
+        Risk {
+            category,
+            level,
+            command: text::truncate_middle(command, SNIPPET_WIDTH),
+        }
+    }
+
+    /// "Network: `curl https://example.com`"
+    pub fn reason(&self) -> String {
+        format!("{}: `{}`", self.category.label(), self.command)
+    }
+}
+
+/// コマンド全体の評価
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskAssessment {
+    pub level: RiskLevel,
+    /// 重大なものから順に並べた理由（読み取りのみの場合は空）
+    pub risks: Vec<Risk>,
+}
+
+impl RiskAssessment {
+    fn from_risks(mut risks: Vec<Risk>) -> RiskAssessment {
+        let mut unique: Vec<Risk> = Vec::new();
+        for risk in risks.drain(..) {
+            if !unique.iter().any(|r| r.category == risk.category && r.command == risk.command) {
+                unique.push(risk);
+            }
+        }
+        unique.sort_by_key(|r| std::cmp::Reverse(r.level));
+        RiskAssessment {
+            level: unique.first().map_or(RiskLevel::Low, |r| r.level),
+            risks: unique,
+        }
+    }
+
+    /// 最も重大なカテゴリ（理由が無ければ読み取りのみ）
+    pub fn primary_category(&self) -> RiskCategory {
+        self.risks.first().map_or(RiskCategory::ReadOnly, |r| r.category)
+    }
+
+    /// デスクトップ通知のサブタイトル用（例: "🟠 High risk: Network"）
+    pub fn badge(&self) -> String {
+        format!("{} {}: {}", self.level.emoji(), self.level.label(), self.primary_category().label())
+    }
+
+    /// Slackの"Risk"フィールド用
+    pub fn slack_field(&self) -> String {
+        let mut lines = vec![format!("{} *{}*", self.level.emoji(), self.level.label())];
+        if self.risks.is_empty() {
+            lines.push(format!("• {}", RiskCategory::ReadOnly.label()));
+        }
+        lines.extend(self.risks.iter().map(|r| format!("• {}", r.reason())));
+        lines.join("\n")
+    }
+}
+
+// ===== 解析 =====
+
+/// Bashコマンドのリスクを評価する（リポジトリのルートは `git rev-parse` で決定）
+pub fn analyze(command: &str, cwd: &str, sinks: &dyn Sinks) -> RiskAssessment {
+    let root = sinks.git_toplevel(cwd).unwrap_or_else(|| cwd.to_string());
+    let context = Context {
+        cwd: Path::new(cwd),
+        root: Path::new(&root),
+        home: sinks.home(),
+        temp: sinks.temp_dir(),
+    };
+    let mut risks = Vec::new();
+    analyze_script(command, &context, 0, &mut risks);
+    RiskAssessment::from_risks(risks)
+}
+
+struct Context<'a> {
+    cwd: &'a Path,
+    root: &'a Path,
+    home: Option<PathBuf>,
+    /// `$TMPDIR`（macOSでは `/var/folders/.../T`）
+    temp: Option<PathBuf>,
+}
+
+fn analyze_script(script: &str, context: &Context, depth: usize, risks: &mut Vec<Risk>) {
+    if depth > MAX_DEPTH {
+        risks.push(Risk::new(RiskCategory::Unknown, script));
+        return;
+    }
+    let parsed = match shell::parse(script) {
+        Ok(parsed) => parsed,
+        Err(_) => {
+            risks.push(Risk::new(RiskCategory::Unknown, script));
+            return;
+        }
+    };
+
+    let mut pipeline_network = false;
+    for command in &parsed.commands {
+        if !command.piped {
+            pipeline_network = false;
+        }
+        let before = risks.len();
+        analyze_command(command, context, depth, risks);
+        let unwrapped = shell::unwrap_command(&command.argv);
+
+        // ダウンロードした内容をそのままシェルに渡す（curl ... | sh）
+        if command.piped && pipeline_network && is_interpreter(unwrapped.program()) {
+            risks.push(Risk::with_level(RiskCategory::Network, RiskLevel::Critical, &snippet(command)));
+        }
+        if risks[before..].iter().any(|r| r.category == RiskCategory::Network) {
+            pipeline_network = true;
+        }
+    }
+    for substitution in &parsed.substitutions {
+        analyze_script(substitution, context, depth + 1, risks);
+    }
+}
+
+fn analyze_command(command: &SimpleCommand, context: &Context, depth: usize, risks: &mut Vec<Risk>) {
+    let line = snippet(command);
+    let unwrapped = shell::unwrap_command(&command.argv);
+
+    for redirect in command.redirects.iter().filter(|r| r.is_write()) {
+        check_write(&redirect.target, &format!("{} {}", redirect.op, redirect.target), context, risks);
+    }
+    if unwrapped.wrappers.iter().any(|w| matches!(*w, "sudo" | "doas")) {
+        risks.push(Risk::new(RiskCategory::PrivilegeEscalation, &line));
+    }
+    if let Some(script) = shell::inline_script(&unwrapped) {
+        analyze_script(&script, context, depth + 1, risks);
+        return;
+    }
+    classify_program(&unwrapped, &line, context, risks);
+}
+
+fn classify_program(command: &Unwrapped, line: &str, context: &Context, risks: &mut Vec<Risk>) {
+    let args = command.args();
+    let has_flag = |flags: &[&str]| args.iter().any(|a| flags.contains(&a.as_str()));
+    let positional = || args.iter().filter(|a| !a.starts_with('-')).map(|a| a.as_str());
+    let first = positional().next().unwrap_or("");
+
+    match command.program() {
+        "" => {}
+        // 読み取りのみ
+        "ls" | "cat" | "head" | "tail" | "less" | "more" | "grep" | "egrep" | "fgrep" | "rg" | "ag" | "fd" | "wc"
+        | "uniq" | "cut" | "tr" | "jq" | "yq" | "echo" | "printf" | "pwd" | "which" | "type" | "whoami" | "date"
+        | "diff" | "cmp" | "file" | "stat" | "du" | "df" | "ps" | "tree" | "realpath" | "dirname" | "basename"
+        | "true" | "false" | "test" | "[" | "cd" | "pushd" | "popd" | "uname" | "hostname" | "id" | "env"
+        | "printenv" | "sleep" | "column" | "nl" | "od" | "xxd" | "hexdump" | "md5" | "md5sum" | "shasum"
+        | "sha256sum" | "lsof" | "top" | "history" | "man" | "export" | "set" | "unset" | "source" | "." | "read"
+        | "exit" | "wait" | "jobs" | "awk" | "gawk" => {}
+        "sort" => {
+            if let Some(out) = option_value(args, &["-o", "--output"]) {
+                check_write(out, line, context, risks);
+            }
+        }
+        "find" => {
+            if has_flag(&["-delete"]) {
+                risks.push(Risk::with_level(RiskCategory::DestructiveDelete, RiskLevel::High, line));
+            } else if let Some(position) =
+                args.iter().position(|a| matches!(a.as_str(), "-exec" | "-execdir" | "-ok" | "-okdir"))
+            {
+                // -exec の後ろのコマンドを解析
+                let inner: Vec<String> = args[position + 1..]
+                    .iter()
+                    .take_while(|a| *a != ";" && *a != "+")
+                    .cloned()
+                    .collect();
+                let inner = shell::unwrap_command(&inner);
+                classify_program(&inner, line, context, risks);
+            }
+        }
+        "sed" | "gsed" | "perl" => {
+            let in_place = args.iter().any(|a| {
+                a.starts_with("--in-place") || (a.starts_with('-') && !a.starts_with("--") && a.contains('i'))
+            });
+            if in_place {
+                for path in positional().skip(1) {
+                    check_write(path, line, context, risks);
+                }
+            }
+        }
+        "rm" | "rmdir" | "unlink" => classify_delete(command, line, context, risks),
+        "shred" | "mkfs" | "fdisk" | "diskutil" | "wipefs" => {
+            risks.push(Risk::new(RiskCategory::DestructiveDelete, line));
+        }
+        program if program.starts_with("mkfs.") => risks.push(Risk::new(RiskCategory::DestructiveDelete, line)),
+        "dd" => {
+            let target = args.iter().find_map(|a| a.strip_prefix("of="));
+            match target {
+                Some(path) if path.starts_with("/dev/") && path != "/dev/null" => {
+                    risks.push(Risk::new(RiskCategory::DestructiveDelete, line))
+                }
+                Some(path) => check_write(path, line, context, risks),
+                None => {}
+            }
+        }
+        "cp" | "mv" | "install" => {
+            // 最後の引数が書き込み先（mvは移動元も変更される）
+            let paths: Vec<&str> = positional().collect();
+            if let Some(dest) = paths.last() {
+                check_write(dest, line, context, risks);
+            }
+            if command.program() == "mv" {
+                for source in &paths[..paths.len().saturating_sub(1)] {
+                    check_write(source, line, context, risks);
+                }
+            }
+        }
+        "touch" | "mkdir" | "tee" | "ln" | "truncate" | "chown" | "chgrp" | "patch" => {
+            let paths: Vec<&str> = positional().collect();
+            let paths = if matches!(command.program(), "chown" | "chgrp") { paths.get(1..).unwrap_or(&[]).to_vec() } else { paths };
+            if paths.is_empty() {
+                risks.push(Risk::new(RiskCategory::WritesInRepo, line));
+            }
+            for path in paths {
+                check_write(path, line, context, risks);
+            }
+        }
+        "chmod" => {
+            let mut paths = positional();
+            let mode = paths.next().unwrap_or("");
+            if is_setuid_mode(mode) {
+                risks.push(Risk::new(RiskCategory::PrivilegeEscalation, line));
+            }
+            for path in paths {
+                check_write(path, line, context, risks);
+            }
+        }
+        "su" | "sudo" | "doas" | "pkexec" | "launchctl" | "visudo" => {
+            risks.push(Risk::new(RiskCategory::PrivilegeEscalation, line));
+        }
+        "git" => classify_git(args, line, context, risks),
+        "curl" => {
+            risks.push(Risk::new(RiskCategory::Network, line));
+            if let Some(out) = option_value(args, &["-o", "--output"]) {
+                check_write(out, line, context, risks);
+            }
+        }
+        "wget" => {
+            risks.push(Risk::new(RiskCategory::Network, line));
+            let out = option_value(args, &["-O", "--output-document", "-P", "--directory-prefix"]).unwrap_or(".");
+            check_write(out, line, context, risks);
+        }
+        "rsync" if !is_remote_args(args) => {
+            if let Some(dest) = positional().next_back() {
+                check_write(dest, line, context, risks);
+            }
+        }
+        "rsync" | "scp" | "sftp" | "ssh" | "nc" | "ncat" | "netcat" | "telnet" | "ftp" | "http" | "https" | "xh"
+        | "ping" | "dig" | "nslookup" | "host" | "socat" | "aria2c" => {
+            risks.push(Risk::new(RiskCategory::Network, line));
+        }
+        "gh" => {
+            let destructive = positional().any(|a| a == "delete");
+            let category = if destructive { RiskCategory::DestructiveDelete } else { RiskCategory::Network };
+            risks.push(Risk::new(category, line));
+        }
+        "npm" | "pnpm" | "yarn" | "bun" => match first {
+            "install" | "i" | "add" | "ci" | "update" | "upgrade" | "up" | "dlx" | "x" | "global" => {
+                risks.push(Risk::new(RiskCategory::PackageInstall, line))
+            }
+            "" if command.program() == "yarn" => risks.push(Risk::new(RiskCategory::PackageInstall, line)),
+            "publish" | "unpublish" | "login" => risks.push(Risk::new(RiskCategory::Network, line)),
+            _ => risks.push(Risk::new(RiskCategory::WritesInRepo, line)),
+        },
+        "npx" | "bunx" | "pnpx" | "pipx" | "uvx" => risks.push(Risk::new(RiskCategory::PackageInstall, line)),
+        "pip" | "pip3" | "gem" | "go" | "composer" | "conda" | "mamba" => match first {
+            "install" | "get" | "require" | "update" | "upgrade" | "create" => {
+                risks.push(Risk::new(RiskCategory::PackageInstall, line))
+            }
+            _ => risks.push(Risk::new(RiskCategory::WritesInRepo, line)),
+        },
+        "uv" => match first {
+            "pip" | "add" | "sync" | "tool" => risks.push(Risk::new(RiskCategory::PackageInstall, line)),
+            _ => risks.push(Risk::new(RiskCategory::WritesInRepo, line)),
+        },
+        "brew" | "apt" | "apt-get" | "yum" | "dnf" | "pacman" | "apk" | "port" | "snap" | "zypper" => {
+            if !matches!(first, "list" | "info" | "search" | "show" | "outdated" | "doctor" | "config") {
+                risks.push(Risk::new(RiskCategory::PackageInstall, line));
+            }
+        }
+        "cargo" => match first {
+            "install" => risks.push(Risk::new(RiskCategory::PackageInstall, line)),
+            "publish" | "login" | "yank" => risks.push(Risk::new(RiskCategory::Network, line)),
+            "tree" | "metadata" | "version" | "search" => {}
+            _ => risks.push(Risk::new(RiskCategory::WritesInRepo, line)),
+        },
+        "make" | "cmake" | "ninja" | "gradle" | "gradlew" | "mvn" | "node" | "python" | "python3" | "ruby" | "deno"
+        | "tsc" | "rustc" | "gcc" | "clang" | "swift" | "xcodebuild" | "docker" | "kubectl" | "terraform" => {
+            risks.push(Risk::new(RiskCategory::WritesInRepo, line));
+        }
+        "kill" | "pkill" | "killall" => risks.push(Risk::new(RiskCategory::WritesOutsideRepo, line)),
+        "shutdown" | "reboot" | "halt" => risks.push(Risk::new(RiskCategory::PrivilegeEscalation, line)),
+        _ => risks.push(Risk::new(RiskCategory::Unknown, line)),
+    }
+}
+
+/// chmodのモードがsetuid / setgidを付けるか（"u+s"、"g=rws"、"4755" など）
+pub fn is_setuid_mode(mode: &str) -> bool {
+    if !mode.is_empty() && mode.bytes().all(|b| b.is_ascii_digit()) {
+        // 4桁の数値モードの先頭が setuid (4) / setgid (2) を含む
+        return mode.len() == 4 && matches!(mode.as_bytes()[0], b'2'..=b'7');
+    }
+    // 記号モードは "+" / "=" の後ろの "s"（"u-s" は外すだけ）
+    let mut adding = false;
+    for c in mode.chars() {
+        match c {
+            '+' | '=' => adding = true,
+            '-' | ',' => adding = false,
+            's' if adding => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+fn classify_delete(command: &Unwrapped, line: &str, context: &Context, risks: &mut Vec<Risk>) {
+    let args = command.args();
+    let recursive = args.iter().any(|a| {
+        a == "--recursive" || (a.starts_with('-') && !a.starts_with("--") && (a.contains('r') || a.contains('R')))
+    });
+    let targets: Vec<&str> = args.iter().filter(|a| !a.starts_with('-')).map(|a| a.as_str()).collect();
+
+    let dangerous = targets.iter().any(|t| {
+        matches!(*t, "/" | "/*" | "~" | "~/" | "*" | "." | ".." | "$HOME" | "${HOME}") || !context.is_inside(t)
+    });
+    if dangerous {
+        risks.push(Risk::new(RiskCategory::DestructiveDelete, line));
+    } else if recursive {
+        risks.push(Risk::with_level(RiskCategory::DestructiveDelete, RiskLevel::High, line));
+    } else {
+        risks.push(Risk::new(RiskCategory::WritesInRepo, line));
+    }
+}
+
+fn classify_git(args: &[String], line: &str, context: &Context, risks: &mut Vec<Risk>) {
+    // `-C path` `-c key=value` などのグローバルオプションを読み飛ばす
+    let mut i = 0;
+    while i < args.len() && args[i].starts_with('-') {
+        if matches!(args[i].as_str(), "-C" | "-c" | "--git-dir" | "--work-tree" | "--namespace") {
+            i += 1;
+        }
+        i += 1;
+    }
+    let subcommand = args.get(i).map(|s| s.as_str()).unwrap_or("");
+    let rest = args.get(i + 1..).unwrap_or(&[]);
+    let has = |flags: &[&str]| rest.iter().any(|a| flags.contains(&a.as_str()));
+    let has_prefix = |prefix: &str| rest.iter().any(|a| a.starts_with(prefix));
+
+    let category = match subcommand {
+        "" | "status" | "log" | "diff" | "show" | "blame" | "rev-parse" | "ls-files" | "ls-tree" | "describe"
+        | "shortlog" | "grep" | "cat-file" | "rev-list" | "merge-base" | "help" | "version" | "whatchanged" => {
+            RiskCategory::ReadOnly
+        }
+        "branch" if has(&["-D", "--delete"]) || (has(&["-d"]) && has(&["--force", "-f"])) => {
+            RiskCategory::GitHistoryRewrite
+        }
+        "branch" | "tag" | "remote" | "stash" | "worktree" | "reflog" | "config" if rest.is_empty() => RiskCategory::ReadOnly,
+        "branch" if has(&["--list", "-l", "-a", "-r", "-v", "-vv", "--show-current"]) => RiskCategory::ReadOnly,
+        "remote" if has(&["-v", "show", "get-url"]) => RiskCategory::ReadOnly,
+        "stash" if has(&["list", "show"]) => RiskCategory::ReadOnly,
+        "tag" if has(&["-l", "--list"]) => RiskCategory::ReadOnly,
+        "worktree" if has(&["list"]) => RiskCategory::ReadOnly,
+        "config" if has(&["--get", "--list", "-l", "--get-all", "--show-origin"]) => RiskCategory::ReadOnly,
+        "config" if has(&["--global", "--system"]) => RiskCategory::WritesOutsideRepo,
+        "reflog" if has(&["expire", "delete"]) => RiskCategory::GitHistoryRewrite,
+        "reflog" => RiskCategory::ReadOnly,
+        "push" if has(&["-f", "--force", "--mirror", "--delete", "-d"]) || has_prefix("--force-with-lease") || rest.iter().any(|a| a.starts_with('+')) => {
+            RiskCategory::GitHistoryRewrite
+        }
+        "clone" | "fetch" | "pull" | "push" | "ls-remote" | "submodule" => RiskCategory::Network,
+        "reset" if has(&["--hard", "--merge", "--keep"]) => RiskCategory::GitHistoryRewrite,
+        "rebase" | "filter-branch" | "filter-repo" | "replace" => RiskCategory::GitHistoryRewrite,
+        "commit" if has(&["--amend"]) => RiskCategory::GitHistoryRewrite,
+        "update-ref" if has(&["-d"]) => RiskCategory::GitHistoryRewrite,
+        "gc" if has_prefix("--prune") => RiskCategory::GitHistoryRewrite,
+        "clean" if rest.iter().any(|a| a.starts_with('-') && !a.starts_with("--") && a.contains('f')) || has(&["--force"]) => {
+            RiskCategory::DestructiveDelete
+        }
+        "checkout" | "restore" if has(&["--", ".", "-f", "--force"]) => RiskCategory::DestructiveDelete,
+        _ => RiskCategory::WritesInRepo,
+    };
+
+    match category {
+        RiskCategory::ReadOnly => {}
+        // 作業ツリーの変更の破棄は復元できないが、リポジトリ外には影響しない
+        RiskCategory::DestructiveDelete => {
+            risks.push(Risk::with_level(RiskCategory::DestructiveDelete, RiskLevel::High, line))
+        }
+        RiskCategory::Network if subcommand == "clone" => {
+            risks.push(Risk::new(category, line));
+            if let Some(dest) = rest.iter().filter(|a| !a.starts_with('-')).nth(1) {
+                check_write(dest, line, context, risks);
+            }
+        }
+        _ => risks.push(Risk::new(category, line)),
+    }
+}
+
+/// 書き込み先がリポジトリ内か外かで分類する
+fn check_write(path: &str, line: &str, context: &Context, risks: &mut Vec<Risk>) {
+    if matches!(path, "/dev/null" | "/dev/stdout" | "/dev/stderr" | "/dev/tty" | "-") {
+        return;
+    }
+    if context.is_inside(path) {
+        risks.push(Risk::new(RiskCategory::WritesInRepo, line));
+    } else if context.is_temp(path) {
+        // 一時ディレクトリはリポジトリ外でも影響が小さい
+        risks.push(Risk::with_level(RiskCategory::WritesOutsideRepo, RiskLevel::Medium, line));
+    } else {
+        risks.push(Risk::new(RiskCategory::WritesOutsideRepo, line));
+    }
+}
+
+impl Context<'_> {
+    /// `~` と `$HOME` を展開し、`..` を解決した絶対パス（他の変数を含む場合は `None`）
+    fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let expanded = if let Some(rest) = path
+            .strip_prefix("~")
+            .or_else(|| path.strip_prefix("$HOME"))
+            .or_else(|| path.strip_prefix("${HOME}"))
+        {
+            let home = self.home.as_ref()?;
+            home.join(rest.trim_start_matches('/'))
+        } else if path.contains('$') || path.contains('`') {
+            return None;
+        } else {
+            self.cwd.join(path)
+        };
+        Some(normalize(&expanded))
+    }
+
+    fn is_inside(&self, path: &str) -> bool {
+        self.resolve(path).is_some_and(|p| p.starts_with(normalize(self.root)))
+    }
+
+    fn is_temp(&self, path: &str) -> bool {
+        let temp = self.temp.as_deref().map(normalize);
+        self.resolve(path).is_some_and(|p| {
+            p.starts_with("/tmp") || p.starts_with("/private/tmp") || temp.as_ref().is_some_and(|t| p.starts_with(t))
+        })
+    }
+}
+
+/// シンボリックリンクを辿らずに `.` と `..` を解決する
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                result.pop();
+            }
+            Component::CurDir => {}
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+/// `-o file` / `-ofile` / `--output=file` 形式のオプション値
+fn option_value<'a>(args: &'a [String], names: &[&str]) -> Option<&'a str> {
+    for (i, arg) in args.iter().enumerate() {
+        for name in names {
+            if arg == name {
+                return args.get(i + 1).map(|s| s.as_str());
+            }
+            if let Some(value) = arg.strip_prefix(&format!("{}=", name)) {
+                return Some(value);
+            }
+            if name.len() == 2 && !name.starts_with("--") && arg.len() > 2 && arg.starts_with(name) {
+                return Some(&arg[2..]);
+            }
+        }
+    }
+    None
+}
+
+fn is_interpreter(program: &str) -> bool {
+    matches!(program, "sh" | "bash" | "zsh" | "dash" | "ksh" | "fish" | "python" | "python3" | "perl" | "ruby" | "node")
+}
+
+fn is_remote_args(args: &[String]) -> bool {
+    args.iter().any(|a| !a.starts_with('-') && a.contains(':') && !a.starts_with('/'))
+}
+
+fn snippet(command: &SimpleCommand) -> String {
+    let mut parts: Vec<String> = command.argv.clone();
+    parts.extend(command.redirects.iter().map(|r| format!("{} {}", r.op, r.target)));
+    parts.join(" ")
+}
@@ -0,0 +1,255 @@
+use claude_hooks::config::HooksConfig;
+use claude_hooks::dashboard;
+use claude_hooks::doctor::{self, DoctorContext, Status};
+use claude_hooks::focus;
+use claude_hooks::handlers;
+use claude_hooks::i18n::Locale;
+use claude_hooks::install::{self, DEFAULT_BIN_DIR};
+use claude_hooks::replay::{self, Fixture};
+use claude_hooks::sinks::RealSinks;
+use claude_hooks::{t, tf, RealSystem};
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+
+/// コマンドライン引数
+struct Options {
+    settings: PathBuf,
+    bin_dir: String,
+    copy: bool,
+    dry_run: bool,
+}
+
+fn parse_options(args: &[String], home: Option<&str>) -> Option<Options> {
+    let mut options = Options {
+        settings: install::expand_home("~/.claude/settings.json", home),
+        bin_dir: DEFAULT_BIN_DIR.to_string(),
+        copy: true,
+        dry_run: false,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--settings" => options.settings = install::expand_home(args.next()?, home),
+            "--bin-dir" => options.bin_dir = args.next()?.clone(),
+            "--no-copy" => options.copy = false,
+            "--dry-run" => options.dry_run = true,
+            _ => return None,
+        }
+    }
+    Some(options)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let home = env::var("HOME").ok();
+    if args.first().map(|s| s.as_str()) == Some("replay") {
+        return run_replay(&args[1..]);
+    }
+    Locale::init_from(HooksConfig::global(), &RealSystem);
+    if args.first().map(|s| s.as_str()) == Some("idle-watch") {
+        return run_idle_watch(&args[1..]);
+    }
+    if args.first().map(|s| s.as_str()) == Some("focus") {
+        return run_focus(&args[1..]);
+    }
+    if args.first().map(|s| s.as_str()) == Some("serve") {
+        return run_serve(&args[1..]);
+    }
+    let options = args.get(1..).and_then(|rest| parse_options(rest, home.as_deref()));
+
+    let result = match (args.first().map(|s| s.as_str()), options) {
+        (Some("install"), Some(options)) => run_install(&options, home.as_deref()),
+        (Some("doctor"), Some(options)) => Ok(run_doctor(&options)),
+        (Some("register-url-handler"), Some(options)) => run_register_url_handler(&options),
+        _ => {
+            eprintln!("{}", t("install.usage"));
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// バイナリをコピーし、settings.json にフックをマージする
+fn run_install(options: &Options, home: Option<&str>) -> Result<ExitCode, String> {
+    // 実行中のバイナリと同じディレクトリ（target/release）からコピーする
+    if options.copy && !options.dry_run {
+        let exe = env::current_exe().map_err(|e| e.to_string())?;
+        let source_dir = exe.parent().ok_or("cannot locate the build directory")?;
+        let bin_dir = install::expand_home(&options.bin_dir, home);
+        for path in install::install_binaries(source_dir, &bin_dir).map_err(|e| e.to_string())? {
+            println!("{}", tf("install.copied", &[("path", &path.display().to_string())]));
+        }
+    }
+
+    let mut settings = install::read_settings(&options.settings).map_err(|e| e.to_string())?;
+    let added = install::merge_hooks(&mut settings, &options.bin_dir, home)?;
+    let path = options.settings.display().to_string();
+    if added.is_empty() {
+        println!("{}", tf("install.up_to_date", &[("path", &path)]));
+        return Ok(ExitCode::SUCCESS);
+    }
+    for hook in &added {
+        let vars = [("event", hook.event.as_str()), ("matcher", hook.matcher.as_str()), ("command", hook.command.as_str())];
+        println!("{}", tf("install.added", &vars));
+    }
+
+    if options.dry_run {
+        println!("{}", t("install.dry_run"));
+        return Ok(ExitCode::SUCCESS);
+    }
+    let backup = install::write_settings(&options.settings, &settings).map_err(|e| e.to_string())?;
+    if let Some(backup) = backup {
+        println!("{}", tf("install.backup", &[("path", &backup.display().to_string())]));
+    }
+    println!("{}", tf("install.written", &[("path", &path)]));
+    Ok(ExitCode::SUCCESS)
+}
+
+/// 設定と環境を確認してチェックリストを表示する（エラーがあれば終了コード1）
+fn run_doctor(options: &Options) -> ExitCode {
+    let checks = doctor::run(&DoctorContext::from_env(&RealSystem, options.settings.clone()));
+    println!("{}", doctor::format_checklist(&checks));
+    if checks.iter().any(|c| c.status == Status::Error) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// `x-claude://` の `.desktop` ファイルを登録する（Linux）
+fn run_register_url_handler(options: &Options) -> Result<ExitCode, String> {
+    let path = focus::register_desktop_handler(&RealSystem, &options.bin_dir).map_err(|e| e.to_string())?;
+    println!("{}", tf("focus.registered", &[("path", &path.display().to_string())]));
+    Ok(ExitCode::SUCCESS)
+}
+
+/// 通知・SlackのリンクのURLを開き、セッションのペイン・ウィンドウに移動する
+fn run_focus(args: &[String]) -> ExitCode {
+    let [url] = args else {
+        eprintln!("{}", t("install.usage"));
+        return ExitCode::from(2);
+    };
+    match focus::focus(&RealSystem, url, cfg!(target_os = "macos")) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// 実行中のセッションのダッシュボードを `127.0.0.1` で表示する
+fn run_serve(args: &[String]) -> ExitCode {
+    let config = HooksConfig::global();
+    let port = match args {
+        [] => Some(config.dashboard.port),
+        [flag, port] if flag == "--port" => port.parse().ok(),
+        _ => None,
+    };
+    let Some(port) = port else {
+        eprintln!("{}", t("install.usage"));
+        return ExitCode::from(2);
+    };
+    println!("{}", tf("dashboard.serving", &[("port", &port.to_string())]));
+    match dashboard::serve(RealSystem, config, port) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `permission-notification` がバックグラウンドで起動する: 入力が無いまま時間が経ったらSlack・メンションで通知する
+fn run_idle_watch(args: &[String]) -> ExitCode {
+    let [session_id, since] = args else {
+        eprintln!("{}", t("install.usage"));
+        return ExitCode::from(2);
+    };
+    let sinks = RealSinks::new(RealSystem);
+    loop {
+        match handlers::idle_watch(session_id, since, &sinks) {
+            Ok(Some(wait)) => thread::sleep(wait),
+            Ok(None) => return ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+}
+
+/// フィクスチャをリプレイして期待する出力と比較する（`--update` で期待する出力を書き換える）
+///
+/// 期待する出力が無いフィクスチャは出力をJSONで表示する。ディレクトリを指定した場合は配下の全てのフィクスチャを使う。
+/// テストと同じ結果になるよう、表示言語は英語に固定する。
+fn run_replay(args: &[String]) -> ExitCode {
+    let update = args.iter().any(|a| a == "--update");
+    let targets: Vec<PathBuf> = args.iter().filter(|a| *a != "--update").map(PathBuf::from).collect();
+    if targets.is_empty() {
+        eprintln!("{}", t("install.usage"));
+        return ExitCode::from(2);
+    }
+    Locale::init(Locale::En);
+
+    let mut paths = Vec::new();
+    for target in targets {
+        if target.is_dir() {
+            paths.extend(replay::find_fixtures(&target).unwrap_or_default());
+        } else {
+            paths.push(target);
+        }
+    }
+
+    let mut failed = false;
+    for path in paths {
+        let display = path.display().to_string();
+        let result = Fixture::load(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|fixture| Ok((fixture.replay()?, fixture)));
+        let (outputs, mut fixture) = match result {
+            Ok(result) => result,
+            Err(err) => {
+                eprintln!("{}", tf("replay.error", &[("path", &display), ("error", &err)]));
+                failed = true;
+                continue;
+            }
+        };
+
+        if update {
+            fixture.expect = Some(outputs);
+            match fixture.save(&path) {
+                Ok(()) => println!("{}", tf("replay.updated", &[("path", &display)])),
+                Err(err) => {
+                    eprintln!("{}", tf("replay.error", &[("path", &display), ("error", &err.to_string())]));
+                    failed = true;
+                }
+            }
+            continue;
+        }
+
+        let actual = serde_json::to_string_pretty(&outputs).unwrap_or_default();
+        match &fixture.expect {
+            Some(expect) if *expect == outputs => println!("✅ {}", tf("replay.ok", &[("path", &display)])),
+            Some(_) => {
+                println!("❌ {}\n{}", tf("replay.mismatch", &[("path", &display)]), actual);
+                failed = true;
+            }
+            None => println!("{}\n{}", display, actual),
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,26 @@
+use claude_hooks::*;
+use std::env;
+use std::io;
+
+/// ステータスライン等の外部スクリプト向けにGitコンテキストをJSONで出力する
+fn main() -> io::Result<()> {
+    let cwd = match env::args().nth(1) {
+        Some(dir) => dir,
+        None => env::current_dir()?.to_string_lossy().to_string(),
+    };
+
+    let output = match GitContext::collect(&RealSystem, &cwd) {
+        Some(ctx) => serde_json::json!({
+            "label": ctx.branch_label(),
+            "summary": ctx.status_summary(),
+            "display": ctx.display(),
+            "dirty": ctx.is_dirty(),
+            "branch_url": ctx.branch_url(),
+            "context": ctx,
+        }),
+        None => serde_json::Value::Null,
+    };
+
+    println!("{}", output);
+    Ok(())
+}
@@ -0,0 +1,5 @@
+use std::io;
+
+fn main() -> io::Result<()> {
+    claude_hooks::handlers::main("session-end-summary")
+}
@@ -0,0 +1,187 @@
+use crate::git::DiffStatEntry;
+use crate::{t, tf, user_prompt_text, TranscriptMessage};
+use std::path::Path;
+
+/// ファイルを変更するツール名
+const FILE_EDIT_TOOLS: &[&str] = &["Write", "Edit", "MultiEdit", "NotebookEdit"];
+
+/// 一覧表示するファイル数の上限
+const MAX_LISTED_FILES: usize = 10;
+
+// ===== 変更ファイル集計 =====
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedFile {
+    /// リポジトリルート（Git管理外の場合はcwd）からの相対パス
+    pub path: String,
+    pub added: Option<usize>,
+    pub removed: Option<usize>,
+    /// 直近のプロンプト以降にこのセッションのツール呼び出しで変更されたか
+    pub by_session: bool,
+}
+
+/// タスク完了時に表示する変更ファイルの要約
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeSummary {
+    pub files: Vec<ChangedFile>,
+}
+
+impl ChangeSummary {
+    /// セッションで変更したファイル（絶対パス）と差分統計を突き合わせる
+    ///
+    /// `session_files` と `root` はシンボリックリンクを解決したパスで渡す（`git rev-parse --show-toplevel` は解決済みのパスを返す）。
+    pub fn from_parts(session_files: &[String], diff: &[DiffStatEntry], root: &str) -> ChangeSummary {
+        let mut files: Vec<ChangedFile> = session_files
+            .iter()
+            .map(|path| {
+                let path = relative_to_root(path, root);
+                let stat = diff.iter().find(|d| d.path == path);
+                ChangedFile {
+                    added: stat.and_then(|d| d.added),
+                    removed: stat.and_then(|d| d.removed),
+                    path,
+                    by_session: true,
+                }
+            })
+            .collect();
+
+        for entry in diff {
+            if !files.iter().any(|f| f.path == entry.path) {
+                files.push(ChangedFile {
+                    path: entry.path.clone(),
+                    added: entry.added,
+                    removed: entry.removed,
+                    by_session: false,
+                });
+            }
+        }
+
+        ChangeSummary { files }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// 追加行数・削除行数の合計
+    pub fn totals(&self) -> (usize, usize) {
+        self.files.iter().fold((0, 0), |(a, r), f| {
+            (a + f.added.unwrap_or(0), r + f.removed.unwrap_or(0))
+        })
+    }
+
+    /// 件数と行数の要約（例: "3 files (+12 -4)"）
+    pub fn headline(&self) -> String {
+        let (added, removed) = self.totals();
+        let key = if self.files.len() == 1 { "changes.headline_one" } else { "changes.headline" };
+        tf(
+            key,
+            &[
+                ("count", &self.files.len().to_string()),
+                ("added", &added.to_string()),
+                ("removed", &removed.to_string()),
+            ],
+        )
+    }
+
+    /// デスクトップ通知の本文に追記する1行（例: "📁 2 files (+3 -1): a.rs, b.rs"）
+    pub fn desktop_line(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let names: Vec<&str> = self
+            .files
+            .iter()
+            .take(3)
+            .map(|f| f.path.rsplit('/').next().unwrap_or(&f.path))
+            .collect();
+        let more = if self.files.len() > names.len() {
+            tf("changes.more_short", &[("count", &(self.files.len() - names.len()).to_string())])
+        } else {
+            String::new()
+        };
+        Some(format!("📁 {}: {}{}", self.headline(), names.join(", "), more))
+    }
+
+    /// Slackの"Changed Files"フィールド用の値
+    pub fn slack_field(&self) -> String {
+        if self.is_empty() {
+            return t("changes.none").to_string();
+        }
+        let mut lines = vec![format!("*{}*", self.headline())];
+        for file in self.files.iter().take(MAX_LISTED_FILES) {
+            let marker = if file.by_session { "✏️" } else { "•" };
+            let stat = match (file.added, file.removed) {
+                (Some(a), Some(r)) => format!(" +{} -{}", a, r),
+                (None, None) if file.by_session => format!(" {}", t("changes.new")),
+                _ => format!(" {}", t("changes.binary")),
+            };
+            lines.push(format!("{} `{}`{}", marker, file.path, stat));
+        }
+        if self.files.len() > MAX_LISTED_FILES {
+            lines.push(tf("changes.more", &[("count", &(self.files.len() - MAX_LISTED_FILES).to_string())]));
+        }
+        lines.join("\n")
+    }
+}
+
+fn relative_to_root(path: &str, root: &str) -> String {
+    Path::new(path)
+        .strip_prefix(root)
+        .ok()
+        .and_then(|p| p.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+// ===== トランスクリプト解析 =====
+
+/// トランスクリプト（JSONL）の最後のユーザープロンプト以降に変更されたファイル
+pub fn session_edited_files(transcript: &str) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+
+    for line in transcript.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let msg = match serde_json::from_str::<TranscriptMessage>(line) {
+            Ok(msg) => msg,
+            Err(_) => continue,
+        };
+
+        // 新しいプロンプトが来たら集計し直す
+        if user_prompt_text(&msg).is_some() {
+            files.clear();
+            continue;
+        }
+
+        if msg.msg_type != "assistant" {
+            continue;
+        }
+        let items = match msg.message.as_ref().and_then(|m| m.content.as_array()) {
+            Some(items) => items,
+            None => continue,
+        };
+        for item in items {
+            if item.get("type").and_then(|v| v.as_str()) != Some("tool_use") {
+                continue;
+            }
+            let name = item.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            if !FILE_EDIT_TOOLS.contains(&name) {
+                continue;
+            }
+            let input = item.get("input");
+            let path = input
+                .and_then(|i| i.get("file_path").or_else(|| i.get("notebook_path")))
+                .and_then(|v| v.as_str());
+            if let Some(path) = path {
+                if !files.iter().any(|f| f == path) {
+                    files.push(path.to_string());
+                }
+            }
+        }
+    }
+
+    files
+}
@@ -0,0 +1,84 @@
+use crate::context::ContextConfig;
+use crate::dashboard::DashboardConfig;
+use crate::idle::IdleConfig;
+use crate::mcp::McpServer;
+use crate::network_policy::NetworkPolicyConfig;
+use crate::path_policy::PathPolicyConfig;
+use crate::prompt::PromptConfig;
+use crate::session_context::SessionContextConfig;
+use crate::subagent::SubagentConfig;
+use crate::tool_display::ToolTemplate;
+use serde::Deserialize;
+use crate::system::{RealSystem, System};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// ===== 設定ファイル =====
+
+/// `~/.claude/claude-hooks.json` の内容
+///
+/// 全ての項目は省略可能で、ファイルが存在しない場合はデフォルト値を使う。
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// 表示言語（"ja" / "en"）。未設定の場合は `LANG` などの環境変数から決定する
+    pub locale: Option<String>,
+    /// `format-tool-display` のツールごとの表示テンプレート（組み込みより優先）
+    pub tool_display: Vec<ToolTemplate>,
+    /// MCPサーバー名ごとの絵文字・表示名
+    pub mcp_servers: HashMap<String, McpServer>,
+    /// `path-policy-check` のRead / Write / Edit / Glob / Grepのパスのルール
+    pub path_policy: PathPolicyConfig,
+    /// `network-policy-check` のWebFetch / WebSearch / Bashの接続先ドメインのルール
+    pub network_policy: NetworkPolicyConfig,
+    /// `subagent-slack` のサブエージェントの種類ごとの通知とスレッド返信
+    pub subagents: SubagentConfig,
+    /// `context-notification` の通知する使用率と自動圧縮のトークン数
+    pub context: ContextConfig,
+    /// `session-context` のプロジェクトごとの有効・無効、チームのメモ、文字数の上限
+    pub session_context: SessionContextConfig,
+    /// `user-prompt-slack` のシークレットのブロックとマクロの展開
+    pub prompt: PromptConfig,
+    /// `permission-notification` の入力待ちの通知をSlack・メンションに強めるまでの時間
+    pub idle: IdleConfig,
+    /// `claude-hooks serve` のポート、表示するセッションの期間、更新の間隔
+    pub dashboard: DashboardConfig,
+}
+
+impl HooksConfig {
+    /// 設定ファイルのパス（`CLAUDE_HOOKS_CONFIG` で上書き可能）
+    pub fn path(system: &dyn System) -> Option<PathBuf> {
+        if let Some(path) = system.env_var("CLAUDE_HOOKS_CONFIG").filter(|p| !p.is_empty()) {
+            return Some(PathBuf::from(path));
+        }
+        Some(system.home_dir()?.join(".claude/claude-hooks.json"))
+    }
+
+    pub fn load_from(system: &dyn System, path: &Path) -> io::Result<HooksConfig> {
+        let content = system.read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// 設定を読み込む（ファイルが無い・壊れている場合はデフォルト値）
+    pub fn load(system: &dyn System) -> HooksConfig {
+        let Some(path) = HooksConfig::path(system) else {
+            return HooksConfig::default();
+        };
+        match HooksConfig::load_from(system, &path) {
+            Ok(config) => config,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HooksConfig::default(),
+            Err(err) => {
+                eprintln!("Failed to load {}: {}", path.display(), err);
+                HooksConfig::default()
+            }
+        }
+    }
+
+    /// プロセス内で1度だけ読み込んだ設定（`claude-hooks` のサブコマンド用。フックは `Sinks::config` を使う）
+    pub fn global() -> &'static HooksConfig {
+        static CONFIG: OnceLock<HooksConfig> = OnceLock::new();
+        CONFIG.get_or_init(|| HooksConfig::load(&RealSystem))
+    }
+}
@@ -0,0 +1,180 @@
+use crate::mcp::McpServerRegistry;
+use crate::{session_file_name, tf, ToolSummary};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 1トークンあたりの文字数の目安（ツール結果のトークン数の推定に使う）
+const CHARS_PER_TOKEN: usize = 4;
+
+// ===== 設定 =====
+
+/// `claude-hooks.json` の `context`
+///
+/// 使用率は `statusline.js` と同じく、自動圧縮が始まるトークン数に対する割合。
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ContextConfig {
+    /// 通知する使用率（%）
+    pub thresholds: Vec<u8>,
+    /// 自動圧縮が始まるトークン数
+    pub compaction_tokens: u64,
+    /// 圧縮されそうなツール結果を表示する件数
+    pub top_results: usize,
+}
+
+impl Default for ContextConfig {
+    fn default() -> ContextConfig {
+        ContextConfig {
+            thresholds: vec![70, 90],
+            compaction_tokens: 160_000,
+            top_results: 5,
+        }
+    }
+}
+
+impl ContextConfig {
+    /// 使用トークン数の割合（%、100で頭打ち）
+    pub fn percentage(&self, tokens: u64) -> u8 {
+        if self.compaction_tokens == 0 {
+            return 100;
+        }
+        (tokens.saturating_mul(100) / self.compaction_tokens).min(100) as u8
+    }
+
+    /// `previous`（前回通知した使用率）から `current` までの間に超えた最も高い閾値
+    pub fn crossed_threshold(&self, previous: Option<u8>, current: u8) -> Option<u8> {
+        self.thresholds
+            .iter()
+            .copied()
+            .filter(|&t| current >= t && previous.is_none_or(|p| p < t))
+            .max()
+    }
+}
+
+// ===== トランスクリプトの解析 =====
+
+/// 最後のアシスタントメッセージの `usage` から求めた現在のコンテキストのトークン数
+pub fn context_tokens(transcript: &str) -> Option<u64> {
+    transcript
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .find_map(|entry| entry.pointer("/message/usage").cloned())
+        .map(|usage| {
+            ["input_tokens", "output_tokens", "cache_creation_input_tokens", "cache_read_input_tokens"]
+                .iter()
+                .filter_map(|key| usage.get(*key).and_then(|v| v.as_u64()))
+                .sum()
+        })
+}
+
+/// 圧縮で失われそうなツール結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolResultSize {
+    /// ツールの1行表示（`📖 ファイル読み込み: main.rs`）
+    pub label: String,
+    /// 推定トークン数
+    pub tokens: u64,
+}
+
+/// トークン数（推定）の大きいツール結果を `limit` 件返す
+pub fn largest_tool_results(transcript: &str, cwd: &str, registry: &McpServerRegistry, limit: usize) -> Vec<ToolResultSize> {
+    let mut tool_uses: HashMap<String, (String, Value)> = HashMap::new();
+    let mut results: Vec<ToolResultSize> = Vec::new();
+
+    for entry in transcript.lines().filter_map(|line| serde_json::from_str::<Value>(line).ok()) {
+        let Some(content) = entry.pointer("/message/content").and_then(|c| c.as_array()) else {
+            continue;
+        };
+        for item in content {
+            match item.get("type").and_then(|v| v.as_str()) {
+                Some("tool_use") => {
+                    let id = item.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+                    let name = item.get("name").and_then(|v| v.as_str()).unwrap_or_default();
+                    let input = item.get("input").cloned().unwrap_or(Value::Null);
+                    tool_uses.insert(id.to_string(), (name.to_string(), input));
+                }
+                Some("tool_result") => {
+                    let id = item.get("tool_use_id").and_then(|v| v.as_str()).unwrap_or_default();
+                    let chars = result_chars(item.get("content").unwrap_or(&Value::Null));
+                    let label = match tool_uses.get(id) {
+                        Some((name, input)) => ToolSummary::build(name, Some(input), cwd, registry).short_line(),
+                        None => id.to_string(),
+                    };
+                    results.push(ToolResultSize {
+                        label,
+                        tokens: chars.div_ceil(CHARS_PER_TOKEN) as u64,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // 同じトークン数の場合は古い結果（先に圧縮される）を先にする
+    results.sort_by_key(|r| Reverse(r.tokens));
+    results.truncate(limit);
+    results
+}
+
+/// tool_resultの `content`（文字列、またはtextブロックの配列）の文字数
+fn result_chars(content: &Value) -> usize {
+    match content {
+        Value::String(text) => text.chars().count(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|v| v.as_str()))
+            .map(|text| text.chars().count())
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// `142k`（1000未満はそのまま）
+pub fn format_tokens(tokens: u64) -> String {
+    if tokens < 1000 {
+        tokens.to_string()
+    } else {
+        format!("{}k", (tokens + 500) / 1000)
+    }
+}
+
+/// Slackに表示する圧縮されそうなツール結果の一覧
+pub fn results_text(results: &[ToolResultSize]) -> String {
+    results
+        .iter()
+        .map(|r| tf("context.result", &[("label", &r.label), ("tokens", &format_tokens(r.tokens))]))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ===== 通知済みの閾値 =====
+
+/// セッションごとに前回通知した使用率
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContextState {
+    pub notified: Option<u8>,
+}
+
+impl ContextState {
+    pub fn parse(content: &str) -> ContextState {
+        serde_json::from_str(content).unwrap_or_default()
+    }
+
+    /// 圧縮などで使用率が通知済みの閾値より下がった場合は、再び通知できるようにする
+    pub fn rearm(&mut self, current: u8, config: &ContextConfig) {
+        let level = config.crossed_threshold(None, current);
+        if self.notified.is_some_and(|notified| level.is_none_or(|l| l < notified)) {
+            self.notified = level;
+        }
+    }
+}
+
+/// `~/.claude/context-state/<session_id>.json`
+pub fn state_path(home: &Path, session_id: &str) -> PathBuf {
+    home.join(".claude/context-state")
+        .join(format!("{}.json", session_file_name(session_id)))
+}
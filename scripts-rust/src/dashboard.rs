@@ -0,0 +1,455 @@
+use crate::config::HooksConfig;
+use crate::context::context_tokens;
+use crate::idle;
+use crate::system::System;
+use crate::text::truncate_chars;
+use crate::timeline::{self, EventKind};
+use crate::{get_dir_name, t, user_prompt_text, TranscriptMessage};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::SystemTime;
+
+/// ダッシュボードのHTML（外部のスクリプト・スタイルは使わない）
+const DASHBOARD_HTML: &str = include_str!("dashboard_page.html");
+
+/// 表示するプロンプトの上限文字数
+const PROMPT_LIMIT: usize = 200;
+
+/// 変化が無い場合にコメント行を送る間隔（更新の回数）
+const KEEPALIVE_TICKS: u32 = 15;
+
+/// 同時に処理する接続の上限（Server-Sent Eventsの接続はタブを閉じるまで続く）
+const MAX_CONNECTIONS: usize = 32;
+
+/// リクエストの読み込み・レスポンスの書き込みのタイムアウト
+const IO_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+// ===== 設定 =====
+
+/// `claude-hooks.json` の `dashboard`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct DashboardConfig {
+    /// `127.0.0.1` で待ち受けるポート
+    pub port: u16,
+    /// この時間（時間）以内に更新されたトランスクリプトを表示する
+    pub active_hours: u64,
+    /// Server-Sent Eventsで変化を確認する間隔（秒）
+    pub refresh_seconds: u64,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> DashboardConfig {
+        DashboardConfig {
+            port: 7878,
+            active_hours: 24,
+            refresh_seconds: 2,
+        }
+    }
+}
+
+// ===== セッションの状態 =====
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionState {
+    /// プロンプトの処理中（ツールの実行中を含む）
+    Working,
+    /// 権限リクエストの応答待ち
+    WaitingForPermission,
+    /// 入力待ち（`idle_prompt` の通知後）
+    Idle,
+    /// 最後の応答を返した
+    Done,
+}
+
+/// ダッシュボードの1行
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SessionStatus {
+    pub session_id: String,
+    pub cwd: String,
+    /// ディレクトリ名
+    pub project: String,
+    pub branch: Option<String>,
+    pub state: SessionState,
+    pub last_prompt: Option<String>,
+    pub context_tokens: Option<u64>,
+    /// 自動圧縮が始まるトークン数に対する割合（%）
+    pub context_percent: Option<u8>,
+    /// トランスクリプトの最終更新（RFC 3339）
+    pub updated_at: String,
+}
+
+/// トランスクリプトから分かる内容
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranscriptSummary {
+    pub cwd: Option<String>,
+    pub branch: Option<String>,
+    pub last_prompt: Option<String>,
+    /// 最後のエントリがツール呼び出し・プロンプト・ツール結果の場合はtrue、テキストの応答・中断の場合はfalse
+    pub working: bool,
+    /// 最後のエントリの `timestamp`
+    pub last_timestamp: Option<DateTime<FixedOffset>>,
+}
+
+/// トランスクリプトの作業ディレクトリ・ブランチ・最後のプロンプトと、処理中かどうか
+pub fn summarize_transcript(transcript: &str) -> TranscriptSummary {
+    let mut summary = TranscriptSummary::default();
+    for line in transcript.lines() {
+        let Ok(entry) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let text = |key: &str| entry.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(str::to_string);
+        summary.cwd = text("cwd").or(summary.cwd.take());
+        summary.branch = text("gitBranch").or(summary.branch.take());
+
+        let working = match entry.get("type").and_then(|v| v.as_str()) {
+            Some("user") => {
+                if let Some(prompt) = serde_json::from_value::<TranscriptMessage>(entry.clone())
+                    .ok()
+                    .as_ref()
+                    .and_then(user_prompt_text)
+                {
+                    summary.last_prompt = Some(prompt);
+                }
+                // Escで中断した場合は応答を返さずに止まる
+                !entry
+                    .pointer("/message/content")
+                    .is_some_and(|c| c.to_string().contains("[Request interrupted by user"))
+            }
+            Some("assistant") => {
+                let content = entry.pointer("/message/content");
+                content
+                    .and_then(|c| c.as_array())
+                    .is_some_and(|items| items.iter().any(|i| i.get("type").and_then(|v| v.as_str()) == Some("tool_use")))
+            }
+            _ => continue,
+        };
+        summary.working = working;
+        summary.last_timestamp = text("timestamp")
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .or(summary.last_timestamp);
+    }
+    summary
+}
+
+/// 状態を決める（入力待ちの状態ファイル、記録した権限リクエスト、トランスクリプトの順）
+pub fn session_state(summary: &TranscriptSummary, idle: bool, last_permission: Option<DateTime<FixedOffset>>) -> SessionState {
+    if idle {
+        return SessionState::Idle;
+    }
+    if !summary.working {
+        return SessionState::Done;
+    }
+    // 権限リクエストの後にトランスクリプトが進んでいなければ応答待ち
+    match (last_permission, summary.last_timestamp) {
+        (Some(permission), Some(last)) if permission >= last => SessionState::WaitingForPermission,
+        (Some(_), None) => SessionState::WaitingForPermission,
+        _ => SessionState::Working,
+    }
+}
+
+// ===== セッションの検出 =====
+
+/// トランスクリプトから分かる内容とコンテキストのトークン数
+#[derive(Debug, Clone)]
+struct TranscriptInfo {
+    summary: TranscriptSummary,
+    context_tokens: Option<u64>,
+}
+
+/// トランスクリプトの要約のキャッシュ（パスと更新時刻が同じ間は読み直さない）
+///
+/// `serve` のすべての接続で共有する。入力待ち・権限リクエストの状態ファイルは小さいため毎回読む。
+#[derive(Debug, Default)]
+pub struct SessionCache {
+    entries: Mutex<HashMap<PathBuf, (SystemTime, TranscriptInfo)>>,
+}
+
+impl SessionCache {
+    pub fn new() -> SessionCache {
+        SessionCache::default()
+    }
+
+    /// 読み込んだトランスクリプトの数
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, (SystemTime, TranscriptInfo)>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn transcript_info(&self, system: &dyn System, path: &Path, modified: SystemTime) -> Option<TranscriptInfo> {
+        if let Some((cached, info)) = self.lock().get(path) {
+            if *cached == modified {
+                return Some(info.clone());
+            }
+        }
+        let transcript = system.read_to_string(path).ok()?;
+        let info = TranscriptInfo {
+            summary: summarize_transcript(&transcript),
+            context_tokens: context_tokens(&transcript),
+        };
+        self.lock().insert(path.to_path_buf(), (modified, info.clone()));
+        Some(info)
+    }
+
+    /// 一覧に出なくなったトランスクリプトを捨てる
+    fn retain(&self, paths: &HashSet<PathBuf>) {
+        self.lock().retain(|path, _| paths.contains(path));
+    }
+}
+
+/// `~/.claude/projects/*/*.jsonl` のうち `active_hours` 以内に更新されたセッション（新しい順）
+pub fn discover_sessions(system: &dyn System, config: &HooksConfig, cache: &SessionCache) -> Vec<SessionStatus> {
+    let Some(home) = system.home_dir() else {
+        return Vec::new();
+    };
+    let now = system.now();
+    let active = Duration::try_hours(i64::try_from(config.dashboard.active_hours).unwrap_or(i64::MAX))
+        .and_then(|hours| now.checked_sub_signed(hours));
+
+    let mut sessions: Vec<SessionStatus> = Vec::new();
+    let mut seen = HashSet::new();
+    for project in system.read_dir(&home.join(".claude/projects")).unwrap_or_default() {
+        for path in system.read_dir(&project).unwrap_or_default() {
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Ok(modified) = system.modified(&path) else {
+                continue;
+            };
+            let updated = DateTime::<Utc>::from(modified).with_timezone(now.offset());
+            if active.is_some_and(|active| updated < active) {
+                continue;
+            }
+            let Some(info) = cache.transcript_info(system, &path, modified) else {
+                continue;
+            };
+            if let Some(status) = session_status(system, &home, &path, info, updated, config) {
+                sessions.push(status);
+            }
+            seen.insert(path);
+        }
+    }
+    cache.retain(&seen);
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    sessions
+}
+
+fn session_status(
+    system: &dyn System,
+    home: &Path,
+    path: &Path,
+    info: TranscriptInfo,
+    updated: DateTime<FixedOffset>,
+    config: &HooksConfig,
+) -> Option<SessionStatus> {
+    let session_id = path.file_stem()?.to_str()?.to_string();
+    let TranscriptInfo { summary, context_tokens: tokens } = info;
+    let cwd = summary.cwd.clone()?;
+
+    let idle = system.read_to_string(&idle::state_path(home, &session_id)).is_ok();
+    let last_permission = system
+        .read_to_string(&timeline::events_path(home, &session_id))
+        .ok()
+        .and_then(|log| {
+            timeline::recorded_events(&log)
+                .into_iter()
+                .filter(|e| e.kind == EventKind::Permission)
+                .filter_map(|e| e.time())
+                .max()
+        });
+
+    Some(SessionStatus {
+        session_id,
+        project: get_dir_name(&cwd),
+        cwd,
+        branch: summary.branch.clone(),
+        state: session_state(&summary, idle, last_permission),
+        last_prompt: summary.last_prompt.map(|p| truncate_chars(&p, PROMPT_LIMIT)),
+        context_tokens: tokens,
+        context_percent: tokens.map(|t| config.context.percentage(t)),
+        updated_at: updated.to_rfc3339(),
+    })
+}
+
+// ===== HTTP =====
+
+/// リクエスト行（`GET /events?x=1 HTTP/1.1`）のメソッドとパス（クエリは除く）
+pub fn parse_request_line(line: &str) -> Option<(String, String)> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+    let path = target.split('?').next().unwrap_or(target).to_string();
+    Some((method, path))
+}
+
+/// `Host` が `127.0.0.1` / `localhost` / `[::1]` か（DNSリバインディングで他のサイトから読まれないようにする）
+pub fn is_local_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    matches!(name, "127.0.0.1" | "localhost" | "[::1]")
+}
+
+/// Server-Sent Eventsの1イベント
+pub fn sse_message(data: &str) -> String {
+    let lines: String = data.lines().map(|line| format!("data: {}\n", line)).collect();
+    format!("{}\n", lines)
+}
+
+/// ダッシュボードのHTML（表示する文言を埋め込む）
+pub fn dashboard_html() -> String {
+    let labels = json!({
+        "title": t("dashboard.title"),
+        "empty": t("dashboard.empty"),
+        "directory": t("dashboard.column.directory"),
+        "branch": t("dashboard.column.branch"),
+        "state": t("dashboard.column.state"),
+        "prompt": t("dashboard.column.prompt"),
+        "context": t("dashboard.column.context"),
+        "updated": t("dashboard.column.updated"),
+        "disconnected": t("dashboard.disconnected"),
+        "working": t("dashboard.state.working"),
+        "waiting_for_permission": t("dashboard.state.waiting_for_permission"),
+        "idle": t("dashboard.state.idle"),
+        "done": t("dashboard.state.done"),
+    });
+    // `</script>` で閉じないように `<` をエスケープする
+    let labels = labels.to_string().replace('<', "\\u003c");
+    DASHBOARD_HTML
+        .replace("{{title}}", &t("dashboard.title").replace('<', "&lt;"))
+        .replace("{{labels}}", &labels)
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+/// `127.0.0.1:<port>` で待ち受け、接続ごとにスレッドで処理する（同時に `MAX_CONNECTIONS` まで）
+pub fn serve<S: System + Copy + Send + 'static>(system: S, config: &'static HooksConfig, port: u16) -> io::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    let cache = Arc::new(SessionCache::new());
+    let connections = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Connection failed: {}", err);
+                continue;
+            }
+        };
+        // ヘッダーを送らない・読まない接続でスレッドが止まったままにならないようにする
+        if let Err(err) = stream
+            .set_read_timeout(Some(IO_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(IO_TIMEOUT)))
+        {
+            eprintln!("Connection failed: {}", err);
+            continue;
+        }
+        if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            connections.fetch_sub(1, Ordering::SeqCst);
+            let _ = write_response(&mut stream, "503 Service Unavailable", "text/plain", "Too Many Connections");
+            continue;
+        }
+        let cache = Arc::clone(&cache);
+        let connections = Arc::clone(&connections);
+        thread::spawn(move || {
+            let result = handle_connection(stream, &system, config, &cache);
+            connections.fetch_sub(1, Ordering::SeqCst);
+            if let Err(err) = result {
+                // ブラウザがタブを閉じた場合など
+                if err.kind() != io::ErrorKind::BrokenPipe && err.kind() != io::ErrorKind::ConnectionReset {
+                    eprintln!("Request failed: {}", err);
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream, system: &dyn System, config: &HooksConfig, cache: &SessionCache) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut host = String::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("host") {
+                host = value.trim().to_string();
+            }
+        }
+    }
+
+    let Some((method, path)) = parse_request_line(&request_line) else {
+        return write_response(&mut stream, "400 Bad Request", "text/plain", "Bad Request");
+    };
+    if !is_local_host(&host) {
+        return write_response(&mut stream, "403 Forbidden", "text/plain", "Forbidden");
+    }
+    match (method.as_str(), path.as_str()) {
+        ("GET", "/") => write_response(&mut stream, "200 OK", "text/html; charset=utf-8", &dashboard_html()),
+        ("GET", "/api/sessions") => {
+            let body = serde_json::to_string(&discover_sessions(system, config, cache)).map_err(io::Error::other)?;
+            write_response(&mut stream, "200 OK", "application/json", &body)
+        }
+        ("GET", "/events") => stream_events(&mut stream, system, config, cache),
+        ("GET", _) => write_response(&mut stream, "404 Not Found", "text/plain", "Not Found"),
+        _ => write_response(&mut stream, "405 Method Not Allowed", "text/plain", "Method Not Allowed"),
+    }
+}
+
+/// セッションの一覧が変わるたびに送る（変化が無い間は接続を保つコメント行を送る）
+fn stream_events(stream: &mut TcpStream, system: &dyn System, config: &HooksConfig, cache: &SessionCache) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: keep-alive\r\n\r\n"
+    )?;
+    let interval = std::time::Duration::from_secs(config.dashboard.refresh_seconds.max(1));
+    let mut last = String::new();
+    let mut unchanged: u32 = 0;
+    loop {
+        let sessions = serde_json::to_string(&discover_sessions(system, config, cache)).map_err(io::Error::other)?;
+        if sessions != last {
+            stream.write_all(sse_message(&sessions).as_bytes())?;
+            last = sessions;
+            unchanged = 0;
+        } else {
+            unchanged += 1;
+            if unchanged.is_multiple_of(KEEPALIVE_TICKS) {
+                stream.write_all(b": keepalive\n\n")?;
+            }
+        }
+        stream.flush()?;
+        thread::sleep(interval);
+    }
+}
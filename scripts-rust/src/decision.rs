@@ -0,0 +1,54 @@
+use serde_json::{json, Value};
+
+// ===== PreToolUseの判定 =====
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    Allow,
+    Deny,
+    Ask,
+}
+
+impl Permission {
+    fn as_str(self) -> &'static str {
+        match self {
+            Permission::Allow => "allow",
+            Permission::Deny => "deny",
+            Permission::Ask => "ask",
+        }
+    }
+}
+
+/// PreToolUseフックが返す判定（`permissionDecision` と理由）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub permission: Permission,
+    pub reason: String,
+}
+
+impl Decision {
+    pub fn deny(reason: impl Into<String>) -> Decision {
+        Decision {
+            permission: Permission::Deny,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn ask(reason: impl Into<String>) -> Decision {
+        Decision {
+            permission: Permission::Ask,
+            reason: reason.into(),
+        }
+    }
+
+    /// 標準出力に書き出すフックの出力JSON
+    pub fn to_hook_output(&self) -> Value {
+        json!({
+            "hookSpecificOutput": {
+                "hookEventName": "PreToolUse",
+                "permissionDecision": self.permission.as_str(),
+                "permissionDecisionReason": self.reason,
+            }
+        })
+    }
+}
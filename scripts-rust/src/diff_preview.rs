@@ -0,0 +1,233 @@
+use crate::path_policy::{Access, PathPolicy};
+use crate::prompt;
+use crate::sinks::Sinks;
+use crate::slack::FIELD_TEXT_LIMIT;
+use crate::slack_markdown;
+use crate::text::truncate_chars;
+use crate::{get_relative_path, tf};
+use serde_json::Value;
+use unicode_segmentation::UnicodeSegmentation;
+use std::path::Path;
+
+/// プレビューに含める最大行数
+pub const MAX_PREVIEW_LINES: usize = 60;
+
+/// コードブロックのフェンス（"```\n" と "\n```"）の文字数
+const FENCE_CHARS: usize = 8;
+
+/// フェンス内の末尾に付ける省略した行数の1行（改行を含む）に確保する文字数
+const OMITTED_LINE_CHARS: usize = 40;
+
+/// フィールドのラベル（"Diff Preview (TypeScript)"）と区切りに確保する文字数
+const LABEL_CHARS: usize = 50;
+
+/// プレビューに含める最大文字数（エスケープ後。フェンス・省略した行数・ラベルと合わせてSlackのフィールド1つに収まる長さ）
+pub const MAX_PREVIEW_CHARS: usize = FIELD_TEXT_LIMIT - FENCE_CHARS - OMITTED_LINE_CHARS - LABEL_CHARS;
+
+/// 変更箇所の前後に表示する行数
+const CONTEXT_LINES: usize = 2;
+
+// ===== 差分プレビュー =====
+
+/// Edit / MultiEdit / Write の権限リクエストで表示する差分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffPreview {
+    /// cwdからの相対パス
+    pub path: String,
+    /// 拡張子から推定した言語名
+    pub language: &'static str,
+    /// 上限までに切り詰めたunified diff
+    pub diff: String,
+    /// 上限を超えて省略した行数
+    pub omitted_lines: usize,
+}
+
+impl DiffPreview {
+    /// ツール入力から差分を作る（差分を表示できないツール・入力の場合は `None`）
+    ///
+    /// ディスク上のファイルに編集を適用できる場合はファイル全体に対する差分（行番号付き）、
+    /// できない場合は `old_string` と `new_string` の差分を返す。
+    /// `PathPolicy` で読み書きを拒否されたパスは表示せず、差分中のシークレットは `[REDACTED]` に置き換える。
+    pub fn from_tool(tool_name: &str, tool_input: &Value, cwd: &str, sinks: &dyn Sinks) -> Option<DiffPreview> {
+        let file_path = tool_input.get("file_path").and_then(|v| v.as_str())?;
+        let policy = PathPolicy::load(cwd, sinks);
+        if [Access::Read, Access::Write]
+            .into_iter()
+            .any(|access| policy.check_path(access, file_path, sinks).is_some())
+        {
+            return None;
+        }
+        let path = get_relative_path(file_path, cwd);
+        let current = sinks.read_file(Path::new(file_path)).ok();
+
+        let diff = match tool_name {
+            "Write" => {
+                let content = tool_input.get("content").and_then(|v| v.as_str())?;
+                unified_diff(current.as_deref().unwrap_or(""), content, &path)
+            }
+            "Edit" => edits_diff(current.as_deref(), &[tool_input], &path),
+            "MultiEdit" => {
+                let edits: Vec<&Value> = tool_input.get("edits")?.as_array()?.iter().collect();
+                edits_diff(current.as_deref(), &edits, &path)
+            }
+            _ => return None,
+        }?;
+
+        let diff = prompt::redact(&diff, &prompt::find_secrets(&diff));
+        let (diff, omitted_lines) = bound(&diff, MAX_PREVIEW_LINES, MAX_PREVIEW_CHARS);
+        Some(DiffPreview {
+            language: language_for(&path),
+            path,
+            diff,
+            omitted_lines,
+        })
+    }
+
+    /// Slackフィールドのラベル（例: "Diff Preview (Rust)"）
+    pub fn slack_label(&self) -> String {
+        tf("field.diff_preview", &[("language", self.language)])
+    }
+
+    /// Slackフィールドの値（コードブロック。省略した行数はフェンスの内側の最後の行）
+    pub fn slack_value(&self) -> String {
+        let mut code = slack_markdown::escape(&self.diff);
+        if self.omitted_lines > 0 {
+            code.push('\n');
+            code.push_str(&tf("slack.diff_omitted", &[("count", &self.omitted_lines.to_string())]));
+        }
+        format!("```\n{}\n```", code)
+    }
+}
+
+/// 複数の編集を順に適用した差分
+fn edits_diff(current: Option<&str>, edits: &[&Value], path: &str) -> Option<String> {
+    let edits: Vec<(&str, &str, bool)> = edits
+        .iter()
+        .filter_map(|edit| {
+            let old = edit.get("old_string")?.as_str()?;
+            let new = edit.get("new_string")?.as_str()?;
+            let replace_all = edit.get("replace_all").and_then(|v| v.as_bool()).unwrap_or(false);
+            Some((old, new, replace_all))
+        })
+        .collect();
+    if edits.is_empty() {
+        return None;
+    }
+
+    if let Some(current) = current {
+        if let Some(edited) = apply_edits(current, &edits) {
+            return unified_diff(current, &edited, path);
+        }
+    }
+
+    // ファイルが読めない・old_stringが見つからない場合は編集ごとの差分を並べる
+    // （断片の末尾に改行が無いことによる "No newline at end of file" は表示しない）
+    let diffs: Vec<String> = edits
+        .iter()
+        .filter_map(|(old, new, _)| unified_diff(&with_newline(old), &with_newline(new), path))
+        .collect();
+    (!diffs.is_empty()).then(|| diffs.join("\n"))
+}
+
+fn with_newline(text: &str) -> String {
+    if text.is_empty() || text.ends_with('\n') {
+        text.to_string()
+    } else {
+        format!("{}\n", text)
+    }
+}
+
+/// Edit / MultiEdit と同じ規則で編集を適用する（old_stringが見つからない場合は `None`）
+pub fn apply_edits(content: &str, edits: &[(&str, &str, bool)]) -> Option<String> {
+    let mut result = content.to_string();
+    for (old, new, replace_all) in edits {
+        if old.is_empty() || !result.contains(old) {
+            return None;
+        }
+        result = if *replace_all {
+            result.replace(old, new)
+        } else {
+            result.replacen(old, new, 1)
+        };
+    }
+    Some(result)
+}
+
+fn unified_diff(old: &str, new: &str, path: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+    let diff = similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string();
+    Some(diff.trim_end_matches('\n').to_string())
+}
+
+/// 行数・文字数（Slackのエスケープ後）の上限までで切り詰め、省略した行数を返す
+///
+/// 最初の行だけで上限を超える場合は、その行を上限まで切り詰めて残す。
+pub fn bound(diff: &str, max_lines: usize, max_chars: usize) -> (String, usize) {
+    let lines: Vec<&str> = diff.lines().collect();
+    let mut kept: Vec<String> = Vec::new();
+    let mut chars = 0;
+    for line in lines.iter().take(max_lines) {
+        let len = slack_markdown::escape(line).chars().count() + 1;
+        if chars + len > max_chars {
+            if kept.is_empty() {
+                kept.push(truncate_escaped(line, max_chars.saturating_sub(1)));
+            }
+            break;
+        }
+        chars += len;
+        kept.push(line.to_string());
+    }
+    (kept.join("\n"), lines.len() - kept.len())
+}
+
+/// エスケープ後の長さが `max_chars` 以内になるように省略記号付きで切り詰める
+fn truncate_escaped(line: &str, max_chars: usize) -> String {
+    // エスケープは文字単位なので、先頭から数えて収まる書記素の数まで切り詰める
+    let mut chars = 0;
+    let fits = line
+        .graphemes(true)
+        .take_while(|g| {
+            chars += slack_markdown::escape(g).chars().count();
+            chars <= max_chars
+        })
+        .count();
+    truncate_chars(line, fits)
+}
+
+/// 拡張子から言語名を推定する
+pub fn language_for(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "rs" => "Rust",
+        "ts" | "tsx" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "py" => "Python",
+        "go" => "Go",
+        "rb" => "Ruby",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "swift" => "Swift",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "hpp" => "C++",
+        "cs" => "C#",
+        "sh" | "bash" | "zsh" => "Shell",
+        "json" => "JSON",
+        "toml" => "TOML",
+        "yaml" | "yml" => "YAML",
+        "md" => "Markdown",
+        "html" => "HTML",
+        "css" | "scss" => "CSS",
+        "sql" => "SQL",
+        _ => "Text",
+    }
+}
@@ -0,0 +1,369 @@
+use crate::config::HooksConfig;
+use crate::install::{self, configured_commands, expand_home, HOOKS};
+use crate::slack::SlackMode;
+use crate::system::{RealSystem, System};
+use crate::{t, tf};
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 必要な外部コマンドと、インストールしていない場合の重大度・対処
+const REQUIRED_TOOLS: &[(&str, Status, &str)] = if cfg!(target_os = "macos") {
+    &[
+        // 無くてもSlackには通知できる
+        ("terminal-notifier", Status::Warning, "brew install terminal-notifier"),
+        ("git", Status::Error, "xcode-select --install"),
+    ]
+} else {
+    &[("git", Status::Error, "sudo apt install git")]
+};
+
+/// フックに例を渡してから終了を待つ時間
+const SAMPLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 各イベントの入力の例（`/tmp` は使い捨てのディレクトリに置き換えてフックのコマンドに渡す）
+const SAMPLE_EVENTS: &[(&str, &str)] = &[
+    (
+        "PreToolUse",
+        r#"{"session_id":"s","cwd":"/tmp","hook_event_name":"PreToolUse","tool_name":"Bash","tool_input":{"command":"ls"}}"#,
+    ),
+    (
+        "Notification",
+        r#"{"session_id":"s","cwd":"/tmp","hook_event_name":"Notification","notification_type":"permission_prompt","message":"waiting"}"#,
+    ),
+    (
+        "PermissionRequest",
+        r#"{"session_id":"s","cwd":"/tmp","hook_event_name":"PermissionRequest","tool_name":"Edit","tool_input":{"file_path":"/tmp/a"}}"#,
+    ),
+    (
+        "Stop",
+        r#"{"session_id":"s","cwd":"/tmp","hook_event_name":"Stop","transcript_path":"/tmp/t.jsonl"}"#,
+    ),
+    (
+        "UserPromptSubmit",
+        r#"{"session_id":"s","cwd":"/tmp","hook_event_name":"UserPromptSubmit","permission_mode":"default","prompt":"hello"}"#,
+    ),
+    (
+        "PostToolUse",
+        r#"{"session_id":"s","cwd":"/tmp","hook_event_name":"PostToolUse","permission_mode":"plan","tool_name":"ExitPlanMode","tool_input":{"plan":"x"},"tool_response":{},"tool_use_id":"t"}"#,
+    ),
+    (
+        "SubagentStop",
+        r#"{"session_id":"s","cwd":"/tmp","hook_event_name":"SubagentStop","transcript_path":"/tmp/t.jsonl","stop_hook_active":false,"agent_id":"a1","agent_transcript_path":"/tmp/a.jsonl"}"#,
+    ),
+    (
+        "PreCompact",
+        r#"{"session_id":"s","cwd":"/tmp","hook_event_name":"PreCompact","transcript_path":"/tmp/t.jsonl","trigger":"auto","custom_instructions":""}"#,
+    ),
+    (
+        "SessionStart",
+        r#"{"session_id":"s","cwd":"/tmp","hook_event_name":"SessionStart","transcript_path":"/tmp/t.jsonl","source":"startup"}"#,
+    ),
+    (
+        "SessionEnd",
+        r#"{"session_id":"s","cwd":"/tmp","hook_event_name":"SessionEnd","transcript_path":"/tmp/t.jsonl","reason":"prompt_input_exit"}"#,
+    ),
+];
+
+// ===== チェック結果 =====
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl Status {
+    fn emoji(self) -> &'static str {
+        match self {
+            Status::Ok => "✅",
+            Status::Warning => "⚠️",
+            Status::Error => "❌",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub status: Status,
+    pub label: String,
+    /// 問題がある場合の対処
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(label: String) -> Check {
+        Check {
+            status: Status::Ok,
+            label,
+            fix: None,
+        }
+    }
+
+    fn problem(status: Status, label: String, fix: impl Into<String>) -> Check {
+        Check {
+            status,
+            label,
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// チェックの実行環境
+#[derive(Debug, Clone)]
+pub struct DoctorContext {
+    pub settings_path: PathBuf,
+    pub config_path: Option<PathBuf>,
+    pub home: Option<String>,
+    /// コマンドを探す `PATH`
+    pub path_var: String,
+    pub slack: SlackMode,
+}
+
+impl DoctorContext {
+    pub fn from_env(system: &dyn System, settings_path: PathBuf) -> DoctorContext {
+        DoctorContext {
+            settings_path,
+            config_path: HooksConfig::path(system),
+            home: system.env_var("HOME"),
+            path_var: system.env_var("PATH").unwrap_or_default(),
+            slack: SlackMode::from_env(system),
+        }
+    }
+}
+
+// ===== チェック =====
+
+/// 全てのチェックを実行する
+pub fn run(context: &DoctorContext) -> Vec<Check> {
+    let mut checks = settings_checks(context);
+    checks.extend(tool_checks(context));
+    checks.push(slack_check(&context.slack));
+    checks.extend(config_check(context));
+    if let Ok(settings) = install::read_settings(&context.settings_path) {
+        checks.extend(sample_checks(&settings, context));
+    }
+    checks
+}
+
+/// settings.json のフックのコマンドが存在して実行できるか、クレートのフックが登録されているか
+fn settings_checks(context: &DoctorContext) -> Vec<Check> {
+    let path = context.settings_path.display().to_string();
+    let settings = match install::read_settings(&context.settings_path) {
+        Ok(settings) => settings,
+        Err(err) => {
+            let label = tf("doctor.settings_error", &[("path", &path), ("error", &err.to_string())]);
+            return vec![Check::problem(Status::Error, label, t("doctor.fix_install"))];
+        }
+    };
+    let mut checks = vec![Check::ok(tf("doctor.settings_ok", &[("path", &path)]))];
+    let home = context.home.as_deref();
+    let hooks = settings.get("hooks").and_then(|h| h.as_object());
+
+    for (event, groups) in hooks.into_iter().flatten() {
+        let groups = groups.as_array().map(|g| g.as_slice()).unwrap_or(&[]);
+        for command in configured_commands(groups) {
+            checks.push(command_check(event, command, context));
+        }
+    }
+
+    for spec in HOOKS {
+        let groups = hooks
+            .and_then(|h| h.get(spec.event))
+            .and_then(|g| g.as_array())
+            .map(|g| g.as_slice())
+            .unwrap_or(&[]);
+        let configured = configured_commands(groups).any(|c| {
+            let program = c.split_whitespace().next().unwrap_or("");
+            expand_home(program, home).file_name() == Some(spec.binary.as_ref())
+        });
+        if !configured {
+            let label = tf("doctor.hook_not_configured", &[("event", spec.event), ("binary", spec.binary)]);
+            checks.push(Check::problem(Status::Warning, label, t("doctor.fix_install")));
+        }
+    }
+    checks
+}
+
+fn command_check(event: &str, command: &str, context: &DoctorContext) -> Check {
+    let vars = [("event", event), ("command", command)];
+    let program = command.split_whitespace().next().unwrap_or("");
+    let path = resolve_program(program, context);
+    let is_crate_binary = is_crate_binary(program);
+
+    match path {
+        None => {
+            let fix = if is_crate_binary { t("doctor.fix_install").to_string() } else { t("doctor.fix_path").to_string() };
+            Check::problem(Status::Error, tf("doctor.hook_missing", &vars), fix)
+        }
+        Some(path) if !is_executable(&path) => Check::problem(
+            Status::Error,
+            tf("doctor.hook_not_executable", &vars),
+            format!("chmod +x {}", path.display()),
+        ),
+        Some(_) => Check::ok(tf("doctor.hook_ok", &vars)),
+    }
+}
+
+fn tool_checks(context: &DoctorContext) -> Vec<Check> {
+    REQUIRED_TOOLS
+        .iter()
+        .map(|(tool, status, fix)| match find_in_path(tool, &context.path_var) {
+            Some(path) => Check::ok(tf("doctor.tool_ok", &[("tool", tool), ("path", &path.display().to_string())])),
+            None => Check::problem(*status, tf("doctor.tool_missing", &[("tool", tool)]), *fix),
+        })
+        .collect()
+}
+
+fn slack_check(slack: &SlackMode) -> Check {
+    match slack {
+        SlackMode::Webhook { .. } => Check::ok(tf("doctor.slack_ok", &[("mode", "Webhook")])),
+        SlackMode::Bot { .. } => Check::ok(tf("doctor.slack_ok", &[("mode", "Bot")])),
+        SlackMode::Disabled => Check::problem(
+            Status::Warning,
+            t("doctor.slack_missing").to_string(),
+            "export CLAUDE_CODE_SLACK_WEBHOOK_URL=https://hooks.slack.com/services/...",
+        ),
+    }
+}
+
+/// `claude-hooks.json` がある場合は読み込めるか
+fn config_check(context: &DoctorContext) -> Option<Check> {
+    let path = context.config_path.as_ref().filter(|p| p.exists())?;
+    let display = path.display().to_string();
+    Some(match HooksConfig::load_from(&RealSystem, path) {
+        Ok(_) => Check::ok(tf("doctor.config_ok", &[("path", &display)])),
+        Err(err) => Check::problem(
+            Status::Error,
+            tf("doctor.config_error", &[("path", &display), ("error", &err.to_string())]),
+            t("doctor.fix_config"),
+        ),
+    })
+}
+
+/// 各イベントの入力の例を、settings.json に登録したこのクレートのフックのコマンドに渡し、正常に終了するか
+///
+/// 実際の設定・Slack・通知に影響しないよう、環境変数を空にし、使い捨てのHOMEと空のPATHで実行する。
+/// 見つからない・実行できないコマンドは `settings_checks` で報告済みのため飛ばす。
+fn sample_checks(settings: &Value, context: &DoctorContext) -> Vec<Check> {
+    let sandbox = env::temp_dir().join(format!("claude-hooks-doctor-{}", std::process::id()));
+    if let Err(err) = fs::create_dir_all(sandbox.join("bin")) {
+        eprintln!("Failed to create {}: {}", sandbox.display(), err);
+        return Vec::new();
+    }
+
+    let mut checks = Vec::new();
+    for (event, sample) in SAMPLE_EVENTS {
+        let groups = settings["hooks"][*event].as_array().map(|g| g.as_slice()).unwrap_or(&[]);
+        for command in configured_commands(groups) {
+            let mut words = command.split_whitespace();
+            let program = words.next().unwrap_or("");
+            let Some(path) = resolve_program(program, context).filter(|p| is_crate_binary(program) && is_executable(p)) else {
+                continue;
+            };
+            let args: Vec<&str> = words.collect();
+            let input = sample.replace("/tmp", &sandbox.to_string_lossy());
+            let vars = [("event", *event), ("command", command)];
+            checks.push(match run_sample(&path, &args, &input, &sandbox) {
+                Ok(()) => Check::ok(tf("doctor.sample_ok", &vars)),
+                Err(err) => Check::problem(
+                    Status::Error,
+                    tf("doctor.sample_error", &[("event", event), ("command", command), ("error", &err.to_string())]),
+                    t("doctor.fix_rebuild"),
+                ),
+            });
+        }
+    }
+
+    if let Err(err) = fs::remove_dir_all(&sandbox) {
+        eprintln!("Failed to remove {}: {}", sandbox.display(), err);
+    }
+    checks
+}
+
+/// フックのコマンドに入力を渡して実行し、終了コードが0でなければ標準エラー出力の最後の行を返す
+fn run_sample(program: &Path, args: &[&str], input: &str, sandbox: &Path) -> io::Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .env_clear()
+        .env("HOME", sandbox)
+        .env("PATH", sandbox.join("bin"))
+        .current_dir(sandbox)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // 入力を読まずに終了するフックもある
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    let deadline = Instant::now() + SAMPLE_TIMEOUT;
+    while child.try_wait()?.is_none() {
+        if Instant::now() >= deadline {
+            child.kill()?;
+            child.wait()?;
+            return Err(io::Error::new(io::ErrorKind::TimedOut, format!("timed out after {}s", SAMPLE_TIMEOUT.as_secs())));
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = stderr.lines().rfind(|l| !l.trim().is_empty()).map(|l| l.trim().to_string());
+    Err(io::Error::other(message.unwrap_or_else(|| output.status.to_string())))
+}
+
+/// settings.json のコマンドの実行ファイル（`~` を展開し、`/` を含まなければ `PATH` から探す）
+fn resolve_program(program: &str, context: &DoctorContext) -> Option<PathBuf> {
+    if program.contains('/') {
+        Some(expand_home(program, context.home.as_deref())).filter(|p| p.exists())
+    } else {
+        find_in_path(program, &context.path_var)
+    }
+}
+
+fn is_crate_binary(program: &str) -> bool {
+    HOOKS.iter().any(|spec| program.ends_with(&format!("/{}", spec.binary)))
+}
+
+/// `PATH` からコマンドを探す
+pub fn find_in_path(program: &str, path_var: &str) -> Option<PathBuf> {
+    env::split_paths(path_var)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata().is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+// ===== 表示 =====
+
+/// チェックリストとして表示する
+pub fn format_checklist(checks: &[Check]) -> String {
+    let mut lines = Vec::new();
+    for check in checks {
+        lines.push(format!("{} {}", check.status.emoji(), check.label));
+        if let Some(fix) = &check.fix {
+            lines.push(format!("   → {}", fix));
+        }
+    }
+    let count = |status: Status| checks.iter().filter(|c| c.status == status).count().to_string();
+    lines.push(String::new());
+    lines.push(tf(
+        "doctor.summary",
+        &[("errors", &count(Status::Error)), ("warnings", &count(Status::Warning))],
+    ));
+    lines.join("\n")
+}
@@ -0,0 +1,336 @@
+use crate::install::{expand_home, DEFAULT_BIN_DIR};
+use crate::system::System;
+use crate::{build_iterm2_url_scheme, detect_ide_bundle_id, detect_terminal_bundle_id, iterm2_osascript_args, latest_ide_lock};
+use std::io;
+use std::path::PathBuf;
+
+/// `x-claude://focus?...`
+pub const FOCUS_URL_PREFIX: &str = "x-claude://focus?";
+
+/// iTerm2Switch.app（`bin/setup-iterm2-url-handler.sh`）が処理するURL
+pub const ITERM2_URL_PREFIX: &str = "x-claude-iterm://switch?";
+
+/// Linuxで `x-claude://` を処理する `.desktop` ファイルの名前
+pub const DESKTOP_FILE_NAME: &str = "claude-hooks-focus.desktop";
+
+// ===== フォーカスする対象 =====
+
+/// Claude Codeが動いているペイン・ウィンドウ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FocusTarget {
+    /// tmuxのペイン（`TMUX_PANE`）とサーバーのソケット（`TMUX` の最初の要素）
+    Tmux { pane: String, socket: Option<String> },
+    /// kittyのウィンドウ（`KITTY_WINDOW_ID`）とリモート制御のソケット（`KITTY_LISTEN_ON`）
+    Kitty { window: String, listen_on: Option<String> },
+    /// WezTermのペイン（`WEZTERM_PANE`）
+    WezTerm { pane: String },
+    /// iTerm2のセッション（`ITERM_SESSION_ID` のGUID）
+    ITerm2 { guid: String },
+    /// IDEのワークスペース（CLIでフォルダーを開くと既存のウィンドウに移動する）
+    Ide { cli: String, workspace: String },
+}
+
+/// フォーカスする対象と、macOSでアクティブにするアプリ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Focus {
+    pub target: FocusTarget,
+    /// ターミナル・IDEのBundle ID
+    pub app: Option<String>,
+}
+
+impl Focus {
+    /// 環境変数とIDEのlockファイルから検出する（マルチプレクサーを外側のターミナルより優先する）
+    pub fn detect(system: &dyn System) -> Option<Focus> {
+        let env = |key: &str| system.env_var(key).filter(|v| !v.is_empty());
+
+        let target = if let Some(pane) = env("TMUX_PANE") {
+            let socket = env("TMUX").and_then(|tmux| tmux.split(',').next().map(str::to_string));
+            FocusTarget::Tmux { pane, socket }
+        } else if let Some(pane) = env("WEZTERM_PANE") {
+            FocusTarget::WezTerm { pane }
+        } else if let Some(window) = env("KITTY_WINDOW_ID") {
+            FocusTarget::Kitty {
+                window,
+                listen_on: env("KITTY_LISTEN_ON"),
+            }
+        } else if let Some(guid) = env("ITERM_SESSION_ID")
+            .and_then(|id| id.split(':').nth(1).map(str::to_string))
+            .filter(|guid| !guid.is_empty())
+        {
+            FocusTarget::ITerm2 { guid }
+        } else if env("TERM_PROGRAM").as_deref() == Some("vscode") {
+            let lock = latest_ide_lock(system)?;
+            FocusTarget::Ide {
+                cli: ide_cli(&lock.ide_name)?.to_string(),
+                workspace: lock.workspace_folders.into_iter().next()?,
+            }
+        } else {
+            return None;
+        };
+
+        let app = match target {
+            FocusTarget::Ide { .. } => detect_ide_bundle_id(system),
+            _ => detect_terminal_bundle_id(system),
+        };
+        Some(Focus { target, app })
+    }
+
+    /// SlackのボタンのURL（iTerm2はiTerm2Switch.appのURL）
+    pub fn url(&self) -> String {
+        let mut params: Vec<(&str, &str)> = match &self.target {
+            FocusTarget::Tmux { pane, socket } => {
+                let mut params = vec![("target", "tmux"), ("pane", pane.as_str())];
+                params.extend(socket.as_deref().map(|s| ("socket", s)));
+                params
+            }
+            FocusTarget::Kitty { window, listen_on } => {
+                let mut params = vec![("target", "kitty"), ("window", window.as_str())];
+                params.extend(listen_on.as_deref().map(|s| ("listen_on", s)));
+                params
+            }
+            FocusTarget::WezTerm { pane } => vec![("target", "wezterm"), ("pane", pane.as_str())],
+            FocusTarget::Ide { cli, workspace } => {
+                vec![("target", "ide"), ("cli", cli.as_str()), ("workspace", workspace.as_str())]
+            }
+            FocusTarget::ITerm2 { guid } => return format!("{}guid={}", ITERM2_URL_PREFIX, guid),
+        };
+        params.extend(self.app.as_deref().map(|app| ("app", app)));
+        let query: Vec<String> = params
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, encode_component(value)))
+            .collect();
+        format!("{}{}", FOCUS_URL_PREFIX, query.join("&"))
+    }
+
+    /// `url()` の逆変換
+    pub fn from_url(url: &str) -> Result<Focus, String> {
+        let invalid = |reason: &str| format!("invalid focus URL ({}): {}", reason, url);
+        let (query, iterm2) = match (url.strip_prefix(FOCUS_URL_PREFIX), url.strip_prefix(ITERM2_URL_PREFIX)) {
+            (Some(query), _) => (query, false),
+            (None, Some(query)) => (query, true),
+            (None, None) => return Err(invalid("unknown scheme")),
+        };
+        let mut params: Vec<(&str, String)> = Vec::new();
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            params.push((key, decode_component(value).ok_or_else(|| invalid("bad percent-encoding"))?));
+        }
+        let param = |key: &str| params.iter().find(|(k, _)| *k == key).map(|(_, v)| v.clone());
+        let required = |key: &str| param(key).filter(|v| !v.is_empty()).ok_or_else(|| invalid(&format!("missing {}", key)));
+        // URLは誰でも作れるため、コマンドの引数になる値は形式を確認する
+        let checked = |key: &str, valid: fn(&str) -> bool| {
+            required(key).and_then(|value| if valid(&value) { Ok(value) } else { Err(invalid(&format!("bad {}", key))) })
+        };
+
+        let target = if iterm2 {
+            FocusTarget::ITerm2 { guid: checked("guid", is_guid)? }
+        } else {
+            match required("target")?.as_str() {
+                "tmux" => FocusTarget::Tmux {
+                    pane: checked("pane", is_pane_id)?,
+                    socket: param("socket"),
+                },
+                "kitty" => FocusTarget::Kitty {
+                    window: checked("window", is_pane_id)?,
+                    listen_on: param("listen_on"),
+                },
+                "wezterm" => FocusTarget::WezTerm { pane: checked("pane", is_pane_id)? },
+                "iterm2" => FocusTarget::ITerm2 { guid: checked("guid", is_guid)? },
+                "ide" => FocusTarget::Ide {
+                    cli: checked("cli", |cli| IDE_CLIS.iter().any(|(_, known)| *known == cli))?,
+                    // `-` で始まる値はCLIのオプションとして解釈される
+                    workspace: checked("workspace", |workspace| workspace.starts_with('/'))?,
+                },
+                other => return Err(invalid(&format!("unknown target {}", other))),
+            }
+        };
+        Ok(Focus {
+            target,
+            app: param("app"),
+        })
+    }
+
+    /// フォーカスするために実行するコマンド（macOSでは先にアプリをアクティブにする）
+    pub fn commands(&self, macos: bool) -> Vec<Vec<String>> {
+        let argv = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        let mut commands = Vec::new();
+        if let (true, Some(app)) = (macos, &self.app) {
+            commands.push(argv(&["open", "-b", app]));
+        }
+        match &self.target {
+            FocusTarget::Tmux { pane, socket } => {
+                let mut tmux = vec!["tmux".to_string()];
+                if let Some(socket) = socket {
+                    tmux.extend(["-S".to_string(), socket.clone()]);
+                }
+                for command in ["select-window", "select-pane"] {
+                    let mut args = tmux.clone();
+                    args.extend(argv(&[command, "-t", pane]));
+                    commands.push(args);
+                }
+            }
+            FocusTarget::Kitty { window, listen_on } => {
+                let mut args = argv(&["kitty", "@"]);
+                if let Some(listen_on) = listen_on {
+                    args.extend(argv(&["--to", listen_on]));
+                }
+                args.extend(argv(&["focus-window", "--match", &format!("id:{}", window)]));
+                commands.push(args);
+            }
+            FocusTarget::WezTerm { pane } => commands.push(argv(&["wezterm", "cli", "activate-pane", "--pane-id", pane])),
+            FocusTarget::ITerm2 { guid } => {
+                let mut args = vec!["osascript".to_string()];
+                args.extend(iterm2_osascript_args(guid));
+                commands.push(args);
+            }
+            FocusTarget::Ide { cli, workspace } => commands.push(argv(&[cli, workspace])),
+        }
+        commands
+    }
+
+    /// terminal-notifierの `-execute` に渡すコマンド（`claude-hooks focus '<url>'`）
+    pub fn execute_command(&self, home: Option<&str>) -> String {
+        let program = expand_home(DEFAULT_BIN_DIR, home).join("claude-hooks");
+        format!("{} focus {}", shell_quote(&program.to_string_lossy()), shell_quote(&self.url()))
+    }
+}
+
+/// SlackのボタンのURL
+///
+/// macOSには `x-claude://` のハンドラーが無いため、iTerm2の中で動いている場合（iTerm2の中のtmuxなど）は
+/// iTerm2Switch.appのURLにする。
+pub fn button_url(system: &dyn System, macos: bool) -> Option<String> {
+    if macos {
+        if let Some(url) = build_iterm2_url_scheme(system) {
+            return Some(url);
+        }
+    }
+    Focus::detect(system).map(|focus| focus.url())
+}
+
+/// `ideName` に含まれる名前とIDEのCLI（フォルダーを開くコマンド）
+const IDE_CLIS: [(&str, &str); 4] = [("cursor", "cursor"), ("windsurf", "windsurf"), ("visual studio code", "code"), ("vscode", "code")];
+
+/// `ideName` からIDEのCLI
+fn ide_cli(ide_name: &str) -> Option<&'static str> {
+    let name = ide_name.to_ascii_lowercase();
+    IDE_CLIS.iter().find(|(key, _)| name.contains(key)).map(|(_, cli)| *cli)
+}
+
+/// iTerm2のセッションのGUID（`[A-Za-z0-9-]+`）
+fn is_guid(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// tmux・WezTermのペイン、kittyのウィンドウのID（`%?\d+`）
+fn is_pane_id(value: &str) -> bool {
+    let digits = value.strip_prefix('%').unwrap_or(value);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// `x-claude://focus?...` を開いたときの処理（`claude-hooks focus <url>`）
+pub fn focus(system: &dyn System, url: &str, macos: bool) -> Result<(), String> {
+    let focus = Focus::from_url(url)?;
+    for command in focus.commands(macos) {
+        let args: Vec<&str> = command[1..].iter().map(String::as_str).collect();
+        let output = system.run(&command[0], &args).map_err(|e| format!("{}: {}", command[0], e))?;
+        if !output.success {
+            return Err(format!("{} failed: {}", command.join(" "), output.stderr.trim()));
+        }
+    }
+    Ok(())
+}
+
+// ===== URLのエンコード =====
+
+/// クエリの値をパーセントエンコードする（RFC 3986の非予約文字以外）
+pub fn encode_component(value: &str) -> String {
+    let mut out = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// パーセントエンコードを戻す（`+` は空白として扱う。不正な場合はNone）
+pub fn decode_component(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = value.get(i + 1..i + 3)?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// シェルの単一引用符で囲む
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+// ===== Linuxの登録 =====
+
+/// `x-claude://` を `claude-hooks focus` で開く `.desktop` ファイルの内容
+pub fn desktop_entry(claude_hooks: &str) -> String {
+    [
+        "[Desktop Entry]",
+        "Type=Application",
+        "Name=Claude Hooks Focus",
+        "Comment=Focus the terminal pane or IDE window of a Claude Code session",
+        &format!("Exec={} focus %u", desktop_quote(claude_hooks)),
+        "Terminal=false",
+        "NoDisplay=true",
+        "MimeType=x-scheme-handler/x-claude;",
+        "",
+    ]
+    .join("\n")
+}
+
+/// `.desktop` ファイルの `Exec` の引数の引用（二重引用符で囲み、`"` `` ` `` `$` `\` をエスケープする）
+fn desktop_quote(value: &str) -> String {
+    let mut out = String::from('"');
+    for c in value.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+/// `~/.local/share/applications/` に `.desktop` ファイルを書き込み、`x-claude://` の既定のハンドラーにする
+pub fn register_desktop_handler(system: &dyn System, bin_dir: &str) -> io::Result<PathBuf> {
+    let home = system.home_dir();
+    let home = home.as_deref().and_then(|h| h.to_str());
+    let applications = expand_home("~/.local/share/applications", home);
+    let path = applications.join(DESKTOP_FILE_NAME);
+    let claude_hooks = expand_home(bin_dir, home).join("claude-hooks");
+    system.write_file(&path, &desktop_entry(&claude_hooks.to_string_lossy()))?;
+
+    let output = system.run("xdg-mime", &["default", DESKTOP_FILE_NAME, "x-scheme-handler/x-claude"])?;
+    if !output.success {
+        return Err(io::Error::other(format!("xdg-mime failed: {}", output.stderr.trim())));
+    }
+    // MIMEタイプのキャッシュの更新（コマンドが無い環境もある）
+    let _ = system.run("update-desktop-database", &[&applications.to_string_lossy()]);
+    Ok(path)
+}
@@ -0,0 +1,339 @@
+use crate::system::System;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// ===== Gitコンテキスト =====
+
+/// 1回のフック実行で収集するGitリポジトリの状態
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct GitContext {
+    /// ブランチ名（detached HEADの場合はNone）
+    pub branch: Option<String>,
+    /// HEADのコミットハッシュ（短縮形、コミットが無い場合はNone）
+    pub commit: Option<String>,
+    pub detached: bool,
+    pub staged: usize,
+    pub modified: usize,
+    pub untracked: usize,
+    pub conflicted: usize,
+    /// 追跡ブランチ（例: "origin/main"）
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    /// リンクされたworktreeの場合のworktree名
+    pub worktree: Option<String>,
+    /// リモートのWeb URL（例: "https://github.com/owner/repo"）
+    pub remote_url: Option<String>,
+}
+
+impl GitContext {
+    /// `cwd`のGit状態を収集する（Gitリポジトリでない場合はNone）
+    pub fn collect(system: &dyn System, cwd: &str) -> Option<GitContext> {
+        let status = run_git(system, cwd, &["status", "--porcelain=v2", "--branch"])?;
+        let mut ctx = parse_status_porcelain_v2(&status);
+
+        let dirs = ["rev-parse", "--path-format=absolute", "--git-dir", "--git-common-dir", "--show-toplevel"];
+        if let Some(dirs) = run_git(system, cwd, &dirs) {
+            ctx.worktree = parse_worktree_name(&dirs);
+        }
+
+        let remote = ctx
+            .upstream
+            .as_deref()
+            .and_then(|u| u.split('/').next())
+            .unwrap_or("origin")
+            .to_string();
+        ctx.remote_url = run_git(system, cwd, &["remote", "get-url", &remote])
+            .and_then(|url| remote_to_web_url(url.trim()));
+
+        Some(ctx)
+    }
+
+    /// 表示用のブランチ名（detached HEADの場合は短縮コミットハッシュ）
+    pub fn branch_label(&self) -> String {
+        match (&self.branch, &self.commit) {
+            (Some(branch), _) => branch.clone(),
+            (None, Some(commit)) => format!("detached@{}", commit),
+            (None, None) => "unknown".to_string(),
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.staged + self.modified + self.untracked + self.conflicted > 0
+    }
+
+    /// 変更状態と upstream との差分の短い要約（例: "+1 ~2 ?3 ↑1 ↓2"）
+    pub fn status_summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.staged > 0 {
+            parts.push(format!("+{}", self.staged));
+        }
+        if self.modified > 0 {
+            parts.push(format!("~{}", self.modified));
+        }
+        if self.untracked > 0 {
+            parts.push(format!("?{}", self.untracked));
+        }
+        if self.conflicted > 0 {
+            parts.push(format!("!{}", self.conflicted));
+        }
+        if self.ahead > 0 {
+            parts.push(format!("↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            parts.push(format!("↓{}", self.behind));
+        }
+        parts.join(" ")
+    }
+
+    /// ブランチ名・worktree・状態要約をまとめた1行表示（例: "feature (wt: fix) +1 ↑2"）
+    pub fn display(&self) -> String {
+        let mut s = self.branch_label();
+        if let Some(worktree) = &self.worktree {
+            s.push_str(&format!(" (wt: {})", worktree));
+        }
+        let summary = self.status_summary();
+        if !summary.is_empty() {
+            s.push(' ');
+            s.push_str(&summary);
+        }
+        s
+    }
+
+    /// 現在のブランチ（detached HEADの場合はコミット）のWeb URL
+    pub fn branch_url(&self) -> Option<String> {
+        let base = self.remote_url.as_ref()?;
+        if let Some(branch) = &self.branch {
+            let remote_branch = self
+                .upstream
+                .as_deref()
+                .and_then(|u| u.split_once('/').map(|(_, b)| b))
+                .unwrap_or(branch);
+            Some(format!("{}/tree/{}", base, remote_branch))
+        } else {
+            self.commit
+                .as_ref()
+                .map(|commit| format!("{}/commit/{}", base, commit))
+        }
+    }
+
+    /// 通知タイトル用のサフィックス（例: " [main]"）
+    pub fn title_suffix(ctx: Option<&GitContext>) -> String {
+        ctx.map(|c| format!(" [{}]", c.branch_label()))
+            .unwrap_or_default()
+    }
+
+    /// デスクトップ通知のサブタイトル用プレフィックス（例: "[main +1 ~2] "）
+    pub fn subtitle_prefix(ctx: Option<&GitContext>) -> String {
+        ctx.map(|c| {
+            let summary = c.status_summary();
+            if summary.is_empty() {
+                format!("[{}] ", c.branch_label())
+            } else {
+                format!("[{} {}] ", c.branch_label(), summary)
+            }
+        })
+        .unwrap_or_default()
+    }
+
+    /// Slackの"Branch"フィールド用の値（ブランチURLがあればリンク化）
+    pub fn slack_field(ctx: Option<&GitContext>) -> String {
+        let ctx = match ctx {
+            Some(c) => c,
+            None => return crate::t("fallback.not_available").to_string(),
+        };
+        let label = match ctx.branch_url() {
+            Some(url) => format!("<{}|{}>", url, ctx.branch_label()),
+            None => ctx.branch_label(),
+        };
+        let mut value = label;
+        if let Some(worktree) = &ctx.worktree {
+            value.push_str(&format!(" (wt: {})", worktree));
+        }
+        let summary = ctx.status_summary();
+        if !summary.is_empty() {
+            value.push_str(&format!("\n`{}`", summary));
+        }
+        value
+    }
+}
+
+/// `git -C <cwd> <args>` の標準出力（失敗した場合はNone）
+fn run_git(system: &dyn System, cwd: &str, args: &[&str]) -> Option<String> {
+    let mut command = vec!["-C", cwd];
+    command.extend_from_slice(args);
+    system
+        .run("git", &command)
+        .ok()
+        .filter(|output| output.success)
+        .map(|output| output.stdout)
+}
+
+/// `git status --porcelain=v2 --branch` の出力を解析する
+pub fn parse_status_porcelain_v2(output: &str) -> GitContext {
+    let mut ctx = GitContext::default();
+
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => {
+                    ctx.commit = Some(value.chars().take(7).collect());
+                }
+                "branch.head" => {
+                    if value == "(detached)" {
+                        ctx.detached = true;
+                    } else {
+                        ctx.branch = Some(value.to_string());
+                    }
+                }
+                "branch.upstream" => ctx.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for part in value.split_whitespace() {
+                        if let Some(n) = part.strip_prefix('+') {
+                            ctx.ahead = n.parse().unwrap_or(0);
+                        } else if let Some(n) = part.strip_prefix('-') {
+                            ctx.behind = n.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let mut fields = line.splitn(3, ' ');
+        match fields.next() {
+            Some("1") | Some("2") => {
+                let xy = fields.next().unwrap_or("..");
+                let mut chars = xy.chars();
+                if chars.next().is_some_and(|c| c != '.') {
+                    ctx.staged += 1;
+                }
+                if chars.next().is_some_and(|c| c != '.') {
+                    ctx.modified += 1;
+                }
+            }
+            Some("u") => ctx.conflicted += 1,
+            Some("?") => ctx.untracked += 1,
+            _ => {}
+        }
+    }
+
+    ctx
+}
+
+/// `git rev-parse --path-format=absolute --git-dir --git-common-dir --show-toplevel` の出力からworktree名を求める
+pub fn parse_worktree_name(output: &str) -> Option<String> {
+    let mut lines = output.lines();
+    let git_dir = lines.next()?;
+    let common_dir = lines.next()?;
+    let toplevel = lines.next()?;
+    if Path::new(git_dir) == Path::new(common_dir) {
+        return None;
+    }
+
+    Path::new(toplevel)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|s| s.to_string())
+}
+
+/// リモートURL（SSH/HTTPS）をブラウザで開けるURLに変換する
+///
+/// `file://` やローカルパス、ドメインを含まないSSHのエイリアスなど、Webのホストが無いリモートはNone。
+pub fn remote_to_web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim().trim_end_matches('/');
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+
+    let (host, path) = if let Some(rest) = remote
+        .strip_prefix("https://")
+        .or_else(|| remote.strip_prefix("http://"))
+        .or_else(|| remote.strip_prefix("ssh://"))
+        .or_else(|| remote.strip_prefix("git://"))
+    {
+        let (authority, path) = rest.split_once('/')?;
+        // user@host:port から host のみを取り出す
+        let host = authority.rsplit('@').next()?;
+        let host = host.split(':').next()?;
+        (host.to_string(), path.to_string())
+    } else if remote.contains("://") {
+        // file:// などWebで開けないスキーム
+        return None;
+    } else if let Some((authority, path)) = remote.split_once(':') {
+        // scp形式: git@github.com:owner/repo
+        if authority.contains('/') {
+            return None;
+        }
+        let host = authority.rsplit('@').next()?;
+        (host.to_string(), path.trim_start_matches('/').to_string())
+    } else {
+        return None;
+    };
+
+    // `C:\repo` や `myalias:repo` のようにドメインを含まないホストは除く
+    if !host.contains('.') || path.is_empty() {
+        return None;
+    }
+
+    Some(format!("https://{}/{}", host, path))
+}
+
+// ===== 差分統計 =====
+
+/// `git diff --numstat` の1ファイル分
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DiffStatEntry {
+    /// リポジトリルートからの相対パス
+    pub path: String,
+    /// 追加行数（バイナリファイルの場合はNone）
+    pub added: Option<usize>,
+    /// 削除行数（バイナリファイルの場合はNone）
+    pub removed: Option<usize>,
+}
+
+/// HEADに対するworktreeの差分（staged + unstaged）と未追跡のファイルを取得する
+pub fn diff_numstat(system: &dyn System, cwd: &str) -> Option<Vec<DiffStatEntry>> {
+    let output = run_git(system, cwd, &["diff", "--numstat", "--no-renames", "HEAD"])
+        // コミットがまだ無いリポジトリではindexとの差分にフォールバック
+        .or_else(|| run_git(system, cwd, &["diff", "--numstat", "--no-renames"]))?;
+    let mut entries = parse_numstat(&output);
+
+    // `git diff` に含まれない未追跡のファイル（追加行数はファイルの行数）
+    let root = toplevel(system, cwd)?;
+    let untracked = ["ls-files", "--others", "--exclude-standard", "--full-name", "-z"];
+    let untracked = run_git(system, cwd, &untracked).unwrap_or_default();
+    for path in untracked.split('\0').filter(|p| !p.is_empty()) {
+        let lines = system.read_to_string(&Path::new(&root).join(path)).ok().map(|c| c.lines().count());
+        entries.push(DiffStatEntry {
+            path: path.to_string(),
+            added: lines,
+            removed: lines.map(|_| 0),
+        });
+    }
+    Some(entries)
+}
+
+/// `git diff --numstat` の出力を解析する
+pub fn parse_numstat(output: &str) -> Vec<DiffStatEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let added = fields.next()?;
+            let removed = fields.next()?;
+            let path = fields.next()?;
+            Some(DiffStatEntry {
+                path: path.to_string(),
+                added: added.parse().ok(),
+                removed: removed.parse().ok(),
+            })
+        })
+        .collect()
+}
+
+/// リポジトリのルートディレクトリを取得する
+pub fn toplevel(system: &dyn System, cwd: &str) -> Option<String> {
+    run_git(system, cwd, &["rev-parse", "--show-toplevel"]).map(|s| s.trim().to_string())
+}
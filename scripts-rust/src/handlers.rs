@@ -0,0 +1,1096 @@
+use crate::changes::{session_edited_files, ChangeSummary};
+use crate::config::HooksConfig;
+use crate::context::{self, ContextState};
+use crate::i18n::Locale;
+use crate::idle::{self, IdleLevel, IdleState};
+use crate::mcp::McpServerRegistry;
+use crate::network_policy::{self, NetworkPolicy};
+use crate::path_policy::{Access, PathContext, PathPolicy};
+use crate::prompt::{self, Macro, PromptSettings};
+use crate::replay;
+use crate::session_context;
+use crate::slack;
+use crate::system::{RealSystem, System};
+use crate::sinks::{DesktopNotification, RealSinks, SlackPost, Sinks};
+use crate::subagent::{self, SubagentReport};
+use crate::timeline::{self, EventKind, Timeline, TimelineEvent};
+use crate::tool_display::ToolDisplayRegistry;
+use crate::{
+    bash_risk, diff_preview, extract_questions_with_options, format_log_entry, get_dir_name, last_assistant_message,
+    last_user_prompt, parse_log_entries, plan, t, tf, GitContext, HookInput, PostToolUseInput, SessionEndInput, StopHookInput, SubagentStopInput,
+    ToolSummary, UserPromptSubmitInput, TASK_COMPLETE_LOG,
+};
+use chrono::{DateTime, FixedOffset};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// 実行したフックの標準入力を記録するディレクトリを指定する環境変数
+pub const RECORD_DIR_ENV: &str = "CLAUDE_HOOKS_RECORD_DIR";
+
+/// ハンドラーの名前（バイナリ名と同じ）
+pub const HANDLERS: &[&str] = &[
+    "permission-notification",
+    "task-complete-notification",
+    "format-tool-display",
+    "user-prompt-slack",
+    "askuser-answer-slack",
+    "exitplanmode-slack",
+    "askuser-question-slack",
+    "path-policy-check",
+    "network-policy-check",
+    "subagent-slack",
+    "context-notification",
+    "session-context",
+    "session-end-summary",
+];
+
+// ===== エントリーポイント =====
+
+/// バイナリの `main`: 標準入力を読み込み、実際の環境でハンドラーを実行する
+///
+/// `CLAUDE_HOOKS_RECORD_DIR` が設定されている場合は入力をフィクスチャとして記録する。
+pub fn main(name: &str) -> io::Result<()> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+
+    let sinks = RealSinks::new(RealSystem);
+    Locale::init_from(sinks.config(), &sinks.system);
+    if let Some(dir) = sinks.system.env_var(RECORD_DIR_ENV).filter(|d| !d.is_empty()) {
+        let config_path = HooksConfig::path(&sinks.system);
+        if let Err(err) = replay::record(Path::new(&dir), name, &input, &sinks, config_path.as_deref()) {
+            eprintln!("Failed to record fixture: {}", err);
+        }
+    }
+    run(name, &input, &sinks)
+}
+
+/// 名前でハンドラーを選んで実行する
+pub fn run(name: &str, input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    match name {
+        "permission-notification" => permission_notification(input, sinks),
+        "task-complete-notification" => task_complete_notification(input, sinks),
+        "format-tool-display" => format_tool_display(input, sinks),
+        "user-prompt-slack" => user_prompt_slack(input, sinks),
+        "askuser-answer-slack" => askuser_answer_slack(input, sinks),
+        "exitplanmode-slack" => exitplanmode_slack(input, sinks),
+        "askuser-question-slack" => askuser_question_slack(input, sinks),
+        "path-policy-check" => path_policy_check(input, sinks),
+        "network-policy-check" => network_policy_check(input, sinks),
+        "subagent-slack" => subagent_slack(input, sinks),
+        "context-notification" => context_notification(input, sinks),
+        "session-context" => session_context(input, sinks),
+        "session-end-summary" => session_end_summary(input, sinks),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("unknown handler: {}", name))),
+    }
+}
+
+fn parse<T: DeserializeOwned>(input: &str) -> io::Result<T> {
+    serde_json::from_str(input).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Slackに送信する（失敗はログに出すだけで、送信できたかを返す）
+fn post_slack(sinks: &dyn Sinks, post: SlackPost) -> bool {
+    match sinks.post_slack(&post.with_button(sinks.focus_url())) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("Slack notification failed: {}", err);
+            false
+        }
+    }
+}
+
+// ===== Notification / PermissionRequest =====
+
+pub fn permission_notification(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: HookInput = parse(input)?;
+    if input.notification_type.as_deref() == Some("idle_prompt") {
+        return idle_notification(input, sinks);
+    }
+
+    // ディレクトリ名取得
+    let dir_name = get_dir_name(&input.cwd);
+
+    // Gitコンテキスト取得
+    let git = sinks.git_context(&input.cwd);
+    let branch_suffix = GitContext::title_suffix(git.as_ref());
+
+    // ブランチ名のサブタイトル用プレフィックス
+    let branch_prefix = GitContext::subtitle_prefix(git.as_ref());
+
+    // Slack用のツールの説明（コマンドはコードブロックで表示）、差分プレビュー、Bashコマンドのリスク
+    let mut tool_details: Option<String> = None;
+    let mut diff_preview: Option<diff_preview::DiffPreview> = None;
+    let mut risk: Option<bash_risk::RiskAssessment> = None;
+    let mut permission: Option<TimelineEvent> = None;
+
+    // 通知タイプに応じてメッセージを生成
+    let (title, subtitle, message) = match input.notification_type.as_deref() {
+        Some("permission_prompt") | None => {
+            // ツール実行の許可リクエスト（従来の動作）
+            if let (Some(tool_name), Some(tool_input)) = (&input.tool_name, &input.tool_input) {
+                let summary = ToolSummary::build(
+                    tool_name,
+                    Some(tool_input),
+                    &input.cwd,
+                    &McpServerRegistry::from_config(sinks.config()),
+                );
+                tool_details = Some(summary.slack_text(tool_name));
+                permission = Some(TimelineEvent::new(sinks.now(), EventKind::Permission, &summary.short_line()));
+                diff_preview = diff_preview::DiffPreview::from_tool(tool_name, tool_input, &input.cwd, sinks);
+                if tool_name == "Bash" {
+                    if let Some(command) = tool_input.get("command").and_then(|v| v.as_str()) {
+                        risk = Some(bash_risk::analyze(command, &input.cwd, sinks));
+                    }
+                }
+                let title = tf("notify.permission.title", &[("dir", &dir_name)]);
+                let subtitle = match &risk {
+                    Some(risk) => format!("{}{} · {}", branch_prefix, summary.title(), risk.badge()),
+                    None => format!("{}{}", branch_prefix, summary.title()),
+                };
+                (title, subtitle, summary.long_text(tool_name))
+            } else {
+                // tool_nameもtool_inputもない場合はスキップ（通知を送らない）
+                return Ok(());
+            }
+        }
+        Some(other_type) => {
+            // その他の通知タイプ
+            let title = tf("notify.other.title", &[("dir", &dir_name)]);
+            let subtitle = format!("{}📢 {}", branch_prefix, other_type);
+            let message = input.message.unwrap_or_else(|| t("notify.other.message").to_string());
+            (title, subtitle, message)
+        }
+    };
+
+    // SessionEndのタイムライン用に権限リクエストを記録する（トランスクリプトには残らない）
+    if let (Some(event), Some(home)) = (&permission, sinks.home()) {
+        let line = serde_json::to_string(event).map_err(io::Error::other)?;
+        if let Err(err) = sinks.append_file(&timeline::events_path(&home, &input.session_id), &format!("{}\n", line)) {
+            eprintln!("Failed to record permission request: {}", err);
+        }
+    }
+
+    // 通知送信
+    sinks.notify(&DesktopNotification {
+        title,
+        subtitle: subtitle.clone(),
+        message: message.clone(),
+        sound: "Glass".to_string(),
+    })?;
+
+    // Slack通知送信
+    let slack_title_base = match input.notification_type.as_deref() {
+        Some("permission_prompt") | None => t("slack.title.permission"),
+        _ => t("slack.title.notification"),
+    };
+    let slack_title = format!("{}{}", slack_title_base, branch_suffix);
+
+    let branch_display = GitContext::slack_field(git.as_ref());
+    let diff_field = diff_preview.as_ref().map(|d| (d.slack_label(), d.slack_value()));
+    let risk_field = risk.as_ref().map(|r| r.slack_field());
+    let mut slack_fields = vec![
+        (t("field.session_id"), input.session_id.as_str()),
+        (t("field.directory"), dir_name.as_str()),
+        (t("field.branch"), branch_display.as_str()),
+        (t("field.type"), subtitle.as_str()),
+        (t("field.message"), tool_details.as_deref().unwrap_or(&message)),
+    ];
+    if let Some(value) = &risk_field {
+        slack_fields.push((t("field.risk"), value.as_str()));
+    }
+    if let Some((label, value)) = &diff_field {
+        slack_fields.push((label.as_str(), value.as_str()));
+    }
+
+    post_slack(sinks, SlackPost::new(slack_title, &slack_fields));
+    Ok(())
+}
+
+// ===== Notification（idle_prompt） =====
+
+/// アイドル通知（60秒以上待機）: デスクトップに通知し、入力が無いまま時間が経つとSlack、メンションと強める
+///
+/// 状態は `~/.claude/idle-state/` に保存し、次の段階はバックグラウンドの `claude-hooks idle-watch` が通知する。
+fn idle_notification(input: HookInput, sinks: &dyn Sinks) -> io::Result<()> {
+    let dir_name = get_dir_name(&input.cwd);
+    let git = sinks.git_context(&input.cwd);
+    let branch_prefix = GitContext::subtitle_prefix(git.as_ref());
+    let message = input.message.unwrap_or_else(|| t("notify.idle.message").to_string());
+
+    sinks.notify(&DesktopNotification {
+        title: tf("notify.idle.title", &[("dir", &dir_name)]),
+        subtitle: format!("{}{}", branch_prefix, t("notify.idle.subtitle")),
+        message: message.clone(),
+        sound: "Glass".to_string(),
+    })?;
+
+    let now = sinks.now();
+    let mut state = IdleState {
+        session_id: input.session_id,
+        cwd: input.cwd,
+        since: now.to_rfc3339(),
+        level: IdleLevel::Desktop,
+        message,
+        transcript_path: input.transcript_path,
+    };
+    // Slackまでの時間が0分の場合はすぐに送る
+    escalate_idle(&mut state, now, sinks);
+
+    let Some(home) = sinks.home() else {
+        return Ok(());
+    };
+    let content = serde_json::to_string_pretty(&state).map_err(io::Error::other)?;
+    if let Err(err) = sinks.write_file(&idle::state_path(&home, &state.session_id), &content) {
+        eprintln!("Failed to save idle state: {}", err);
+        return Ok(());
+    }
+    if sinks.config().idle.next_step(now, state.level).is_some() {
+        if let Err(err) = sinks.spawn_hooks(&state.watch_args()) {
+            eprintln!("Failed to start idle watcher: {}", err);
+        }
+    }
+    Ok(())
+}
+
+/// `claude-hooks idle-watch` の1回分: 時間の来た段階を通知し、次の段階までの待ち時間を返す
+///
+/// 入力があって状態が削除された場合や、新しいアイドル状態に置き換わった場合、セッションが終了した場合はNone（終了する）。
+pub fn idle_watch(session_id: &str, since: &str, sinks: &dyn Sinks) -> io::Result<Option<std::time::Duration>> {
+    let Some(home) = sinks.home() else {
+        return Ok(None);
+    };
+    let path = idle::state_path(&home, session_id);
+    let Some(mut state) = sinks
+        .read_file(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<IdleState>(&content).ok())
+        .filter(|state| state.since == since)
+    else {
+        return Ok(None);
+    };
+    // SessionEndが動かずに終了したセッションには通知しない
+    let ended = state
+        .transcript_path
+        .as_deref()
+        .and_then(|transcript| sinks.read_file(Path::new(transcript)).ok())
+        .is_some_and(|transcript| idle::session_ended(&transcript));
+    if ended {
+        sinks.remove_file(&path)?;
+        return Ok(None);
+    }
+
+    let now = sinks.now();
+    if escalate_idle(&mut state, now, sinks) {
+        let content = serde_json::to_string_pretty(&state).map_err(io::Error::other)?;
+        sinks.write_file(&path, &content)?;
+    }
+    let next = state
+        .since_time()
+        .and_then(|since| sinks.config().idle.next_step(since, state.level));
+    Ok(next.map(|(_, at)| (at - now).to_std().unwrap_or_default()))
+}
+
+/// `now` で時間の来た段階をSlackに通知する（途中の段階は飛ばす）。通知した場合はtrue
+fn escalate_idle(state: &mut IdleState, now: DateTime<FixedOffset>, sinks: &dyn Sinks) -> bool {
+    let config = &sinks.config().idle;
+    let Some(since) = state.since_time() else {
+        return false;
+    };
+    let level = config.due_level(since, now);
+    if level <= state.level {
+        return false;
+    }
+    state.level = level;
+
+    let dir_name = get_dir_name(&state.cwd);
+    let git = sinks.git_context(&state.cwd);
+    let title_key = match level {
+        IdleLevel::Mention => "slack.title.idle_mention",
+        _ => "slack.title.idle",
+    };
+    let title = format!("{}{}", t(title_key), GitContext::title_suffix(git.as_ref()));
+    let branch_display = GitContext::slack_field(git.as_ref());
+    let idle_for = subagent::format_duration(u64::try_from((now - since).num_milliseconds()).unwrap_or(0));
+    let mut fields = vec![
+        (t("field.session_id"), state.session_id.as_str()),
+        (t("field.directory"), dir_name.as_str()),
+        (t("field.branch"), branch_display.as_str()),
+        (t("field.idle_for"), idle_for.as_str()),
+        (t("field.message"), state.message.as_str()),
+    ];
+    if let (IdleLevel::Mention, Some(mention)) = (level, &config.mention) {
+        fields.push((t("field.mention"), mention.as_str()));
+    }
+    post_slack(sinks, SlackPost::new(title, &fields));
+    true
+}
+
+// ===== Stop =====
+
+pub fn task_complete_notification(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: StopHookInput = parse(input)?;
+
+    // ディレクトリ名取得
+    let dir_name = get_dir_name(&input.cwd);
+
+    // Gitコンテキスト取得
+    let git = sinks.git_context(&input.cwd);
+    let branch_suffix = GitContext::title_suffix(git.as_ref());
+
+    // ユーザープロンプトとアシスタントメッセージを抽出
+    let transcript = input
+        .transcript_path
+        .as_deref()
+        .and_then(|path| sinks.read_file(Path::new(path)).ok());
+    let (user_prompt, assistant_message) = if let Some(ref transcript) = transcript {
+        let prompt = last_user_prompt(transcript);
+        let message = last_assistant_message(transcript);
+
+        // デバッグログ出力
+        if let Some(home) = sinks.home() {
+            let timestamp = sinks.now().format("%Y-%m-%d %H:%M:%S").to_string();
+            let entry = format_log_entry(&timestamp, &input.cwd, &prompt, &message);
+            let _ = sinks.append_file(&home.join(TASK_COMPLETE_LOG), &entry);
+        }
+
+        (prompt, message)
+    } else {
+        (t("fallback.request").to_string(), t("fallback.task_complete").to_string())
+    };
+
+    // 直近のプロンプト以降に変更されたファイルを集計
+    let session_files: Vec<String> = transcript
+        .as_deref()
+        .map(session_edited_files)
+        .unwrap_or_default()
+        .iter()
+        .map(|path| sinks.resolve_path(path))
+        .collect();
+    let root = sinks.git_toplevel(&input.cwd).unwrap_or_else(|| sinks.resolve_path(&input.cwd));
+    let diff = sinks.git_diff_numstat(&input.cwd).unwrap_or_default();
+    let changes = ChangeSummary::from_parts(&session_files, &diff, &root);
+    let notification_body = match changes.desktop_line() {
+        Some(line) => format!("{}\n{}", assistant_message, line),
+        None => assistant_message.clone(),
+    };
+    let changed_files = changes.slack_field();
+
+    // サブタイトル構築（ブランチ名をサブタイトル先頭に表示）
+    let branch_prefix = GitContext::subtitle_prefix(git.as_ref());
+    let subtitle = format!("{}📝 {}", branch_prefix, user_prompt);
+
+    // 通知送信
+    sinks.notify(&DesktopNotification {
+        title: tf("notify.complete.title", &[("dir", &dir_name)]),
+        subtitle,
+        message: notification_body,
+        sound: "Funk".to_string(),
+    })?;
+
+    // Slack通知送信
+    let slack_title = format!("{}{}", t("slack.title.complete"), branch_suffix);
+    let branch_display = GitContext::slack_field(git.as_ref());
+    let slack_fields = vec![
+        (t("field.session_id"), input.session_id.as_str()),
+        (t("field.directory"), dir_name.as_str()),
+        (t("field.branch"), branch_display.as_str()),
+        (t("field.user_prompt"), user_prompt.as_str()),
+        (t("field.changed_files"), changed_files.as_str()),
+    ];
+
+    let post = SlackPost::new(slack_title, &slack_fields).with_markdown(t("field.assistant_response"), &assistant_message);
+    post_slack(sinks, post);
+    Ok(())
+}
+
+// ===== PreToolUse =====
+
+/// `format-tool-display` はsession_idやcwdの無い入力も受け付ける
+#[derive(Deserialize, Debug)]
+struct ToolDisplayInput {
+    #[serde(default)]
+    tool_name: Option<String>,
+    #[serde(default)]
+    tool_input: Option<Value>,
+}
+
+pub fn format_tool_display(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: ToolDisplayInput = match serde_json::from_str(input) {
+        Ok(i) => i,
+        Err(_) => return Ok(()), // JSON解析失敗時は何も出力せず終了
+    };
+
+    if let Some(tool_name) = input.tool_name {
+        // 設定ファイルのテンプレート → ToolSummaryの順で表示を決定
+        let registry = ToolDisplayRegistry::from_config(sinks.config());
+        sinks.print(&registry.format(&tool_name, input.tool_input.as_ref()));
+    }
+
+    Ok(())
+}
+
+pub fn path_policy_check(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: HookInput = match serde_json::from_str(input) {
+        Ok(i) => i,
+        Err(_) => return Ok(()), // JSON解析失敗時は判定せず終了
+    };
+
+    let (Some(tool_name), Some(tool_input)) = (&input.tool_name, &input.tool_input) else {
+        return Ok(());
+    };
+
+    // 拒否する場合のみPreToolUseの判定を出力（許可する場合は通常の権限確認に任せる）
+    let policy = PathPolicy::load(&input.cwd, sinks);
+    if let Some(decision) = policy.check_tool(tool_name, tool_input, sinks) {
+        sinks.print(&decision.to_hook_output().to_string());
+    }
+
+    Ok(())
+}
+
+pub fn network_policy_check(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: HookInput = match serde_json::from_str(input) {
+        Ok(i) => i,
+        Err(_) => return Ok(()), // JSON解析失敗時は判定せず終了
+    };
+
+    let (Some(tool_name), Some(tool_input)) = (&input.tool_name, &input.tool_input) else {
+        return Ok(());
+    };
+
+    let config = sinks.config();
+    let policy = NetworkPolicy::from_config(config);
+    if let Some(violation) = policy.check_tool(tool_name, tool_input) {
+        // 違反の記録に失敗しても判定は返す
+        if let Some(path) = config.network_policy.log_path(sinks.home().as_deref()) {
+            if let Err(err) = network_policy::log_violation(&path, &input.session_id, tool_name, &violation, sinks) {
+                eprintln!("Failed to log network violation: {}", err);
+            }
+        }
+        sinks.print(&violation.decision.to_hook_output().to_string());
+    }
+
+    Ok(())
+}
+
+// ===== UserPromptSubmit =====
+
+pub fn user_prompt_slack(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: UserPromptSubmitInput = parse(input)?;
+
+    // 入力があったのでアイドル状態を解除する（バックグラウンドの `idle-watch` も終了する）
+    if let Some(home) = sinks.home() {
+        let path = idle::state_path(&home, &input.session_id);
+        if sinks.read_file(&path).is_ok() {
+            if let Err(err) = sinks.remove_file(&path) {
+                eprintln!("Failed to clear idle state: {}", err);
+            }
+        }
+    }
+
+    // プロジェクトごとの設定
+    let home = sinks.home();
+    let home_str = home.as_deref().and_then(|h| h.to_str());
+    let project = sinks.git_toplevel(&input.cwd).unwrap_or_else(|| input.cwd.clone());
+    let settings = sinks.config().prompt.for_project(&project, home_str);
+
+    // シークレットを含むプロンプトはClaudeにもSlackにも送らない
+    let secrets = prompt::find_secrets(&input.prompt);
+    if settings.block_secrets && !secrets.is_empty() {
+        sinks.print(&prompt::block_output(&prompt::block_reason(&secrets)).to_string());
+        return Ok(());
+    }
+
+    // ディレクトリ名を取得
+    let dir_name = get_dir_name(&input.cwd);
+
+    // Gitコンテキスト取得
+    let git = sinks.git_context(&input.cwd);
+    let branch_suffix = GitContext::title_suffix(git.as_ref());
+    let branch_display = GitContext::slack_field(git.as_ref());
+
+    // マクロを展開してClaudeに渡す
+    if settings.macros {
+        let expansions = expand_macros(&input.prompt, &input.cwd, &settings, git.as_ref(), sinks);
+        if let Some(context) = prompt::build_context(&expansions) {
+            sinks.print(&prompt::context_output(&context).to_string());
+        }
+    }
+
+    // Slackに通知（ブロックしない場合もシークレットは伏せる）
+    let prompt_text = prompt::redact(&input.prompt, &secrets);
+    let title = format!("{}{}", t("slack.title.prompt"), branch_suffix);
+    let fields = vec![
+        (t("field.session_id"), input.session_id.as_str()),
+        (t("field.directory"), dir_name.as_str()),
+        (t("field.branch"), branch_display.as_str()),
+        (t("field.permission_mode"), input.permission_mode.as_str()),
+        (t("field.prompt"), prompt_text.as_str()),
+    ];
+
+    // セッションで最初のプロンプトをサブエージェントの報告のスレッドの親にする
+    post_slack(sinks, SlackPost::new(title, &fields).in_session_thread(&input.session_id, false));
+    Ok(())
+}
+
+/// `#issue-123` / `@file:path` の展開（マクロの表記と本文の組）
+fn expand_macros(
+    text: &str,
+    cwd: &str,
+    settings: &PromptSettings,
+    git: Option<&GitContext>,
+    sinks: &dyn Sinks,
+) -> Vec<(String, String)> {
+    let context = PathContext::new(Path::new(cwd), sinks.home().as_deref(), sinks);
+    let policy = PathPolicy::load(cwd, sinks);
+    let remote_url = git.and_then(|g| g.remote_url.as_deref());
+
+    prompt::find_macros(text)
+        .into_iter()
+        .map(|m| {
+            let body = match &m {
+                Macro::Issue(number) => {
+                    match prompt::issue_link(*number, settings.issue_url.as_deref(), remote_url) {
+                        Some(url) => tf("prompt.issue", &[("number", &number.to_string()), ("url", &url)]),
+                        None => tf("prompt.issue_no_link", &[("number", &number.to_string())]),
+                    }
+                }
+                Macro::File(path) => {
+                    // パスポリシーで読み取りを拒否しているファイルは渡さない
+                    if let Some(decision) = policy.check_path(Access::Read, path, sinks) {
+                        decision.reason
+                    } else {
+                        let resolved = context.resolve(path, sinks);
+                        match sinks.read_file(&resolved) {
+                            Ok(content) => prompt::file_block(&content, settings.max_file_chars),
+                            Err(_) => tf("prompt.file_missing", &[("path", &resolved.to_string_lossy())]),
+                        }
+                    }
+                }
+            };
+            (m.source(), body)
+        })
+        .collect()
+}
+
+// ===== PostToolUse =====
+
+pub fn askuser_question_slack(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: PostToolUseInput = parse(input)?;
+
+    let dir_name = get_dir_name(&input.cwd);
+
+    // Gitコンテキスト取得
+    let git = sinks.git_context(&input.cwd);
+    let branch_suffix = GitContext::title_suffix(git.as_ref());
+    let branch_display = GitContext::slack_field(git.as_ref());
+
+    // tool_input から質問とオプションを抽出
+    let questions_info = extract_questions_with_options(&input.tool_input);
+
+    let title = format!("{}{}", t("slack.title.question"), branch_suffix);
+    let fields = vec![
+        (t("field.session_id"), input.session_id.as_str()),
+        (t("field.directory"), dir_name.as_str()),
+        (t("field.branch"), branch_display.as_str()),
+        (t("field.questions"), questions_info.as_str()),
+    ];
+
+    post_slack(sinks, SlackPost::new(title, &fields));
+    Ok(())
+}
+
+pub fn askuser_answer_slack(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: PostToolUseInput = parse(input)?;
+
+    let dir_name = get_dir_name(&input.cwd);
+
+    // Gitコンテキスト取得
+    let git = sinks.git_context(&input.cwd);
+    let branch_suffix = GitContext::title_suffix(git.as_ref());
+    let branch_display = GitContext::slack_field(git.as_ref());
+
+    // tool_input から質問を抽出
+    let questions = input
+        .tool_input
+        .get("questions")
+        .and_then(|q| q.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|q| q.get("question").and_then(|v| v.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_else(|| t("fallback.not_available").to_string());
+
+    // ユーザー回答を抽出（tool_input.answers → tool_response.answers の順で試行）
+    let answer = extract_answer(&input.tool_input, &input.tool_response);
+
+    let title = format!("{}{}", t("slack.title.answer"), branch_suffix);
+    let fields = vec![
+        (t("field.session_id"), input.session_id.as_str()),
+        (t("field.directory"), dir_name.as_str()),
+        (t("field.branch"), branch_display.as_str()),
+        (t("field.question"), questions.as_str()),
+        (t("field.answer"), answer.as_str()),
+    ];
+
+    post_slack(sinks, SlackPost::new(title, &fields));
+    Ok(())
+}
+
+/// tool_input.answers と tool_response の両方から回答を抽出
+fn extract_answer(tool_input: &Value, tool_response: &Value) -> String {
+    // 1. tool_input.answers から抽出（最も構造化されたデータ）
+    if let Some(answer) = extract_from_answers_field(tool_input) {
+        return answer;
+    }
+
+    // 2. tool_response.answers から抽出
+    if let Some(answer) = extract_from_answers_field(tool_response) {
+        return answer;
+    }
+
+    // 3. tool_response が文字列の場合
+    if let Some(s) = tool_response.as_str() {
+        return s.to_string();
+    }
+
+    // 4. tool_response が配列の場合（content blocks 形式）
+    if let Some(arr) = tool_response.as_array() {
+        let texts: Vec<&str> = arr
+            .iter()
+            .filter_map(|item| {
+                if item.get("type").and_then(|t| t.as_str()) == Some("text") {
+                    item.get("text").and_then(|t| t.as_str())
+                } else {
+                    item.as_str()
+                }
+            })
+            .collect();
+        if !texts.is_empty() {
+            return texts.join("\n");
+        }
+    }
+
+    // 5. フォールバック
+    tool_response.to_string()
+}
+
+/// JSON値の "answers" フィールドから回答文字列を抽出
+fn extract_from_answers_field(value: &Value) -> Option<String> {
+    let answers = value.get("answers")?.as_object()?;
+    let extracted: Vec<String> = answers
+        .values()
+        .map(|v| v.as_str().map(String::from).unwrap_or_else(|| v.to_string()))
+        .collect();
+    if extracted.is_empty() {
+        None
+    } else {
+        Some(extracted.join(", "))
+    }
+}
+
+pub fn exitplanmode_slack(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: PostToolUseInput = parse(input)?;
+
+    let dir_name = get_dir_name(&input.cwd);
+
+    // Gitコンテキスト取得
+    let git = sinks.git_context(&input.cwd);
+    let branch_suffix = GitContext::title_suffix(git.as_ref());
+    let branch_display = GitContext::slack_field(git.as_ref());
+
+    // tool_input.plan → トランスクリプトの順で解決
+    let home = sinks.home();
+    let plans_dir = home.as_ref().map(|h| h.join(".claude/plans"));
+    let resolved = plan::resolve_plan(&input.tool_input, input.transcript_path.as_deref(), plans_dir.as_deref(), sinks);
+    let plan_content = resolved
+        .as_ref()
+        .map(|p| p.content.clone())
+        .unwrap_or_else(|| t("fallback.plan_not_found").to_string());
+
+    // 同じセッションで修正依頼があった場合は前回のプランとの差分を表示
+    let history_dir = home.as_deref().map(plan::history_dir);
+    let plan_diff = match (&resolved, &history_dir) {
+        (Some(current), Some(dir)) => {
+            let diff = plan::load_previous_plan(dir, &input.session_id, sinks)
+                .and_then(|previous| plan::plan_diff(&previous, &current.content));
+            if let Err(err) = plan::save_plan(dir, &input.session_id, &current.content, sinks) {
+                eprintln!("Failed to save plan history: {}", err);
+            }
+            diff
+        }
+        _ => None,
+    };
+    let plan_diff = plan_diff.map(|d| format!("```\n{}\n```", d));
+
+    let title = format!("{}{}", t("slack.title.plan"), branch_suffix);
+    let mut fields = vec![
+        (t("field.session_id"), input.session_id.as_str()),
+        (t("field.directory"), dir_name.as_str()),
+        (t("field.branch"), branch_display.as_str()),
+    ];
+    if let Some(diff) = plan_diff.as_deref() {
+        fields.push((t("field.plan_diff"), diff));
+    }
+
+    post_slack(sinks, SlackPost::new(title, &fields).with_markdown(t("field.plan_content"), &plan_content));
+    Ok(())
+}
+
+// ===== SubagentStop / PostToolUse(Task) =====
+
+/// サブエージェントの終了を報告する（SubagentStopとPostToolUse(Task)のどちらにも登録できる）
+pub fn subagent_slack(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let value: Value = parse(input)?;
+    let (session_id, cwd, report) = match value.get("hook_event_name").and_then(|v| v.as_str()) {
+        Some("SubagentStop") => {
+            let input: SubagentStopInput = serde_json::from_value(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            // Stopフックで続行させられた場合は、次に終了した時に報告する
+            if input.stop_hook_active {
+                return Ok(());
+            }
+            // PostToolUse(Task)にも登録されていれば、使用量を含むそちらで報告する
+            let settings = sinks
+                .home()
+                .and_then(|home| sinks.read_file(&home.join(".claude/settings.json")).ok())
+                .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+            if settings.as_ref().is_some_and(subagent::reports_on_task) {
+                return Ok(());
+            }
+            let read = |path: Option<&str>| path.and_then(|p| sinks.read_file(Path::new(p)).ok());
+            let agent_transcript = read(input.agent_transcript_path.as_deref()).unwrap_or_default();
+            let parent_transcript = read(input.transcript_path.as_deref());
+            let report =
+                SubagentReport::from_transcripts(input.agent_id.as_deref(), &agent_transcript, parent_transcript.as_deref());
+            (input.session_id, input.cwd, report)
+        }
+        _ => {
+            let input: PostToolUseInput = serde_json::from_value(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if input.tool_name != "Task" {
+                return Ok(());
+            }
+            let report = SubagentReport::from_tool_use(&input.tool_input, &input.tool_response);
+            (input.session_id, input.cwd, report)
+        }
+    };
+
+    let config = &sinks.config().subagents;
+    if !config.is_enabled(&report.subagent_type) {
+        return Ok(());
+    }
+
+    // 両方のイベントに登録されていても同じサブエージェントは1度だけ報告する
+    let reported_path = match (&report.agent_id, sinks.home()) {
+        (Some(_), Some(home)) => Some(subagent::reported_path(&home, &session_id)),
+        _ => None,
+    };
+    if let (Some(agent_id), Some(path)) = (&report.agent_id, &reported_path) {
+        if sinks.read_file(path).is_ok_and(|log| subagent::is_reported(&log, agent_id)) {
+            return Ok(());
+        }
+    }
+
+    let dir_name = get_dir_name(&cwd);
+    let git = sinks.git_context(&cwd);
+    let branch_suffix = GitContext::title_suffix(git.as_ref());
+    let branch_display = GitContext::slack_field(git.as_ref());
+
+    let title = format!("{}{}", tf("slack.title.subagent", &[("agent", &report.subagent_type)]), branch_suffix);
+    let description = if report.description.is_empty() {
+        t("fallback.not_available").to_string()
+    } else {
+        report.description.clone()
+    };
+    let stats = report.stats_text();
+    let fields = vec![
+        (t("field.session_id"), session_id.as_str()),
+        (t("field.directory"), dir_name.as_str()),
+        (t("field.branch"), branch_display.as_str()),
+        (t("field.subagent_type"), report.subagent_type.as_str()),
+        (t("field.description"), description.as_str()),
+        (t("field.duration"), stats.as_str()),
+    ];
+
+    let post = SlackPost::new(title, &fields)
+        .with_markdown(t("field.subagent_output"), &report.summary())
+        .in_session_thread(&session_id, config.thread);
+    if !post_slack(sinks, post) {
+        return Ok(());
+    }
+    if let (Some(agent_id), Some(path)) = (&report.agent_id, &reported_path) {
+        if let Err(err) = sinks.append_file(path, &format!("{}\n", agent_id)) {
+            eprintln!("Failed to record subagent report: {}", err);
+        }
+    }
+    Ok(())
+}
+
+// ===== PreCompact / コンテキスト使用率 =====
+
+/// PreCompactと、使用率を監視するStopの共通の入力
+#[derive(Deserialize, Debug)]
+struct ContextInput {
+    session_id: String,
+    #[serde(default)]
+    transcript_path: Option<String>,
+    cwd: String,
+    #[serde(default)]
+    hook_event_name: Option<String>,
+    /// PreCompactの "manual" / "auto"
+    #[serde(default)]
+    trigger: Option<String>,
+}
+
+/// 自動圧縮の直前と、コンテキスト使用率が設定した閾値を超えた時に通知する
+///
+/// トランスクリプト全体を読むため、ツール呼び出しごと（PostToolUse）ではなく応答の終了時（Stop）に確認する。
+pub fn context_notification(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: ContextInput = parse(input)?;
+    let config = &sinks.config().context;
+
+    let transcript = input
+        .transcript_path
+        .as_deref()
+        .and_then(|path| sinks.read_file(Path::new(path)).ok())
+        .unwrap_or_default();
+    let tokens = context::context_tokens(&transcript).unwrap_or(0);
+    let percent = config.percentage(tokens);
+
+    let headline = if input.hook_event_name.as_deref() == Some("PreCompact") {
+        // 手動の /compact は本人が実行しているため通知しない
+        if input.trigger.as_deref() == Some("manual") {
+            return Ok(());
+        }
+        t("context.precompact").to_string()
+    } else {
+        // 閾値ごとに1度だけ通知する（圧縮で使用率が下がったら再び通知する）
+        let Some(home) = sinks.home() else {
+            return Ok(());
+        };
+        let path = context::state_path(&home, &input.session_id);
+        let mut state = sinks.read_file(&path).map(|c| ContextState::parse(&c)).unwrap_or_default();
+        let previous = state.clone();
+        state.rearm(percent, config);
+        let crossed = config.crossed_threshold(state.notified, percent);
+        if let Some(threshold) = crossed {
+            state.notified = Some(threshold);
+        }
+        if state != previous {
+            let content = serde_json::to_string(&state).map_err(io::Error::other)?;
+            if let Err(err) = sinks.write_file(&path, &content) {
+                eprintln!("Failed to save context state: {}", err);
+            }
+        }
+        if crossed.is_none() {
+            return Ok(());
+        }
+        tf("context.threshold", &[("percent", &percent.to_string())])
+    };
+
+    let dir_name = get_dir_name(&input.cwd);
+    let git = sinks.git_context(&input.cwd);
+    let branch_suffix = GitContext::title_suffix(git.as_ref());
+    let branch_prefix = GitContext::subtitle_prefix(git.as_ref());
+
+    let registry = McpServerRegistry::from_config(sinks.config());
+    let results = context::largest_tool_results(&transcript, &input.cwd, &registry, config.top_results);
+    let usage = tf(
+        "context.usage",
+        &[
+            ("tokens", &context::format_tokens(tokens)),
+            ("limit", &context::format_tokens(config.compaction_tokens)),
+            ("percent", &percent.to_string()),
+        ],
+    );
+
+    // 通知送信
+    let message = match results.first() {
+        Some(largest) => format!(
+            "{}\n{}",
+            usage,
+            tf("context.largest", &[("label", &largest.label), ("tokens", &context::format_tokens(largest.tokens))])
+        ),
+        None => usage.clone(),
+    };
+    sinks.notify(&DesktopNotification {
+        title: tf("notify.context.title", &[("dir", &dir_name)]),
+        subtitle: format!("{}🧠 {}", branch_prefix, headline),
+        message,
+        sound: "Purr".to_string(),
+    })?;
+
+    // Slack通知送信
+    let slack_title = match input.hook_event_name.as_deref() {
+        Some("PreCompact") => t("slack.title.precompact").to_string(),
+        _ => tf("slack.title.context", &[("percent", &percent.to_string())]),
+    };
+    let branch_display = GitContext::slack_field(git.as_ref());
+    let results_text = if results.is_empty() {
+        t("fallback.not_available").to_string()
+    } else {
+        context::results_text(&results)
+    };
+    let mut fields = vec![
+        (t("field.session_id"), input.session_id.as_str()),
+        (t("field.directory"), dir_name.as_str()),
+        (t("field.branch"), branch_display.as_str()),
+        (t("field.context"), usage.as_str()),
+        (t("field.largest_results"), results_text.as_str()),
+    ];
+    let export = input
+        .transcript_path
+        .as_deref()
+        .map(|path| tf("context.export", &[("path", path)]));
+    if let Some(export) = &export {
+        fields.push((t("field.transcript"), export.as_str()));
+    }
+
+    post_slack(sinks, SlackPost::new(format!("{}{}", slack_title, branch_suffix), &fields));
+    Ok(())
+}
+
+// ===== SessionStart =====
+
+/// セッションの開始時に、プロジェクトの状況を `additionalContext` としてClaudeに渡す
+pub fn session_context(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: HookInput = parse(input)?;
+    let config = &sinks.config().session_context;
+    let home = sinks.home();
+    let home_str = home.as_deref().and_then(|h| h.to_str());
+
+    let project = sinks.git_toplevel(&input.cwd).unwrap_or_else(|| input.cwd.clone());
+    if !config.is_enabled(&project, home_str) {
+        return Ok(());
+    }
+
+    let mut sections: Vec<(String, String)> = Vec::new();
+
+    // ブランチとGitの状態
+    let git = sinks.git_context(&input.cwd);
+    if let Some(git) = &git {
+        sections.push((t("session.heading.git").to_string(), session_context::git_text(git)));
+    }
+
+    // ブランチのオープンなプルリクエスト（キャッシュのみ参照し、ネットワークにはアクセスしない）
+    let pull_request = match (&home, &git) {
+        (Some(home), Some(git)) => git
+            .remote_url
+            .as_deref()
+            .and_then(|url| session_context::pr_cache_path(home, url))
+            .and_then(|path| sinks.read_file(&path).ok())
+            .zip(git.branch.as_deref())
+            .and_then(|(cache, branch)| session_context::open_pull_request(&cache, branch)),
+        _ => None,
+    };
+    if let Some(pr) = &pull_request {
+        sections.push((t("session.heading.pr").to_string(), pr.line()));
+    }
+
+    // このディレクトリで直近に完了したタスク
+    if let Some(home) = &home {
+        let log = sinks.read_file(&home.join(TASK_COMPLETE_LOG)).unwrap_or_default();
+        let tasks = session_context::recent_tasks(&parse_log_entries(&log), &input.cwd, config.recent_tasks);
+        if !tasks.is_empty() {
+            sections.push((t("session.heading.tasks").to_string(), session_context::tasks_text(&tasks)));
+        }
+    }
+
+    // チームのメモ
+    if let Some(path) = config.notes_path(&project, home_str) {
+        if let Ok(notes) = sinks.read_file(&path) {
+            let file = config.notes_file.as_deref().unwrap_or_default();
+            sections.push((tf("session.heading.notes", &[("file", file)]), notes));
+        }
+    }
+
+    if let Some(context) = session_context::build(&sections, config.max_chars) {
+        sinks.print(&session_context::hook_output(&context).to_string());
+    }
+    Ok(())
+}
+
+// ===== SessionEnd =====
+
+/// セッションの終了時に、トランスクリプトと記録した権限リクエストからタイムラインを作り、保存してSlackに送信する
+pub fn session_end_summary(input: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    let input: SessionEndInput = parse(input)?;
+
+    let transcript = input
+        .transcript_path
+        .as_deref()
+        .and_then(|path| sinks.read_file(Path::new(path)).ok())
+        .unwrap_or_default();
+    let registry = McpServerRegistry::from_config(sinks.config());
+    let mut events = timeline::transcript_events(&transcript, &input.cwd, &registry);
+    let home = sinks.home();
+    if let Some(home) = &home {
+        let recorded = sinks.read_file(&timeline::events_path(home, &input.session_id)).unwrap_or_default();
+        events.extend(timeline::recorded_events(&recorded));
+    }
+    let timeline = Timeline::new(&input.session_id, &input.cwd, input.reason.as_deref(), events);
+    if timeline.events.is_empty() {
+        if let Some(home) = &home {
+            remove_session_state(home, &input.session_id, sinks);
+        }
+        return Ok(());
+    }
+
+    // 後でレポートを作れるように保存する
+    if let Some(home) = &home {
+        let content = serde_json::to_string_pretty(&timeline).map_err(io::Error::other)?;
+        if let Err(err) = sinks.write_file(&timeline::timeline_path(home, &input.session_id), &content) {
+            eprintln!("Failed to save session timeline: {}", err);
+        }
+    }
+
+    // Slack通知送信
+    let dir_name = get_dir_name(&input.cwd);
+    let git = sinks.git_context(&input.cwd);
+    let slack_title = format!("{}{}", t("slack.title.session_end"), GitContext::title_suffix(git.as_ref()));
+    let branch_display = GitContext::slack_field(git.as_ref());
+    let duration = timeline
+        .duration_ms
+        .map(subagent::format_duration)
+        .unwrap_or_else(|| t("fallback.not_available").to_string());
+    let stats = timeline.stats_text();
+    let reason = timeline::reason_text(input.reason.as_deref());
+    let fields = vec![
+        (t("field.session_id"), input.session_id.as_str()),
+        (t("field.directory"), dir_name.as_str()),
+        (t("field.branch"), branch_display.as_str()),
+        (t("field.duration"), duration.as_str()),
+        (t("field.activity"), stats.as_str()),
+        (t("field.end_reason"), reason.as_str()),
+    ];
+    let markdown = timeline.markdown(*sinks.now().offset());
+    post_slack(sinks, SlackPost::new(slack_title, &fields).with_markdown(t("field.timeline"), &markdown));
+
+    if let Some(home) = &home {
+        remove_session_state(home, &input.session_id, sinks);
+    }
+    Ok(())
+}
+
+/// セッションの間だけ使う状態ファイルを削除する（保存したタイムラインは残す）
+fn remove_session_state(home: &Path, session_id: &str, sinks: &dyn Sinks) {
+    let paths = [
+        timeline::events_path(home, session_id),
+        context::state_path(home, session_id),
+        subagent::reported_path(home, session_id),
+        slack::thread_path(home, session_id),
+        plan::history_path(&plan::history_dir(home), session_id),
+        idle::state_path(home, session_id),
+    ];
+    for path in paths {
+        if sinks.read_file(&path).is_ok() {
+            if let Err(err) = sinks.remove_file(&path) {
+                eprintln!("Failed to remove session state {}: {}", path.display(), err);
+            }
+        }
+    }
+}
@@ -0,0 +1,521 @@
+use crate::config::HooksConfig;
+use crate::system::System;
+use std::sync::OnceLock;
+
+// ===== ロケール =====
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Ja,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Ja];
+
+    /// 既定のロケール（従来の表示に合わせて日本語）
+    pub const DEFAULT: Locale = Locale::Ja;
+
+    /// "ja", "ja_JP.UTF-8", "en-US" などの言語タグを解釈する
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let lang = tag
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "ja" => Some(Locale::Ja),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    /// 設定ファイルの `locale` → `LC_ALL` → `LC_MESSAGES` → `LANG` の順にロケールを決定する
+    pub fn resolve(config: &HooksConfig, system: &dyn System) -> Locale {
+        if let Some(locale) = config.locale.as_deref().and_then(Locale::from_tag) {
+            return locale;
+        }
+        for key in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+            if let Some(locale) = system.env_var(key).as_deref().and_then(Locale::from_tag) {
+                return locale;
+            }
+        }
+        Locale::DEFAULT
+    }
+
+    /// プロセス内で1度だけ決定したロケール（決定する前は `DEFAULT`）
+    pub fn current() -> Locale {
+        LOCALE.get().copied().unwrap_or(Locale::DEFAULT)
+    }
+
+    /// 最初の表示より前にロケールを固定する（テストなど）。実際に使われるロケールを返す
+    pub fn init(locale: Locale) -> Locale {
+        *LOCALE.get_or_init(|| locale)
+    }
+
+    /// 最初の表示より前に設定ファイルと環境変数からロケールを決定する。実際に使われるロケールを返す
+    pub fn init_from(config: &HooksConfig, system: &dyn System) -> Locale {
+        *LOCALE.get_or_init(|| Locale::resolve(config, system))
+    }
+
+    pub fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Ja => JA,
+        }
+    }
+}
+
+// ===== 翻訳 =====
+
+/// 現在のロケールでメッセージを取得する
+pub fn t(key: &str) -> &'static str {
+    translate(Locale::current(), key)
+}
+
+/// 現在のロケールでメッセージを取得し、`{name}` 形式のプレースホルダーを置換する
+pub fn tf(key: &str, args: &[(&str, &str)]) -> String {
+    format_message(translate(Locale::current(), key), args)
+}
+
+/// 指定したロケールでメッセージを取得する（見つからない場合は英語、それも無ければ空文字列）
+pub fn translate(locale: Locale, key: &str) -> &'static str {
+    lookup(locale, key)
+        .or_else(|| lookup(Locale::En, key))
+        .unwrap_or_else(|| {
+            eprintln!("Missing message key: {}", key);
+            ""
+        })
+}
+
+pub fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    locale
+        .catalog()
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| *v)
+}
+
+pub fn format_message(template: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(template.to_string(), |acc, (name, value)| {
+        acc.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+// ===== カタログ =====
+
+const EN: &[(&str, &str)] = &[
+    // ツール表示（PreToolUse・権限リクエストのサブタイトル）
+    ("tool.ask_user_question", "Asking the user"),
+    ("tool.exit_plan_mode", "Presenting plan"),
+    ("tool.bash", "Run command"),
+    ("tool.write", "Create file"),
+    ("tool.edit", "Edit file"),
+    ("tool.read", "Read file"),
+    ("tool.grep", "Search code"),
+    ("tool.glob", "Find files"),
+    ("tool.task", "Run agent"),
+    ("tool.web_fetch", "Fetch web page"),
+    ("tool.web_search", "Web search"),
+    ("tool.todo_write", "Update TODOs"),
+    ("tool.notebook_edit", "Edit notebook"),
+    ("tool.skill", "Run skill"),
+    ("tool.slash_command", "Run command"),
+    ("tool.bash_output", "Read output"),
+    ("tool.kill_shell", "Kill shell"),
+    ("tool.pattern", "Pattern: {pattern}"),
+    ("tool.agent_type", "Type: {type}"),
+    // デスクトップ通知
+    ("notify.idle.title", "Claude Code - Waiting for input ({dir})"),
+    ("notify.idle.subtitle", "⏱️ Idle"),
+    ("notify.idle.message", "Waiting for your input"),
+    ("notify.permission.title", "Claude Code - Needs approval ({dir})"),
+    ("notify.other.title", "Claude Code - Notification ({dir})"),
+    ("notify.other.message", "Notification"),
+    ("notify.complete.title", "Claude Code - Task complete ({dir})"),
+    ("notify.context.title", "Claude Code - Context filling up ({dir})"),
+    // Slackタイトル
+    ("slack.title.idle", "⏱️ Claude Code - Idle"),
+    ("slack.title.idle_mention", "🔔 Claude Code - Still waiting for input"),
+    ("slack.title.permission", "🔔 Claude Code - Permission Request"),
+    ("slack.title.notification", "📢 Claude Code - Notification"),
+    ("slack.title.complete", "✅ Claude Code - Task Complete"),
+    ("slack.title.prompt", "🤔 New Claude Prompt"),
+    ("slack.title.question", "❓ AskUserQuestion"),
+    ("slack.title.answer", "💬 AskUserQuestion Response"),
+    ("slack.title.plan", "📋 Plan Ready for Review"),
+    ("slack.title.subagent", "🤖 Subagent Finished: {agent}"),
+    ("slack.title.context", "🧠 Context at {percent}%"),
+    ("slack.title.precompact", "🗜️ Auto-compaction Starting"),
+    ("slack.title.session_end", "🏁 Session Ended"),
+    // Slackフィールド
+    ("field.session_id", "Session ID"),
+    ("field.directory", "Directory"),
+    ("field.branch", "Branch"),
+    ("field.type", "Type"),
+    ("field.message", "Message"),
+    ("field.idle_for", "Idle for"),
+    ("field.mention", "Mention"),
+    ("field.user_prompt", "User Prompt"),
+    ("field.assistant_response", "Assistant Response"),
+    ("field.changed_files", "Changed Files"),
+    ("field.permission_mode", "Permission Mode"),
+    ("field.prompt", "Prompt"),
+    ("field.question", "Question"),
+    ("field.questions", "Questions"),
+    ("field.answer", "Answer"),
+    ("field.plan_content", "Plan Content"),
+    ("field.plan_diff", "Changes Since Previous Plan"),
+    ("field.diff_preview", "Diff Preview ({language})"),
+    ("field.risk", "Risk"),
+    ("field.subagent_type", "Subagent"),
+    ("field.description", "Description"),
+    ("field.duration", "Duration"),
+    ("field.subagent_output", "Final Output"),
+    ("field.context", "Context"),
+    ("field.largest_results", "Likely to Be Compacted"),
+    ("field.transcript", "Transcript"),
+    ("field.activity", "Activity"),
+    ("field.end_reason", "End Reason"),
+    ("field.timeline", "Timeline"),
+    // Slack共通
+    ("slack.open_iterm2", "Open in iTerm2"),
+    ("slack.open_session", "Open session"),
+    ("slack.continued", "{title} (continued {page}/{total})"),
+    ("slack.truncated", "(truncated: {count} more blocks. Incoming Webhooks send at most {max} messages; use Bot mode with CLAUDE_CODE_SLACK_UPLOAD_SNIPPETS=1 to attach the full text)"),
+    ("slack.diff_omitted", "… {count} more lines"),
+    // フォールバック
+    ("fallback.not_available", "N/A"),
+    ("fallback.request", "Request"),
+    ("fallback.task_complete", "Task complete"),
+    ("fallback.plan_not_found", "Plan file not found"),
+    ("fallback.subagent_output", "(no output)"),
+    // サブエージェント
+    ("subagent.tool_uses", "{count} tool uses"),
+    ("subagent.tokens", "{count} tokens"),
+    // コンテキスト
+    ("context.threshold", "Context at {percent}%"),
+    ("context.precompact", "Auto-compaction starting"),
+    ("context.usage", "{tokens} / {limit} tokens ({percent}%)"),
+    ("context.result", "• {label} (~{tokens} tokens)"),
+    ("context.largest", "Largest: {label} (~{tokens} tokens)"),
+    ("context.export", "Run `/export` to save the conversation before it is compacted\n<file://{path}|{path}>"),
+    // プロンプトのガードとマクロ
+    ("prompt.blocked", "Prompt blocked: it looks like it contains secrets ({kinds}). Remove them and send it again."),
+    ("prompt.heading", "Expanded prompt macros (claude-hooks)"),
+    ("prompt.issue", "Issue #{number}: {url}"),
+    ("prompt.issue_no_link", "Issue #{number} (no issue URL configured)"),
+    ("prompt.file_missing", "File not found: {path}"),
+    // セッションのタイムライン
+    ("timeline.prompts", "{count} prompt(s)"),
+    ("timeline.tool_calls", "{count} tool call(s)"),
+    ("timeline.permissions", "{count} permission request(s)"),
+    ("timeline.plans", "{count} plan(s)"),
+    ("timeline.line", "`{time}` {label}"),
+    ("timeline.line_delta", "`{time}` {label} _(+{delta})_"),
+    ("timeline.more_tools", "… {count} more tool calls"),
+    ("timeline.reason.clear", "Cleared with /clear"),
+    ("timeline.reason.logout", "Logged out"),
+    ("timeline.reason.exit", "Exited"),
+    // セッション開始時のコンテキスト
+    ("session.heading", "Project context (claude-hooks)"),
+    ("session.heading.git", "Git"),
+    ("session.heading.pr", "Open pull request"),
+    ("session.heading.tasks", "Recent tasks in this directory"),
+    ("session.heading.notes", "Team notes ({file})"),
+    ("session.branch", "Branch: {branch}"),
+    ("session.worktree", "Worktree: {worktree}"),
+    ("session.status", "Working tree: {staged} staged, {modified} modified, {untracked} untracked, {conflicted} conflicted"),
+    ("session.clean", "Working tree: clean"),
+    ("session.upstream", "Upstream: {upstream} (ahead {ahead}, behind {behind})"),
+    ("session.pr", "#{number} {title}\n{url}"),
+    ("session.pr_draft", "#{number} {title} (draft)\n{url}"),
+    ("session.task", "- [{timestamp}] {prompt} → {response}"),
+    // 変更ファイル
+    ("changes.headline", "{count} files (+{added} -{removed})"),
+    ("changes.headline_one", "1 file (+{added} -{removed})"),
+    ("changes.more_short", " +{count} more"),
+    ("changes.more", "… and {count} more"),
+    ("changes.none", "No changes"),
+    ("changes.new", "(new)"),
+    ("changes.binary", "(binary)"),
+    // Bashコマンドのリスク
+    ("risk.level.low", "Low risk"),
+    ("risk.level.medium", "Medium risk"),
+    ("risk.level.high", "High risk"),
+    ("risk.level.critical", "Critical risk"),
+    ("risk.category.read_only", "Read-only"),
+    ("risk.category.writes_in_repo", "Writes in repository"),
+    ("risk.category.writes_outside_repo", "Writes outside repository"),
+    ("risk.category.network", "Network access"),
+    ("risk.category.package_install", "Package install"),
+    ("risk.category.git_history_rewrite", "Git history rewrite"),
+    ("risk.category.privilege_escalation", "Privilege escalation"),
+    ("risk.category.destructive_delete", "Destructive delete"),
+    ("risk.category.unknown", "Unrecognized command"),
+    // パス・ネットワークのポリシー
+    ("policy.path.deny_read", "Reading {path} is blocked by path policy ({rule})"),
+    ("policy.path.deny_write", "Writing {path} is blocked by path policy ({rule})"),
+    ("policy.path.outside_cwd", "Writing {path} is blocked: outside the working directory {cwd}"),
+    ("policy.path.custom", "{reason}: {path}"),
+    ("policy.path.ask_search", "Searching {path} may read files blocked by path policy ({rule})"),
+    ("policy.network.denied", "Network access to {host} is blocked by network policy ({rule})"),
+    ("policy.network.not_allowed", "Network access to {host} is blocked: not in the allowed domains"),
+    ("policy.network.unparsed", "Could not parse the command to check its network destinations ({program})"),
+    ("policy.network.no_host", "Could not determine the network destination of {program} to check it against the allowed domains"),
+    // install / doctor
+    ("install.added", "Added {event} ({matcher}) → {command}"),
+    ("install.copied", "Copied {path}"),
+    ("install.up_to_date", "All hooks are already configured in {path}"),
+    ("install.backup", "Backed up the previous settings to {path}"),
+    ("install.written", "Updated {path}"),
+    ("focus.registered", "Registered {path} as the x-claude:// URL handler"),
+    ("dashboard.serving", "Dashboard: http://127.0.0.1:{port}/ (Ctrl-C to stop)"),
+    ("dashboard.title", "Claude Code sessions"),
+    ("dashboard.empty", "No active sessions"),
+    ("dashboard.disconnected", "(disconnected, retrying…)"),
+    ("dashboard.column.directory", "Directory"),
+    ("dashboard.column.branch", "Branch"),
+    ("dashboard.column.state", "State"),
+    ("dashboard.column.prompt", "Last prompt"),
+    ("dashboard.column.context", "Context"),
+    ("dashboard.column.updated", "Updated"),
+    ("dashboard.state.working", "Working"),
+    ("dashboard.state.waiting_for_permission", "Waiting for permission"),
+    ("dashboard.state.idle", "Idle"),
+    ("dashboard.state.done", "Done"),
+    ("install.dry_run", "(dry run: nothing was written)"),
+    ("install.usage", "Usage: claude-hooks install [--settings PATH] [--bin-dir DIR] [--no-copy] [--dry-run]\n       claude-hooks doctor [--settings PATH]\n       claude-hooks replay [--update] FIXTURE...\n       claude-hooks idle-watch SESSION_ID SINCE\n       claude-hooks focus URL\n       claude-hooks register-url-handler [--bin-dir DIR]\n       claude-hooks serve [--port PORT]"),
+    ("replay.ok", "{path}: matches the expected output"),
+    ("replay.mismatch", "{path}: output differs from the expected output"),
+    ("replay.updated", "{path}: updated the expected output"),
+    ("replay.error", "{path}: {error}"),
+    ("doctor.settings_ok", "settings.json: {path}"),
+    ("doctor.settings_error", "Cannot read settings.json ({path}): {error}"),
+    ("doctor.hook_ok", "{event} hook: {command}"),
+    ("doctor.hook_missing", "{event} hook command not found: {command}"),
+    ("doctor.hook_not_executable", "{event} hook command is not executable: {command}"),
+    ("doctor.hook_not_configured", "{event} hook for {binary} is not configured"),
+    ("doctor.tool_ok", "{tool}: {path}"),
+    ("doctor.tool_missing", "{tool} is not installed"),
+    ("doctor.slack_ok", "Slack: {mode}"),
+    ("doctor.slack_missing", "Slack is not configured (desktop notifications only)"),
+    ("doctor.config_ok", "claude-hooks.json: {path}"),
+    ("doctor.config_error", "Cannot parse claude-hooks.json ({path}): {error}"),
+    ("doctor.sample_ok", "{command} handles a sample {event} event"),
+    ("doctor.sample_error", "{command} failed on a sample {event} event: {error}"),
+    ("doctor.fix_install", "Run `claude-hooks install`"),
+    ("doctor.fix_path", "Check the command path in settings.json"),
+    ("doctor.fix_config", "Fix the JSON syntax or remove the file"),
+    ("doctor.fix_rebuild", "Rebuild and reinstall the binaries (`cargo build --release && claude-hooks install`)"),
+    ("doctor.summary", "{errors} errors, {warnings} warnings"),
+];
+
+const JA: &[(&str, &str)] = &[
+    // ツール表示（PreToolUse・権限リクエストのサブタイトル）
+    ("tool.ask_user_question", "ユーザーに質問中"),
+    ("tool.exit_plan_mode", "プラン提示中"),
+    ("tool.bash", "コマンド実行"),
+    ("tool.write", "ファイル作成"),
+    ("tool.edit", "ファイル編集"),
+    ("tool.read", "ファイル読み込み"),
+    ("tool.grep", "コード検索"),
+    ("tool.glob", "ファイル検索"),
+    ("tool.task", "エージェント実行"),
+    ("tool.web_fetch", "Web取得"),
+    ("tool.web_search", "Web検索"),
+    ("tool.todo_write", "TODO更新"),
+    ("tool.notebook_edit", "ノートブック編集"),
+    ("tool.skill", "スキル実行"),
+    ("tool.slash_command", "コマンド実行"),
+    ("tool.bash_output", "出力取得"),
+    ("tool.kill_shell", "シェル終了"),
+    ("tool.pattern", "パターン: {pattern}"),
+    ("tool.agent_type", "タイプ: {type}"),
+    // デスクトップ通知
+    ("notify.idle.title", "Claude Code - 入力待ち ({dir})"),
+    ("notify.idle.subtitle", "⏱️ アイドル状態"),
+    ("notify.idle.message", "入力を待っています"),
+    ("notify.permission.title", "Claude Code - 確認待ち ({dir})"),
+    ("notify.other.title", "Claude Code - 通知 ({dir})"),
+    ("notify.other.message", "通知"),
+    ("notify.complete.title", "Claude Code - タスク完了 ({dir})"),
+    ("notify.context.title", "Claude Code - コンテキスト残りわずか ({dir})"),
+    // Slackタイトル
+    ("slack.title.idle", "⏱️ Claude Code - 入力待ち"),
+    ("slack.title.idle_mention", "🔔 Claude Code - 入力を待ち続けています"),
+    ("slack.title.permission", "🔔 Claude Code - 確認待ち"),
+    ("slack.title.notification", "📢 Claude Code - 通知"),
+    ("slack.title.complete", "✅ Claude Code - タスク完了"),
+    ("slack.title.prompt", "🤔 新しいプロンプト"),
+    ("slack.title.question", "❓ Claudeからの質問"),
+    ("slack.title.answer", "💬 質問への回答"),
+    ("slack.title.plan", "📋 プランのレビュー依頼"),
+    ("slack.title.subagent", "🤖 サブエージェント完了: {agent}"),
+    ("slack.title.context", "🧠 コンテキスト使用率 {percent}%"),
+    ("slack.title.precompact", "🗜️ 自動圧縮を開始"),
+    ("slack.title.session_end", "🏁 セッション終了"),
+    // Slackフィールド
+    ("field.session_id", "セッションID"),
+    ("field.directory", "ディレクトリ"),
+    ("field.branch", "ブランチ"),
+    ("field.type", "種類"),
+    ("field.message", "メッセージ"),
+    ("field.idle_for", "待機時間"),
+    ("field.mention", "メンション"),
+    ("field.user_prompt", "ユーザーのリクエスト"),
+    ("field.assistant_response", "Claudeの応答"),
+    ("field.changed_files", "変更ファイル"),
+    ("field.permission_mode", "Permissionモード"),
+    ("field.prompt", "プロンプト"),
+    ("field.question", "質問"),
+    ("field.questions", "質問"),
+    ("field.answer", "回答"),
+    ("field.plan_content", "プラン"),
+    ("field.plan_diff", "前回のプランからの変更"),
+    ("field.diff_preview", "差分プレビュー ({language})"),
+    ("field.risk", "リスク"),
+    ("field.subagent_type", "サブエージェント"),
+    ("field.description", "説明"),
+    ("field.duration", "所要時間"),
+    ("field.subagent_output", "最終出力"),
+    ("field.context", "コンテキスト"),
+    ("field.largest_results", "圧縮されそうな内容"),
+    ("field.transcript", "トランスクリプト"),
+    ("field.activity", "アクティビティ"),
+    ("field.end_reason", "終了理由"),
+    ("field.timeline", "タイムライン"),
+    // Slack共通
+    ("slack.open_iterm2", "iTerm2 で開く"),
+    ("slack.open_session", "セッションを開く"),
+    ("slack.continued", "{title}（続き {page}/{total}）"),
+    ("slack.truncated", "（残り{count}ブロックを省略。Incoming Webhookで送るメッセージは{max}件まで。全文はBotモードで CLAUDE_CODE_SLACK_UPLOAD_SNIPPETS=1 にすると添付されます）"),
+    ("slack.diff_omitted", "… 残り{count}行を省略"),
+    // フォールバック
+    ("fallback.not_available", "N/A"),
+    ("fallback.request", "リクエスト"),
+    ("fallback.task_complete", "タスクが完了しました"),
+    ("fallback.plan_not_found", "プランファイルが見つかりません"),
+    ("fallback.subagent_output", "（出力なし）"),
+    // サブエージェント
+    ("subagent.tool_uses", "ツール実行 {count} 回"),
+    ("subagent.tokens", "{count} トークン"),
+    // コンテキスト
+    ("context.threshold", "コンテキスト使用率 {percent}%"),
+    ("context.precompact", "自動圧縮を開始します"),
+    ("context.usage", "{tokens} / {limit} トークン（{percent}%）"),
+    ("context.result", "• {label}（約{tokens}トークン）"),
+    ("context.largest", "最大: {label}（約{tokens}トークン）"),
+    ("context.export", "圧縮される前に `/export` で会話を保存できます\n<file://{path}|{path}>"),
+    // プロンプトのガードとマクロ
+    ("prompt.blocked", "プロンプトをブロックしました: シークレット（{kinds}）が含まれているようです。取り除いてから送信し直してください。"),
+    ("prompt.heading", "展開したプロンプトのマクロ（claude-hooks）"),
+    ("prompt.issue", "Issue #{number}: {url}"),
+    ("prompt.issue_no_link", "Issue #{number}（IssueのURLが設定されていません）"),
+    ("prompt.file_missing", "ファイルが見つかりません: {path}"),
+    // セッションのタイムライン
+    ("timeline.prompts", "プロンプト{count}件"),
+    ("timeline.tool_calls", "ツール呼び出し{count}件"),
+    ("timeline.permissions", "権限リクエスト{count}件"),
+    ("timeline.plans", "プラン{count}件"),
+    ("timeline.line", "`{time}` {label}"),
+    ("timeline.line_delta", "`{time}` {label} _(+{delta})_"),
+    ("timeline.more_tools", "… 他{count}件のツール呼び出し"),
+    ("timeline.reason.clear", "/clearで終了"),
+    ("timeline.reason.logout", "ログアウト"),
+    ("timeline.reason.exit", "終了"),
+    // セッション開始時のコンテキスト
+    ("session.heading", "プロジェクトの状況（claude-hooks）"),
+    ("session.heading.git", "Git"),
+    ("session.heading.pr", "オープンなプルリクエスト"),
+    ("session.heading.tasks", "このディレクトリの直近のタスク"),
+    ("session.heading.notes", "チームのメモ（{file}）"),
+    ("session.branch", "ブランチ: {branch}"),
+    ("session.worktree", "worktree: {worktree}"),
+    ("session.status", "作業ツリー: ステージ済み{staged}、変更{modified}、未追跡{untracked}、コンフリクト{conflicted}"),
+    ("session.clean", "作業ツリー: 変更なし"),
+    ("session.upstream", "upstream: {upstream}（先行{ahead}、遅れ{behind}）"),
+    ("session.pr", "#{number} {title}\n{url}"),
+    ("session.pr_draft", "#{number} {title}（ドラフト）\n{url}"),
+    ("session.task", "- [{timestamp}] {prompt} → {response}"),
+    // 変更ファイル
+    ("changes.headline", "{count}ファイル (+{added} -{removed})"),
+    ("changes.headline_one", "1ファイル (+{added} -{removed})"),
+    ("changes.more_short", " 他{count}件"),
+    ("changes.more", "… 他{count}件"),
+    ("changes.none", "変更なし"),
+    ("changes.new", "(新規)"),
+    ("changes.binary", "(バイナリ)"),
+    // Bashコマンドのリスク
+    ("risk.level.low", "リスク低"),
+    ("risk.level.medium", "リスク中"),
+    ("risk.level.high", "リスク高"),
+    ("risk.level.critical", "危険"),
+    ("risk.category.read_only", "読み取りのみ"),
+    ("risk.category.writes_in_repo", "リポジトリ内への書き込み"),
+    ("risk.category.writes_outside_repo", "リポジトリ外への書き込み"),
+    ("risk.category.network", "ネットワークアクセス"),
+    ("risk.category.package_install", "パッケージのインストール"),
+    ("risk.category.git_history_rewrite", "Git履歴の書き換え"),
+    ("risk.category.privilege_escalation", "権限昇格"),
+    ("risk.category.destructive_delete", "破壊的な削除"),
+    ("risk.category.unknown", "判別できないコマンド"),
+    // パス・ネットワークのポリシー
+    ("policy.path.deny_read", "パスポリシー（{rule}）により {path} の読み取りは拒否されました"),
+    ("policy.path.deny_write", "パスポリシー（{rule}）により {path} への書き込みは拒否されました"),
+    ("policy.path.outside_cwd", "作業ディレクトリ {cwd} の外にある {path} への書き込みは拒否されました"),
+    ("policy.path.custom", "{reason}: {path}"),
+    ("policy.path.ask_search", "{path} の検索はパスポリシー（{rule}）で読み取りを拒否したファイルを含む可能性があります"),
+    ("policy.network.denied", "ネットワークポリシー（{rule}）により {host} への接続は拒否されました"),
+    ("policy.network.not_allowed", "{host} は許可されたドメインに含まれないため接続は拒否されました"),
+    ("policy.network.unparsed", "コマンドを解析できないため接続先を確認できません（{program}）"),
+    ("policy.network.no_host", "{program} の接続先を判別できないため、許可するドメインと照合できません"),
+    // install / doctor
+    ("install.added", "{event} ({matcher}) に追加しました → {command}"),
+    ("install.copied", "{path} をコピーしました"),
+    ("install.up_to_date", "{path} には全てのフックが設定済みです"),
+    ("install.backup", "変更前の設定を {path} に保存しました"),
+    ("install.written", "{path} を更新しました"),
+    ("focus.registered", "{path} を x-claude:// のURLハンドラーとして登録しました"),
+    ("dashboard.serving", "ダッシュボード: http://127.0.0.1:{port}/ （Ctrl-Cで終了）"),
+    ("dashboard.title", "Claude Codeのセッション"),
+    ("dashboard.empty", "実行中のセッションはありません"),
+    ("dashboard.disconnected", "（切断されました。再接続しています…）"),
+    ("dashboard.column.directory", "ディレクトリ"),
+    ("dashboard.column.branch", "ブランチ"),
+    ("dashboard.column.state", "状態"),
+    ("dashboard.column.prompt", "最後のプロンプト"),
+    ("dashboard.column.context", "コンテキスト"),
+    ("dashboard.column.updated", "更新"),
+    ("dashboard.state.working", "処理中"),
+    ("dashboard.state.waiting_for_permission", "権限の確認待ち"),
+    ("dashboard.state.idle", "入力待ち"),
+    ("dashboard.state.done", "完了"),
+    ("install.dry_run", "（ドライラン: ファイルは変更していません）"),
+    ("install.usage", "使い方: claude-hooks install [--settings PATH] [--bin-dir DIR] [--no-copy] [--dry-run]\n        claude-hooks doctor [--settings PATH]\n        claude-hooks replay [--update] FIXTURE...\n        claude-hooks idle-watch SESSION_ID SINCE\n        claude-hooks focus URL\n        claude-hooks register-url-handler [--bin-dir DIR]\n        claude-hooks serve [--port PORT]"),
+    ("replay.ok", "{path}: 期待する出力と一致しました"),
+    ("replay.mismatch", "{path}: 期待する出力と異なります"),
+    ("replay.updated", "{path}: 期待する出力を更新しました"),
+    ("replay.error", "{path}: {error}"),
+    ("doctor.settings_ok", "settings.json: {path}"),
+    ("doctor.settings_error", "settings.json を読み込めません ({path}): {error}"),
+    ("doctor.hook_ok", "{event} フック: {command}"),
+    ("doctor.hook_missing", "{event} フックのコマンドが見つかりません: {command}"),
+    ("doctor.hook_not_executable", "{event} フックのコマンドに実行権限がありません: {command}"),
+    ("doctor.hook_not_configured", "{binary} の {event} フックが設定されていません"),
+    ("doctor.tool_ok", "{tool}: {path}"),
+    ("doctor.tool_missing", "{tool} がインストールされていません"),
+    ("doctor.slack_ok", "Slack: {mode}"),
+    ("doctor.slack_missing", "Slackが設定されていません（デスクトップ通知のみ）"),
+    ("doctor.config_ok", "claude-hooks.json: {path}"),
+    ("doctor.config_error", "claude-hooks.json を解析できません ({path}): {error}"),
+    ("doctor.sample_ok", "{command} は {event} イベントの例を処理できます"),
+    ("doctor.sample_error", "{command} は {event} イベントの例で失敗しました: {error}"),
+    ("doctor.fix_install", "`claude-hooks install` を実行してください"),
+    ("doctor.fix_path", "settings.json のコマンドのパスを確認してください"),
+    ("doctor.fix_config", "JSONの構文を修正するか、ファイルを削除してください"),
+    ("doctor.fix_rebuild", "バイナリをビルドし直してインストールしてください（`cargo build --release && claude-hooks install`）"),
+    ("doctor.summary", "エラー {errors}件、警告 {warnings}件"),
+];
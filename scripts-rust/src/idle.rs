@@ -0,0 +1,141 @@
+use crate::session_file_name;
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+// ===== 設定 =====
+
+/// `claude-hooks.json` の `idle`
+///
+/// `idle_prompt` の通知はデスクトップ通知から始め、入力が無いまま時間が経つとSlack、メンションと段階的に強める。
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct IdleConfig {
+    /// Slackに通知するまでの時間（分、0はデスクトップ通知と同時）。nullの場合はSlackに通知しない
+    pub slack_after_minutes: Option<u64>,
+    /// メンションするまでの時間（分）
+    pub mention_after_minutes: u64,
+    /// メンションするユーザー・グループ（`<@U0123>` / `<!subteam^S0123>` / `<!here>`）。省略時はメンションしない
+    pub mention: Option<String>,
+}
+
+impl Default for IdleConfig {
+    fn default() -> IdleConfig {
+        IdleConfig {
+            slack_after_minutes: Some(5),
+            mention_after_minutes: 15,
+            mention: None,
+        }
+    }
+}
+
+/// 通知の段階
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum IdleLevel {
+    Desktop,
+    Slack,
+    Mention,
+}
+
+impl IdleConfig {
+    /// 有効な段階と、アイドルになってからの時間（分）
+    pub fn steps(&self) -> Vec<(IdleLevel, u64)> {
+        let mut steps = vec![(IdleLevel::Desktop, 0)];
+        if let Some(slack) = self.slack_after_minutes {
+            steps.push((IdleLevel::Slack, slack));
+            if self.mention.is_some() {
+                steps.push((IdleLevel::Mention, self.mention_after_minutes.max(slack)));
+            }
+        }
+        steps
+    }
+
+    /// `since` にアイドルになった場合に `now` で到達している段階
+    pub fn due_level(&self, since: DateTime<FixedOffset>, now: DateTime<FixedOffset>) -> IdleLevel {
+        self.steps()
+            .into_iter()
+            .filter(|(_, minutes)| step_time(since, *minutes).is_some_and(|time| time <= now))
+            .map(|(level, _)| level)
+            .max()
+            .unwrap_or(IdleLevel::Desktop)
+    }
+
+    /// `level` の次の段階とその時刻（最後の段階の場合はNone）
+    pub fn next_step(&self, since: DateTime<FixedOffset>, level: IdleLevel) -> Option<(IdleLevel, DateTime<FixedOffset>)> {
+        self.steps()
+            .into_iter()
+            .find(|(next, _)| *next > level)
+            .and_then(|(next, minutes)| Some((next, step_time(since, minutes)?)))
+    }
+}
+
+/// 段階の時刻（表せないほど先の場合はNone）
+fn step_time(since: DateTime<FixedOffset>, minutes: u64) -> Option<DateTime<FixedOffset>> {
+    let minutes = Duration::try_minutes(i64::try_from(minutes).ok()?)?;
+    since.checked_add_signed(minutes)
+}
+
+// ===== 状態 =====
+
+/// セッションのアイドル状態（次のUserPromptSubmit、またはSessionEndで削除する）
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct IdleState {
+    pub session_id: String,
+    pub cwd: String,
+    /// アイドルになった時刻（RFC 3339）
+    pub since: String,
+    /// 通知済みの段階
+    pub level: IdleLevel,
+    /// Notificationの `message`
+    pub message: String,
+    /// トランスクリプトのパス（セッションが終了したかどうかの確認用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_path: Option<String>,
+}
+
+impl IdleState {
+    pub fn since_time(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.since).ok()
+    }
+
+    /// 次の段階を通知するバックグラウンドプロセスの引数（`claude-hooks idle-watch <session_id> <since>`）
+    pub fn watch_args(&self) -> [&str; 3] {
+        ["idle-watch", &self.session_id, &self.since]
+    }
+}
+
+/// トランスクリプトの最後の操作が `/exit`（`/quit`）であればtrue
+///
+/// SessionEndのフックが動かずに終了した場合でも、`idle-watch` が通知を続けないようにする。
+pub fn session_ended(transcript: &str) -> bool {
+    let mut ended = false;
+    for line in transcript.lines() {
+        let Ok(entry) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        match entry.get("type").and_then(|v| v.as_str()) {
+            Some("user") => {
+                let content = entry.pointer("/message/content").map(|c| c.to_string()).unwrap_or_default();
+                if EXIT_COMMANDS.iter().any(|command| content.contains(command)) {
+                    ended = true;
+                } else if !content.contains("<local-command-stdout>") {
+                    ended = false;
+                }
+            }
+            Some("assistant") => ended = false,
+            _ => {}
+        }
+    }
+    ended
+}
+
+/// セッションを終了するスラッシュコマンド（トランスクリプト上の表記）
+const EXIT_COMMANDS: [&str; 2] = ["<command-name>/exit</command-name>", "<command-name>/quit</command-name>"];
+
+/// `~/.claude/idle-state/<session_id>.json`
+pub fn state_path(home: &Path, session_id: &str) -> PathBuf {
+    home.join(".claude/idle-state")
+        .join(format!("{}.json", session_file_name(session_id)))
+}
@@ -0,0 +1,318 @@
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// settings.json に書き込むバイナリのディレクトリ
+pub const DEFAULT_BIN_DIR: &str = "~/.claude/bin";
+
+// ===== フックの定義 =====
+
+/// このクレートが提供するフック（settings.json の `hooks.<event>[].matcher` とバイナリ）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HookSpec {
+    pub event: &'static str,
+    pub matcher: &'static str,
+    pub binary: &'static str,
+}
+
+const fn hook(event: &'static str, matcher: &'static str, binary: &'static str) -> HookSpec {
+    HookSpec { event, matcher, binary }
+}
+
+/// settings.json に登録するフック（同じイベント内では記載順に実行される）
+pub const HOOKS: &[HookSpec] = &[
+    hook("PreToolUse", "Read|Write|Edit|MultiEdit|NotebookEdit|Glob|Grep", "path-policy-check"),
+    hook("PreToolUse", "WebFetch|WebSearch|Bash", "network-policy-check"),
+    hook("PreToolUse", "*", "format-tool-display"),
+    hook("Notification", "", "permission-notification"),
+    hook("PermissionRequest", "", "permission-notification"),
+    hook("Stop", "", "task-complete-notification"),
+    hook("Stop", "", "context-notification"),
+    hook("UserPromptSubmit", "", "user-prompt-slack"),
+    hook("PostToolUse", "ExitPlanMode", "exitplanmode-slack"),
+    hook("PostToolUse", "AskUserQuestion", "askuser-question-slack"),
+    hook("PostToolUse", "AskUserQuestion", "askuser-answer-slack"),
+    hook("PostToolUse", "Task", "subagent-slack"),
+    hook("SubagentStop", "", "subagent-slack"),
+    hook("PreCompact", "", "context-notification"),
+    hook("SessionStart", "", "session-context"),
+    hook("SessionEnd", "", "session-end-summary"),
+];
+
+impl HookSpec {
+    pub fn command(&self, bin_dir: &str) -> String {
+        format!("{}/{}", bin_dir.trim_end_matches('/'), self.binary)
+    }
+}
+
+/// インストールするバイナリ（フックのバイナリと、`doctor` を実行するための `claude-hooks`）
+pub fn binaries() -> Vec<&'static str> {
+    let mut names: Vec<&str> = vec!["claude-hooks"];
+    for spec in HOOKS {
+        if !names.contains(&spec.binary) {
+            names.push(spec.binary);
+        }
+    }
+    names
+}
+
+// ===== settings.json へのマージ =====
+
+/// 追加したフック
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddedHook {
+    pub event: String,
+    pub matcher: String,
+    pub command: String,
+}
+
+/// クレートのフックを settings.json にマージする（何度実行しても同じ結果になる）
+///
+/// 同じイベントに同じコマンドが既にあれば（matcherが異なっても）ユーザーの設定として残し、追加しない。
+/// 無ければ同じmatcherのグループに追加し、グループも無ければ新しく作る。その他のキーは変更しない。
+pub fn merge_hooks(settings: &mut Value, bin_dir: &str, home: Option<&str>) -> Result<Vec<AddedHook>, String> {
+    let root = settings.as_object_mut().ok_or("settings.json is not a JSON object")?;
+    let hooks = root
+        .entry("hooks")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or("\"hooks\" is not a JSON object")?;
+
+    let mut added = Vec::new();
+    for spec in HOOKS {
+        let command = spec.command(bin_dir);
+        let groups = hooks
+            .entry(spec.event)
+            .or_insert_with(|| Value::Array(Vec::new()))
+            .as_array_mut()
+            .ok_or_else(|| format!("\"hooks.{}\" is not an array", spec.event))?;
+
+        let installed = configured_commands(groups).any(|c| same_command(c, &command, home));
+        if installed {
+            continue;
+        }
+
+        let entry = json!({ "type": "command", "command": command });
+        let group = groups
+            .iter_mut()
+            .find(|g| g.get("matcher").and_then(|m| m.as_str()).unwrap_or("") == spec.matcher);
+        match group.and_then(|g| g.get_mut("hooks")).and_then(|h| h.as_array_mut()) {
+            Some(group_hooks) => group_hooks.push(entry),
+            None => groups.push(json!({ "matcher": spec.matcher, "hooks": [entry] })),
+        }
+        added.push(AddedHook {
+            event: spec.event.to_string(),
+            matcher: spec.matcher.to_string(),
+            command,
+        });
+    }
+    Ok(added)
+}
+
+/// イベントのグループに登録されたコマンド
+pub fn configured_commands(groups: &[Value]) -> impl Iterator<Item = &str> {
+    groups
+        .iter()
+        .filter_map(|g| g.get("hooks").and_then(|h| h.as_array()))
+        .flatten()
+        .filter_map(|h| h.get("command").and_then(|c| c.as_str()))
+}
+
+/// `~` を展開して比較する
+fn same_command(a: &str, b: &str, home: Option<&str>) -> bool {
+    expand_home(a.trim(), home) == expand_home(b.trim(), home)
+}
+
+/// 先頭の `~` をホームディレクトリに展開する
+pub fn expand_home(path: &str, home: Option<&str>) -> PathBuf {
+    match (path.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => Path::new(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// プロジェクトごとの設定から `project` に一致するものを探す（キーの `~` と末尾の `/` は無視する）
+pub fn project_override<'a, T>(
+    projects: &'a HashMap<String, T>,
+    project: &str,
+    home: Option<&str>,
+) -> Option<&'a T> {
+    let project = Path::new(project.trim_end_matches('/'));
+    projects
+        .iter()
+        .find(|(path, _)| expand_home(path.trim_end_matches('/'), home) == project)
+        .map(|(_, value)| value)
+}
+
+// ===== ファイル操作 =====
+
+/// settings.json を読み込む（存在しない場合は空のオブジェクト）
+pub fn read_settings(path: &Path) -> io::Result<Value> {
+    if !path.exists() {
+        return Ok(json!({}));
+    }
+    let content = fs::read_to_string(path)?;
+    serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// settings.json を書き込む。既存のファイルは `settings.json.bak` に退避し、退避先を返す
+///
+/// 既存のキーは元のファイルの順序で書き出し、新しいキーはその後ろに並べる。
+pub fn write_settings(path: &Path, settings: &Value) -> io::Result<Option<PathBuf>> {
+    let (backup, order) = if path.exists() {
+        let order = serde_json::from_str(&fs::read_to_string(path)?).unwrap_or_default();
+        let backup = path.with_extension("json.bak");
+        fs::copy(path, &backup)?;
+        (Some(backup), order)
+    } else {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        (None, KeyOrder::default())
+    };
+    let ordered = Ordered {
+        value: settings,
+        order: &order,
+    };
+    let content = serde_json::to_string_pretty(&ordered).map_err(io::Error::other)?;
+    fs::write(path, content + "\n")?;
+    Ok(backup)
+}
+
+// ===== キーの順序 =====
+
+/// JSONのオブジェクトのキーの順序（`serde_json::Map` はキーを並べ替えるため、書き戻すときに元の順序に戻す）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyOrder {
+    keys: Vec<(String, KeyOrder)>,
+    items: Vec<KeyOrder>,
+}
+
+/// 元のファイルに無いキーのうち先に書き出すもの（`{"matcher", "hooks"}` / `{"type", "command"}` の順にする）
+const LEADING_KEYS: &[&str] = &["matcher", "type"];
+
+static NO_ORDER: KeyOrder = KeyOrder {
+    keys: Vec::new(),
+    items: Vec::new(),
+};
+
+impl<'de> Deserialize<'de> for KeyOrder {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<KeyOrder, D::Error> {
+        deserializer.deserialize_any(KeyOrderVisitor)
+    }
+}
+
+struct KeyOrderVisitor;
+
+impl<'de> Visitor<'de> for KeyOrderVisitor {
+    type Value = KeyOrder;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<KeyOrder, E> {
+        Ok(KeyOrder::default())
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<KeyOrder, E> {
+        Ok(KeyOrder::default())
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<KeyOrder, E> {
+        Ok(KeyOrder::default())
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<KeyOrder, E> {
+        Ok(KeyOrder::default())
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<KeyOrder, E> {
+        Ok(KeyOrder::default())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<KeyOrder, E> {
+        Ok(KeyOrder::default())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<KeyOrder, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(KeyOrder { keys: Vec::new(), items })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<KeyOrder, A::Error> {
+        let mut keys: Vec<(String, KeyOrder)> = Vec::new();
+        while let Some((key, order)) = map.next_entry::<String, KeyOrder>()? {
+            // 重複したキーは後の値が使われる
+            keys.retain(|(k, _)| *k != key);
+            keys.push((key, order));
+        }
+        Ok(KeyOrder { keys, items: Vec::new() })
+    }
+}
+
+/// `order` の順にキーを並べて書き出す値（`order` に無いキーは後ろ）
+struct Ordered<'a> {
+    value: &'a Value,
+    order: &'a KeyOrder,
+}
+
+impl Serialize for Ordered<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            Value::Object(object) => {
+                let mut map = serializer.serialize_map(Some(object.len()))?;
+                for (key, order) in self.order.keys.iter().filter(|(k, _)| object.contains_key(k)) {
+                    map.serialize_entry(key, &Ordered { value: &object[key], order })?;
+                }
+                let mut added: Vec<(&String, &Value)> =
+                    object.iter().filter(|(k, _)| !self.order.keys.iter().any(|(o, _)| o == *k)).collect();
+                added.sort_by_key(|(k, _)| !LEADING_KEYS.contains(&k.as_str()));
+                for (key, value) in added {
+                    map.serialize_entry(key, &Ordered { value, order: &NO_ORDER })?;
+                }
+                map.end()
+            }
+            Value::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for (i, value) in items.iter().enumerate() {
+                    let order = self.order.items.get(i).unwrap_or(&NO_ORDER);
+                    seq.serialize_element(&Ordered { value, order })?;
+                }
+                seq.end()
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+/// ビルドしたバイナリを `bin_dir` にコピーして実行権限を付ける（コピーしたパスを返す）
+///
+/// `source_dir` に無いバイナリと、コピー元とコピー先が同じファイルは飛ばす。
+pub fn install_binaries(source_dir: &Path, bin_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(bin_dir)?;
+    let mut copied = Vec::new();
+    for name in binaries() {
+        let source = source_dir.join(name);
+        let dest = bin_dir.join(name);
+        if !source.is_file() {
+            continue;
+        }
+        if fs::canonicalize(&source).ok() == fs::canonicalize(&dest).ok() {
+            continue;
+        }
+        fs::copy(&source, &dest)?;
+        fs::set_permissions(&dest, fs::Permissions::from_mode(0o755))?;
+        copied.push(dest);
+    }
+    Ok(copied)
+}
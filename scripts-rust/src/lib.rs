@@ -1,9 +1,44 @@
 use serde::Deserialize;
-use std::env;
-use std::fs::{self, File};
-use std::io::{self, BufRead, BufReader};
+use std::io;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+
+pub mod bash_risk;
+pub mod changes;
+pub mod config;
+pub mod context;
+pub mod dashboard;
+pub mod decision;
+pub mod diff_preview;
+pub mod doctor;
+pub mod focus;
+pub mod git;
+pub mod handlers;
+pub mod i18n;
+pub mod idle;
+pub mod install;
+pub mod mcp;
+pub mod network_policy;
+pub mod path_policy;
+pub mod plan;
+pub mod prompt;
+pub mod replay;
+pub mod shell;
+pub mod session_context;
+pub mod sinks;
+pub mod slack;
+pub mod slack_markdown;
+pub mod subagent;
+pub mod system;
+pub mod text;
+pub mod timeline;
+pub mod tool_display;
+
+pub use changes::ChangeSummary;
+pub use config::HooksConfig;
+pub use i18n::{t, tf};
+pub use mcp::McpServerRegistry;
+pub use git::GitContext;
+pub use system::{RealSystem, System};
 
 // ===== 型定義 =====
 
@@ -12,6 +47,8 @@ pub struct HookInput {
     pub session_id: String,
     pub cwd: String,
     #[serde(default)]
+    pub transcript_path: Option<String>,
+    #[serde(default)]
     pub tool_name: Option<String>,
     #[serde(default)]
     pub tool_input: Option<serde_json::Value>,
@@ -28,6 +65,31 @@ pub struct StopHookInput {
     pub cwd: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct SessionEndInput {
+    pub session_id: String,
+    pub transcript_path: Option<String>,
+    pub cwd: String,
+    /// "clear" / "logout" / "prompt_input_exit" / "other"
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SubagentStopInput {
+    pub session_id: String,
+    /// 親セッションのトランスクリプト
+    pub transcript_path: Option<String>,
+    pub cwd: String,
+    #[serde(default)]
+    pub stop_hook_active: bool,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    /// サブエージェント自身のトランスクリプト
+    #[serde(default)]
+    pub agent_transcript_path: Option<String>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct LockFileData {
     pub pid: u32,
@@ -77,34 +139,35 @@ pub struct PostToolUseInput {
 
 // ===== ターミナル検出 =====
 
-pub fn detect_terminal_bundle_id() -> Option<String> {
+pub fn detect_terminal_bundle_id(system: &dyn System) -> Option<String> {
     // 1. TERM_PROGRAM環境変数で検出
-    if let Ok(term_program) = env::var("TERM_PROGRAM") {
+    if let Some(term_program) = system.env_var("TERM_PROGRAM") {
         match term_program.as_str() {
             "iTerm.app" => return Some("com.googlecode.iterm2".to_string()),
             "Apple_Terminal" => return Some("com.apple.Terminal".to_string()),
             "WarpTerminal" => return Some("dev.warp.Warp-Stable".to_string()),
             "Hyper" => return Some("co.zeit.hyper".to_string()),
+            "WezTerm" => return Some("com.github.wez.wezterm".to_string()),
             _ => {}
         }
     }
 
     // 2. ターミナル固有の環境変数で検出
-    if env::var("ITERM_SESSION_ID").is_ok() {
+    if system.env_var("ITERM_SESSION_ID").is_some() {
         return Some("com.googlecode.iterm2".to_string());
     }
-    if env::var("ALACRITTY_SOCKET").is_ok() {
+    if system.env_var("ALACRITTY_SOCKET").is_some() {
         return Some("io.alacritty.Alacritty".to_string());
     }
-    if env::var("KITTY_WINDOW_ID").is_ok() {
+    if system.env_var("KITTY_WINDOW_ID").is_some() {
         return Some("net.kovidgoyal.kitty".to_string());
     }
-    if env::var("WARP_IS_LOCAL_SHELL_SESSION").is_ok() {
+    if system.env_var("WARP_IS_LOCAL_SHELL_SESSION").is_some() {
         return Some("dev.warp.Warp-Stable".to_string());
     }
 
     // 3. LC_TERMINAL環境変数で検出
-    if let Ok(lc_terminal) = env::var("LC_TERMINAL") {
+    if let Some(lc_terminal) = system.env_var("LC_TERMINAL") {
         match lc_terminal.as_str() {
             "iTerm2" => return Some("com.googlecode.iterm2".to_string()),
             "Terminal" => return Some("com.apple.Terminal".to_string()),
@@ -113,7 +176,7 @@ pub fn detect_terminal_bundle_id() -> Option<String> {
     }
 
     // 4. TERM環境変数で推測
-    if let Ok(term) = env::var("TERM") {
+    if let Some(term) = system.env_var("TERM") {
         match term.as_str() {
             "xterm-kitty" => return Some("net.kovidgoyal.kitty".to_string()),
             "alacritty" => return Some("io.alacritty.Alacritty".to_string()),
@@ -127,71 +190,49 @@ pub fn detect_terminal_bundle_id() -> Option<String> {
 
 // ===== IDE検出 =====
 
-pub fn detect_ide_bundle_id() -> Option<String> {
-    let home = env::var("HOME").ok()?;
-    let lock_dir = Path::new(&home).join(".claude/ide");
-
-    if !lock_dir.exists() {
-        return None;
-    }
-
-    // 最新のlockファイルを取得
-    let latest_lock = find_latest_lock_file(&lock_dir)?;
-
-    // JSON解析してPID取得
-    let file = File::open(&latest_lock).ok()?;
-    let lock_data: LockFileData = serde_json::from_reader(file).ok()?;
+pub fn detect_ide_bundle_id(system: &dyn System) -> Option<String> {
+    let lock_data = latest_ide_lock(system)?;
 
     // プロセスが実行中か確認
-    if !is_process_running(lock_data.pid) {
+    if !is_process_running(system, lock_data.pid) {
         return None;
     }
 
     // Bundle ID取得
-    get_bundle_id_from_pid(lock_data.pid)
+    get_bundle_id_from_pid(system, lock_data.pid)
 }
 
-fn find_latest_lock_file(lock_dir: &Path) -> Option<PathBuf> {
-    let mut lock_files: Vec<_> = fs::read_dir(lock_dir)
+/// `~/.claude/ide/` の最新のlockファイルの内容（ディレクトリが無い場合はNone）
+pub fn latest_ide_lock(system: &dyn System) -> Option<LockFileData> {
+    let lock_dir = system.home_dir()?.join(".claude/ide");
+    let latest_lock = find_latest_lock_file(system, &lock_dir)?;
+    let content = system.read_to_string(&latest_lock).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn find_latest_lock_file(system: &dyn System, lock_dir: &Path) -> Option<PathBuf> {
+    let mut lock_files: Vec<PathBuf> = system
+        .read_dir(lock_dir)
         .ok()?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            entry
-                .path()
-                .extension()
-                .and_then(|s| s.to_str())
-                .map(|s| s == "lock")
-                .unwrap_or(false)
-        })
+        .into_iter()
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("lock"))
         .collect();
 
-    lock_files.sort_by_key(|entry| {
-        entry
-            .metadata()
-            .and_then(|m| m.modified())
-            .ok()
-            .map(|t| std::cmp::Reverse(t))
-    });
+    lock_files.sort_by_key(|path| system.modified(path).ok().map(std::cmp::Reverse));
 
-    lock_files.first().map(|entry| entry.path())
+    lock_files.into_iter().next()
 }
 
-fn is_process_running(pid: u32) -> bool {
-    Command::new("ps")
-        .args(&["-p", &pid.to_string()])
-        .output()
-        .map(|output| output.status.success())
+fn is_process_running(system: &dyn System, pid: u32) -> bool {
+    system
+        .run("ps", &["-p", &pid.to_string()])
+        .map(|output| output.success)
         .unwrap_or(false)
 }
 
-fn get_bundle_id_from_pid(pid: u32) -> Option<String> {
+fn get_bundle_id_from_pid(system: &dyn System, pid: u32) -> Option<String> {
     // ps -p {pid} -o comm= でアプリケーションパスを取得
-    let output = Command::new("ps")
-        .args(&["-p", &pid.to_string(), "-o", "comm="])
-        .output()
-        .ok()?;
-
-    let comm = String::from_utf8(output.stdout).ok()?;
+    let comm = system.run("ps", &["-p", &pid.to_string(), "-o", "comm="]).ok()?.stdout;
     let app_path = comm
         .lines()
         .next()?
@@ -205,16 +246,10 @@ fn get_bundle_id_from_pid(pid: u32) -> Option<String> {
     }
 
     // mdls -name kMDItemCFBundleIdentifier でBundle IDを取得
-    let output = Command::new("mdls")
-        .args(&[
-            "-name",
-            "kMDItemCFBundleIdentifier",
-            &app_path,
-        ])
-        .output()
-        .ok()?;
-
-    let mdls_output = String::from_utf8(output.stdout).ok()?;
+    let mdls_output = system
+        .run("mdls", &["-name", "kMDItemCFBundleIdentifier", &app_path])
+        .ok()?
+        .stdout;
     let bundle_id = mdls_output
         .split('"')
         .nth(1)?
@@ -225,13 +260,13 @@ fn get_bundle_id_from_pid(pid: u32) -> Option<String> {
 
 // ===== 統合検出 =====
 
-pub fn get_activation_bundle_id() -> String {
+pub fn get_activation_bundle_id(system: &dyn System) -> String {
     // 1. ターミナルが明示的に検出された場合はそれを使用
-    if let Some(terminal_id) = detect_terminal_bundle_id() {
+    if let Some(terminal_id) = detect_terminal_bundle_id(system) {
         return terminal_id;
     }
     // 2. IDE検出
-    if let Some(ide_id) = detect_ide_bundle_id() {
+    if let Some(ide_id) = detect_ide_bundle_id(system) {
         return ide_id;
     }
     // 3. フォールバック
@@ -240,7 +275,13 @@ pub fn get_activation_bundle_id() -> String {
 
 // ===== 通知送信 =====
 
+/// デスクトップ通知のタイトル・サブタイトルの上限（表示幅）
+pub const NOTIFICATION_TITLE_WIDTH: usize = 80;
+/// デスクトップ通知の本文の上限（表示幅）
+pub const NOTIFICATION_BODY_WIDTH: usize = 300;
+
 pub fn send_notification(
+    system: &dyn System,
     title: &str,
     message: &str,
     subtitle: &str,
@@ -248,15 +289,20 @@ pub fn send_notification(
     sound: &str,
 ) -> io::Result<()> {
     let mut args = vec![
-        "-title".to_string(), title.to_string(),
-        "-message".to_string(), message.to_string(),
-        "-subtitle".to_string(), subtitle.to_string(),
+        "-title".to_string(), text::truncate_width(title, NOTIFICATION_TITLE_WIDTH),
+        "-message".to_string(), text::truncate_width(message, NOTIFICATION_BODY_WIDTH),
+        "-subtitle".to_string(), text::truncate_width(subtitle, NOTIFICATION_TITLE_WIDTH),
         "-sound".to_string(), sound.to_string(),
     ];
 
-    // iTerm2の場合: -execute で特定セッションに移動
-    if bundle_id == "com.googlecode.iterm2" {
-        if let Some(execute_cmd) = build_iterm2_activate_command() {
+    // tmux / kitty / WezTerm / IDEの場合: -execute で `claude-hooks focus` を実行してペイン・ウィンドウに移動
+    let focus = focus::Focus::detect(system).filter(|f| !matches!(f.target, focus::FocusTarget::ITerm2 { .. }));
+    if let Some(focus) = focus {
+        let home = system.home_dir();
+        args.extend(["-execute".to_string(), focus.execute_command(home.as_deref().and_then(|h| h.to_str()))]);
+    } else if bundle_id == "com.googlecode.iterm2" {
+        // iTerm2の場合: -execute で特定セッションに移動
+        if let Some(execute_cmd) = build_iterm2_activate_command(system) {
             args.extend(["-execute".to_string(), execute_cmd]);
         }
     }
@@ -264,55 +310,62 @@ pub fn send_notification(
     // すべての場合: -activate でアプリをアクティブ化
     args.extend(["-activate".to_string(), bundle_id.to_string()]);
 
-    Command::new("terminal-notifier")
-        .args(args.iter().map(|s| s.as_str()).collect::<Vec<_>>())
-        .output()?;
+    system.run("terminal-notifier", &args.iter().map(|s| s.as_str()).collect::<Vec<_>>())?;
 
     Ok(())
 }
 
-fn build_iterm2_activate_command() -> Option<String> {
-    let session_id = env::var("ITERM_SESSION_ID").ok()?;
+fn build_iterm2_activate_command(system: &dyn System) -> Option<String> {
+    let guid = iterm2_session_guid(system)?;
+
+    // AppleScriptでセッションIDに一致するセッションを選択
+    Some(build_iterm2_osascript(&guid))
+}
+
+/// `ITERM_SESSION_ID`（`w0t0p0:GUID`）のGUID部分
+fn iterm2_session_guid(system: &dyn System) -> Option<String> {
+    let session_id = system.env_var("ITERM_SESSION_ID")?;
     let guid = session_id.split(':').nth(1)?;
     if guid.is_empty() {
         return None;
     }
+    Some(guid.to_string())
+}
 
-    // AppleScriptでセッションIDに一致するセッションを選択
-    Some(build_iterm2_osascript(guid))
+pub fn build_iterm2_osascript(guid: &str) -> String {
+    let args: Vec<String> = iterm2_osascript_args(guid)
+        .chunks(2)
+        .map(|pair| format!("{} {}", pair[0], focus::shell_quote(&pair[1])))
+        .collect();
+    format!("osascript {}", args.join(" "))
 }
 
-fn build_iterm2_osascript(guid: &str) -> String {
-    format!(
-        r#"osascript -e 'tell application "iTerm2"' -e 'activate' -e 'repeat with w in windows' -e 'tell w' -e 'repeat with t in tabs' -e 'tell t' -e 'repeat with s in sessions' -e 'if id of s is "{}" then' -e 'select' -e 'end if' -e 'end repeat' -e 'end tell' -e 'end repeat' -e 'end tell' -e 'end repeat' -e 'end tell'"#,
-        guid
-    )
+/// GUIDに一致するiTerm2のセッションを選択する `osascript` の引数（`-e` と1行ずつ）
+pub fn iterm2_osascript_args(guid: &str) -> Vec<String> {
+    let select = format!(r#"if id of s is "{}" then"#, guid);
+    [
+        r#"tell application "iTerm2""#, "activate", "repeat with w in windows", "tell w", "repeat with t in tabs", "tell t",
+        "repeat with s in sessions", &select, "select", "end if", "end repeat", "end tell", "end repeat", "end tell",
+        "end repeat", "end tell",
+    ]
+    .iter()
+    .flat_map(|line| ["-e".to_string(), line.to_string()])
+    .collect()
 }
 
-pub fn build_iterm2_url_scheme() -> Option<String> {
-    let session_id = env::var("ITERM_SESSION_ID").ok()?;
-    let guid = session_id.split(':').nth(1)?;
-    if guid.is_empty() {
-        return None;
-    }
+pub fn build_iterm2_url_scheme(system: &dyn System) -> Option<String> {
+    let guid = iterm2_session_guid(system)?;
     Some(format!("x-claude-iterm://switch?guid={}", guid))
 }
 
 // ===== ユーティリティ =====
 
-pub fn get_git_branch(cwd: &str) -> Option<String> {
-    Command::new("git")
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-        .current_dir(cwd)
-        .output()
+pub fn get_git_branch(system: &dyn System, cwd: &str) -> Option<String> {
+    system
+        .run("git", &["-C", cwd, "rev-parse", "--abbrev-ref", "HEAD"])
         .ok()
-        .and_then(|output| {
-            if output.status.success() {
-                String::from_utf8(output.stdout).ok().map(|s| s.trim().to_string())
-            } else {
-                None
-            }
-        })
+        .filter(|output| output.success)
+        .map(|output| output.stdout.trim().to_string())
 }
 
 pub fn get_dir_name(cwd: &str) -> String {
@@ -323,6 +376,14 @@ pub fn get_dir_name(cwd: &str) -> String {
         .to_string()
 }
 
+/// セッションごとのファイル名に使うセッションID（パス区切りなどは `_` に置き換え、ディレクトリ外に出ないようにする）
+pub fn session_file_name(session_id: &str) -> String {
+    session_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
 pub fn get_relative_path(file_path: &str, cwd: &str) -> String {
     if file_path.starts_with(cwd) {
         file_path.strip_prefix(cwd)
@@ -340,57 +401,68 @@ pub fn get_relative_path(file_path: &str, cwd: &str) -> String {
 
 // ===== トランスクリプト解析 =====
 
-pub fn extract_user_prompt(transcript_path: &str) -> io::Result<String> {
-    let file = File::open(transcript_path)?;
-    let reader = BufReader::new(file);
+pub fn extract_user_prompt(system: &dyn System, transcript_path: &str) -> io::Result<String> {
+    Ok(last_user_prompt(&system.read_to_string(Path::new(transcript_path))?))
+}
 
+/// トランスクリプト（JSONL）の最後のユーザープロンプト
+pub fn last_user_prompt(transcript: &str) -> String {
     let mut messages: Vec<String> = Vec::new();
 
-    for line in reader.lines() {
-        let line = line?;
+    for line in transcript.lines() {
         if line.trim().is_empty() {
             continue;
         }
 
-        if let Ok(msg) = serde_json::from_str::<TranscriptMessage>(&line) {
-            if msg.msg_type == "user" && msg.is_meta != Some(true) {
-                if let Some(message_content) = msg.message {
-                    if message_content.role == "user" {
-                        let content_str = extract_text_content(&message_content.content);
-                        if !content_str.is_empty()
-                            && !content_str.contains("<command-name>")
-                            && !content_str.starts_with("Caveat:")
-                            && !content_str.starts_with("[Request interrupted by user for tool use]")
-                        {
-                            messages.push(content_str);
-                        }
-                    }
-                }
+        if let Ok(msg) = serde_json::from_str::<TranscriptMessage>(line) {
+            if let Some(content_str) = user_prompt_text(&msg) {
+                messages.push(content_str);
             }
         }
     }
 
-    let prompt = messages
+    messages
         .last()
         .cloned()
-        .unwrap_or_else(|| "リクエスト".to_string());
+        .unwrap_or_else(|| t("fallback.request").to_string())
+}
 
-    Ok(prompt)
+/// トランスクリプトの1エントリがユーザーの入力したプロンプトであればその本文を返す
+///
+/// メタメッセージ・スラッシュコマンド・tool_resultのみのメッセージは除外する。
+pub fn user_prompt_text(msg: &TranscriptMessage) -> Option<String> {
+    if msg.msg_type != "user" || msg.is_meta == Some(true) {
+        return None;
+    }
+    let message_content = msg.message.as_ref()?;
+    if message_content.role != "user" {
+        return None;
+    }
+    let content_str = extract_text_content(&message_content.content);
+    if content_str.is_empty()
+        || content_str.contains("<command-name>")
+        || content_str.starts_with("Caveat:")
+        || content_str.starts_with("[Request interrupted by user for tool use]")
+    {
+        return None;
+    }
+    Some(content_str)
 }
 
-pub fn extract_assistant_message(transcript_path: &str) -> io::Result<String> {
-    let file = File::open(transcript_path)?;
-    let reader = BufReader::new(file);
+pub fn extract_assistant_message(system: &dyn System, transcript_path: &str) -> io::Result<String> {
+    Ok(last_assistant_message(&system.read_to_string(Path::new(transcript_path))?))
+}
 
+/// トランスクリプト（JSONL）の最後のアシスタントメッセージ
+pub fn last_assistant_message(transcript: &str) -> String {
     let mut messages: Vec<String> = Vec::new();
 
-    for line in reader.lines() {
-        let line = line?;
+    for line in transcript.lines() {
         if line.trim().is_empty() {
             continue;
         }
 
-        if let Ok(msg) = serde_json::from_str::<TranscriptMessage>(&line) {
+        if let Ok(msg) = serde_json::from_str::<TranscriptMessage>(line) {
             if msg.msg_type == "assistant" {
                 if let Some(message_content) = msg.message {
                     if message_content.role == "assistant" {
@@ -404,12 +476,10 @@ pub fn extract_assistant_message(transcript_path: &str) -> io::Result<String> {
         }
     }
 
-    let message = messages
+    messages
         .last()
         .cloned()
-        .unwrap_or_else(|| "タスクが完了しました".to_string());
-
-    Ok(message)
+        .unwrap_or_else(|| t("fallback.task_complete").to_string())
 }
 
 fn extract_text_content(content: &serde_json::Value) -> String {
@@ -432,94 +502,151 @@ fn extract_text_content(content: &serde_json::Value) -> String {
 }
 
 
-pub fn log_to_file(user_prompt: &str, assistant_message: &str) -> io::Result<()> {
-    use std::fs::OpenOptions;
-    use std::io::Write;
-
-    let home = env::var("HOME").map_err(|_| io::Error::new(io::ErrorKind::NotFound, "HOME not set"))?;
-    let log_path = Path::new(&home).join(".claude/task-complete.log");
+pub fn log_to_file(system: &dyn System, cwd: &str, user_prompt: &str, assistant_message: &str) -> io::Result<()> {
+    let home = system
+        .home_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME not set"))?;
+    let timestamp = system.now().format("%Y-%m-%d %H:%M:%S").to_string();
+    system.append_file(
+        &home.join(TASK_COMPLETE_LOG),
+        &format_log_entry(&timestamp, cwd, user_prompt, assistant_message),
+    )
+}
 
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(log_path)?;
+/// `log_to_file` のホームディレクトリからのパス
+pub const TASK_COMPLETE_LOG: &str = ".claude/task-complete.log";
 
-    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
-    writeln!(file, "[{}]", timestamp)?;
-    writeln!(file, "  User Prompt: {}", user_prompt)?;
-    writeln!(file, "  Assistant: {}", assistant_message)?;
-    writeln!(file)?;
+/// `task-complete.log` の1回分のエントリ
+pub fn format_log_entry(timestamp: &str, cwd: &str, user_prompt: &str, assistant_message: &str) -> String {
+    format!(
+        "[{}]\n  Directory: {}\n  User Prompt: {}\n  Assistant: {}\n\n",
+        timestamp, cwd, user_prompt, assistant_message
+    )
+}
 
-    Ok(())
+/// `task-complete.log` から読み込んだエントリ
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskLogEntry {
+    pub timestamp: String,
+    /// 作業ディレクトリ（記録していない古いエントリはNone）
+    pub directory: Option<String>,
+    pub user_prompt: String,
+    pub assistant: String,
 }
 
-// ===== Slack通知 =====
+/// `task-complete.log` を古い順のエントリに分ける（複数行のプロンプトや応答は続きの行として扱う）
+pub fn parse_log_entries(log: &str) -> Vec<TaskLogEntry> {
+    let mut entries: Vec<TaskLogEntry> = Vec::new();
+    // 続きの行を追加する先（true: 応答、false: プロンプト）
+    let mut in_assistant = false;
+
+    for line in log.lines() {
+        if let Some(timestamp) = log_header(line) {
+            entries.push(TaskLogEntry {
+                timestamp: timestamp.to_string(),
+                ..TaskLogEntry::default()
+            });
+            in_assistant = false;
+            continue;
+        }
+        let Some(entry) = entries.last_mut() else {
+            continue;
+        };
+        if let Some(directory) = line.strip_prefix("  Directory: ") {
+            entry.directory = Some(directory.to_string());
+        } else if let Some(prompt) = line.strip_prefix("  User Prompt: ") {
+            entry.user_prompt = prompt.to_string();
+        } else if let Some(message) = line.strip_prefix("  Assistant: ") {
+            entry.assistant = message.to_string();
+            in_assistant = true;
+        } else {
+            let field = if in_assistant { &mut entry.assistant } else { &mut entry.user_prompt };
+            field.push('\n');
+            field.push_str(line);
+        }
+    }
 
-pub fn post_to_slack_rich(title: &str, fields: &[(&str, &str)], button_url: Option<&str>) -> Result<(), String> {
-    let webhook_url = match env::var("CLAUDE_CODE_SLACK_WEBHOOK_URL") {
-        Ok(url) if !url.is_empty() => url,
-        _ => return Ok(()), // 環境変数が設定されていない場合はスキップ
-    };
+    for entry in &mut entries {
+        entry.assistant.truncate(entry.assistant.trim_end().len());
+    }
+    entries
+}
 
-    // Slack Block Kit形式のペイロードを構築
-    let mut blocks = Vec::new();
+/// `[2026-01-01 09:00:00]` の行の時刻
+fn log_header(line: &str) -> Option<&str> {
+    let timestamp = line.strip_prefix('[')?.strip_suffix(']')?;
+    chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S").ok()?;
+    Some(timestamp)
+}
 
-    // ヘッダーブロック
-    blocks.push(ureq::json!({
-        "type": "header",
-        "text": {
-            "type": "plain_text",
-            "text": title,
-        }
-    }));
-
-    // フィールドブロック
-    if !fields.is_empty() {
-        let field_elements: Vec<_> = fields
-            .iter()
-            .map(|(label, value)| {
-                ureq::json!({
-                    "type": "mrkdwn",
-                    "text": format!("*{}*\n{}", label, value)
-                })
-            })
-            .collect();
+// ===== Slack通知 =====
 
-        blocks.push(ureq::json!({
-            "type": "section",
-            "fields": field_elements
-        }));
-    }
+pub fn post_to_slack_rich(
+    system: &dyn System,
+    title: &str,
+    fields: &[(&str, &str)],
+    button_url: Option<&str>,
+) -> Result<(), String> {
+    post_to_slack_thread(system, title, fields, None, button_url, None).map(|_| ())
+}
 
-    // iTerm2で開くリンク（Incoming Webhookはactionsブロック非対応のためsectionで実装）
-    if let Some(url) = button_url {
-        blocks.push(ureq::json!({
+/// フィールドに加えてMarkdown本文をBlock Kitに変換して送信する（プランやアシスタントの応答向け）
+///
+/// 本文が長い場合は切り詰めずに複数のメッセージ（Botモードではスレッド）に分けて送信する。
+pub fn post_to_slack_markdown(
+    system: &dyn System,
+    title: &str,
+    fields: &[(&str, &str)],
+    body_label: &str,
+    markdown: &str,
+    button_url: Option<&str>,
+) -> Result<(), String> {
+    post_to_slack_thread(system, title, fields, Some((body_label, markdown)), button_url, None).map(|_| ())
+}
+
+/// `post_to_slack_rich` / `post_to_slack_markdown` を `thread` への返信として送信する
+///
+/// `markdown` は（見出し, Markdown本文）。Botモードでは送信したメッセージのスレッドを返す。
+pub fn post_to_slack_thread(
+    system: &dyn System,
+    title: &str,
+    fields: &[(&str, &str)],
+    markdown: Option<(&str, &str)>,
+    button_url: Option<&str>,
+    thread: Option<&slack::SlackThread>,
+) -> Result<Option<slack::SlackThread>, String> {
+    let mut body = Vec::new();
+    if let Some((body_label, markdown)) = markdown {
+        body.push(ureq::json!({
             "type": "section",
             "text": {
                 "type": "mrkdwn",
-                "text": format!(":computer: <{}|iTerm2 で開く>", url)
+                "text": format!("*{}*", body_label),
             }
         }));
+        body.extend(slack_markdown::markdown_to_blocks(markdown));
     }
 
-    let payload = ureq::json!({
-        "blocks": blocks
-    });
-
-    ureq::post(&webhook_url)
-        .set("Content-Type", "application/json")
-        .send_json(payload)
-        .map(|_| ())
-        .map_err(|e| format!("Slack POST failed: {}", e))
+    let message = slack::SlackMessage {
+        title,
+        fields,
+        body,
+        button_url,
+        full_text: markdown.map(|(_, markdown)| markdown),
+        thread,
+    };
+    slack::send(system, &slack::SlackMode::from_env(system), &message)
 }
 
 // ===== コンテンツ処理 =====
 
-/// コンテンツを指定の長さで切り詰める
+/// コンテンツを指定の長さ（書記素クラスタ数）で切り詰める
 pub fn truncate_content(content: &str) -> String {
     const MAX_LENGTH: usize = 2800;
-    if content.len() > MAX_LENGTH {
-        let truncated = &content[..MAX_LENGTH];
+    if text::grapheme_count(content) > MAX_LENGTH {
+        let truncated: String = unicode_segmentation::UnicodeSegmentation::graphemes(content, true)
+            .take(MAX_LENGTH)
+            .collect();
         format!("{}...\n\n(truncated)", truncated)
     } else {
         content.to_string()
@@ -530,7 +657,7 @@ pub fn truncate_content(content: &str) -> String {
 pub fn extract_questions_with_options(tool_input: &serde_json::Value) -> String {
     let questions = match tool_input.get("questions").and_then(|q| q.as_array()) {
         Some(arr) => arr,
-        None => return "N/A".to_string(),
+        None => return t("fallback.not_available").to_string(),
     };
 
     let mut result = Vec::new();
@@ -539,7 +666,7 @@ pub fn extract_questions_with_options(tool_input: &serde_json::Value) -> String
         let question_text = q
             .get("question")
             .and_then(|v| v.as_str())
-            .unwrap_or("N/A");
+            .unwrap_or(t("fallback.not_available"));
 
         let header = q
             .get("header")
@@ -570,7 +697,7 @@ pub fn extract_questions_with_options(tool_input: &serde_json::Value) -> String
                 .collect();
 
             if !option_strs.is_empty() {
-                question_str.push_str("\n");
+                question_str.push('\n');
                 question_str.push_str(&option_strs.join("\n"));
             }
         }
@@ -580,3 +707,195 @@ pub fn extract_questions_with_options(tool_input: &serde_json::Value) -> String
 
     result.join("\n\n")
 }
+
+// ===== ツール要約 =====
+
+/// ツール呼び出しの要約（PreToolUseの表示・権限リクエストの通知・Slackで共通）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolSummary {
+    pub emoji: String,
+    pub label: String,
+    /// 1行表示用の短い説明（ファイル名、短縮したコマンドなど）
+    pub short: Option<String>,
+    /// 通知本文用の説明（相対パス、URL全体など）
+    pub detail: Option<String>,
+    /// 省略せずに表示するコマンド（Slackではコードブロック）
+    pub code: Option<String>,
+}
+
+impl ToolSummary {
+    pub fn build(
+        tool_name: &str,
+        tool_input: Option<&serde_json::Value>,
+        cwd: &str,
+        mcp_servers: &mcp::McpServerRegistry,
+    ) -> ToolSummary {
+        let null = serde_json::Value::Null;
+        let input = tool_input.unwrap_or(&null);
+        let field = |key: &str| {
+            input
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+        };
+        let summary = |emoji: &str, label: &str| ToolSummary {
+            emoji: emoji.to_string(),
+            label: label.to_string(),
+            short: None,
+            detail: None,
+            code: None,
+        };
+        let file = |emoji: &str, label: &str, key: &str| ToolSummary {
+            short: field(key).map(|p| p.split('/').next_back().unwrap_or(p).to_string()),
+            detail: field(key).map(|p| get_relative_path(p, cwd)),
+            ..summary(emoji, label)
+        };
+        let pattern = |emoji: &str, label: &str| ToolSummary {
+            short: field("pattern").map(|p| text::truncate_width(p, 30)),
+            detail: field("pattern").map(|p| tf("tool.pattern", &[("pattern", p)])),
+            ..summary(emoji, label)
+        };
+
+        match tool_name {
+            "AskUserQuestion" => ToolSummary {
+                detail: input
+                    .pointer("/questions/0/question")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                ..summary("💬", t("tool.ask_user_question"))
+            },
+            "ExitPlanMode" => ToolSummary {
+                detail: field("plan")
+                    .and_then(|p| p.lines().map(str::trim).find(|l| !l.is_empty()))
+                    .map(|l| l.trim_start_matches('#').trim().to_string()),
+                ..summary("📋", t("tool.exit_plan_mode"))
+            },
+            "Bash" => {
+                let description = field("description");
+                let command = field("command");
+                ToolSummary {
+                    // コマンドが長い場合は中央を省略して先頭と末尾を残す
+                    short: description
+                        .map(str::to_string)
+                        .or_else(|| command.map(|c| text::truncate_middle(c, 50))),
+                    detail: description.map(str::to_string),
+                    code: command.map(str::to_string),
+                    ..summary("🔧", t("tool.bash"))
+                }
+            }
+            "Read" => file("📖", t("tool.read"), "file_path"),
+            "Write" => file("✍️", t("tool.write"), "file_path"),
+            "Edit" | "MultiEdit" => file("✏️", t("tool.edit"), "file_path"),
+            "NotebookEdit" => file("📓", t("tool.notebook_edit"), "notebook_path"),
+            "Grep" => pattern("🔍", t("tool.grep")),
+            "Glob" => pattern("🔍", t("tool.glob")),
+            "Task" => {
+                let agent_type = field("subagent_type").map(|s| tf("tool.agent_type", &[("type", s)]));
+                let detail: Vec<String> = field("description").map(str::to_string).into_iter().chain(agent_type).collect();
+                ToolSummary {
+                    short: field("description").map(str::to_string),
+                    detail: (!detail.is_empty()).then(|| detail.join("\n")),
+                    ..summary("🤖", t("tool.task"))
+                }
+            }
+            "WebFetch" => ToolSummary {
+                // URLからドメインを抽出
+                short: field("url").map(|url| {
+                    url.split("://")
+                        .nth(1)
+                        .and_then(|s| s.split('/').next())
+                        .unwrap_or(url)
+                        .to_string()
+                }),
+                detail: field("url").map(str::to_string),
+                ..summary("🌐", t("tool.web_fetch"))
+            },
+            "WebSearch" => ToolSummary {
+                short: field("query").map(|q| text::truncate_width(q, 30)),
+                detail: field("query").map(str::to_string),
+                ..summary("🔎", t("tool.web_search"))
+            },
+            "TodoWrite" => ToolSummary {
+                // 進行中のTODOを表示
+                detail: input
+                    .get("todos")
+                    .and_then(|v| v.as_array())
+                    .and_then(|todos| {
+                        todos
+                            .iter()
+                            .find(|todo| todo.get("status").and_then(|s| s.as_str()) == Some("in_progress"))
+                    })
+                    .and_then(|todo| todo.get("content").and_then(|c| c.as_str()))
+                    .map(str::to_string),
+                ..summary("✅", t("tool.todo_write"))
+            },
+            "Skill" => ToolSummary {
+                short: field("skill").map(str::to_string),
+                detail: field("skill").map(str::to_string),
+                ..summary("⚡", t("tool.skill"))
+            },
+            "SlashCommand" => ToolSummary {
+                short: field("command").map(str::to_string),
+                detail: field("command").map(str::to_string),
+                ..summary("⚙️", t("tool.slash_command"))
+            },
+            "BashOutput" => ToolSummary {
+                detail: field("bash_id").map(str::to_string),
+                ..summary("📤", t("tool.bash_output"))
+            },
+            "KillShell" => ToolSummary {
+                detail: field("shell_id").map(str::to_string),
+                ..summary("⛔", t("tool.kill_shell"))
+            },
+            _ => match mcp::McpTool::parse(tool_name) {
+                // MCPツールはサーバーの表示名と主要な引数を表示
+                Some(tool) => {
+                    let (emoji, label) = mcp_servers.describe(&tool);
+                    let arg = mcp::summarize_input(input);
+                    ToolSummary {
+                        short: arg.as_ref().map(|a| text::truncate_middle(&a.value, 40)),
+                        detail: arg.map(|a| format!("{}: {}", a.key, a.value)),
+                        ..summary(&emoji, &label)
+                    }
+                }
+                None => summary("▶️", tool_name),
+            },
+        }
+    }
+
+    /// "🔧 コマンド実行"（権限リクエストのサブタイトル）
+    pub fn title(&self) -> String {
+        format!("{} {}", self.emoji, self.label)
+    }
+
+    /// "🔧 コマンド実行: cargo test"（PreToolUseの1行表示）
+    pub fn short_line(&self) -> String {
+        match &self.short {
+            Some(short) => format!("{}: {}", self.title(), short),
+            None => self.title(),
+        }
+    }
+
+    /// デスクトップ通知の本文（説明が無い場合はツール名）
+    pub fn long_text(&self, tool_name: &str) -> String {
+        match (&self.detail, &self.code) {
+            (Some(detail), Some(code)) => format!("{}\n{}", detail, code),
+            (Some(text), None) | (None, Some(text)) => text.clone(),
+            (None, None) => tool_name.to_string(),
+        }
+    }
+
+    /// Slackのフィールド用（コマンドはコードブロックで表示）
+    pub fn slack_text(&self, tool_name: &str) -> String {
+        let code = self
+            .code
+            .as_ref()
+            .map(|c| format!("```\n{}\n```", c.replace("```", "` ` `")));
+        match (&self.detail, code) {
+            (Some(detail), Some(code)) => format!("{}\n{}", detail, code),
+            (Some(text), None) => text.clone(),
+            (None, Some(code)) => code,
+            (None, None) => tool_name.to_string(),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::config::HooksConfig;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 設定で指定されていないMCPサーバーの絵文字
+pub const DEFAULT_SERVER_EMOJI: &str = "🔌";
+
+/// 要約に使う引数の優先順（先にあるほど操作内容をよく表す）
+const INFORMATIVE_KEYS: &[&str] = &[
+    "command", "query", "url", "file_path", "filePath", "path", "pattern", "name", "title", "prompt",
+    "message", "text", "content", "id",
+];
+
+// ===== ツール名 =====
+
+/// `mcp__<server>__<tool>` 形式のツール名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpTool {
+    pub server: String,
+    pub tool: String,
+}
+
+impl McpTool {
+    /// MCPツール名をサーバー名とツール名に分解する（MCPツールでなければ `None`）
+    pub fn parse(tool_name: &str) -> Option<McpTool> {
+        let rest = tool_name.strip_prefix("mcp__")?;
+        let (server, tool) = rest.split_once("__")?;
+        if server.is_empty() || tool.is_empty() {
+            return None;
+        }
+        Some(McpTool {
+            server: server.to_string(),
+            tool: tool.to_string(),
+        })
+    }
+
+    /// 表示用のツール名（`batch_design` → `batch design`）
+    pub fn tool_label(&self) -> String {
+        self.tool.replace(['_', '-'], " ")
+    }
+}
+
+// ===== サーバー情報 =====
+
+/// 設定ファイルの `mcp_servers` の各エントリ
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct McpServer {
+    pub emoji: Option<String>,
+    /// サーバーの表示名（省略時はサーバー名）
+    pub description: Option<String>,
+}
+
+/// サーバー名 → 絵文字・表示名（ユーザー設定 → 組み込みの順）
+#[derive(Debug, Clone, Default)]
+pub struct McpServerRegistry {
+    servers: HashMap<String, McpServer>,
+}
+
+impl McpServerRegistry {
+    pub fn new(user_servers: &HashMap<String, McpServer>) -> McpServerRegistry {
+        let mut servers: HashMap<String, McpServer> = builtin_servers()
+            .into_iter()
+            .map(|(name, emoji, description)| {
+                let server = McpServer {
+                    emoji: Some(emoji.to_string()),
+                    description: Some(description.to_string()),
+                };
+                (name.to_string(), server)
+            })
+            .collect();
+        for (name, server) in user_servers {
+            let entry = servers.entry(name.clone()).or_default();
+            if server.emoji.is_some() {
+                entry.emoji = server.emoji.clone();
+            }
+            if server.description.is_some() {
+                entry.description = server.description.clone();
+            }
+        }
+        McpServerRegistry { servers }
+    }
+
+    pub fn from_config(config: &HooksConfig) -> McpServerRegistry {
+        McpServerRegistry::new(&config.mcp_servers)
+    }
+
+    pub fn emoji(&self, server: &str) -> &str {
+        self.servers
+            .get(server)
+            .and_then(|s| s.emoji.as_deref())
+            .unwrap_or(DEFAULT_SERVER_EMOJI)
+    }
+
+    pub fn description<'a>(&'a self, server: &'a str) -> &'a str {
+        self.servers
+            .get(server)
+            .and_then(|s| s.description.as_deref())
+            .unwrap_or(server)
+    }
+
+    /// "🎨" と "Pencil › batch design" の組
+    pub fn describe(&self, tool: &McpTool) -> (String, String) {
+        (
+            self.emoji(&tool.server).to_string(),
+            format!("{} › {}", self.description(&tool.server), tool.tool_label()),
+        )
+    }
+}
+
+/// 組み込みのサーバー情報（settings.jsonで許可しているもの）
+fn builtin_servers() -> Vec<(&'static str, &'static str, &'static str)> {
+    vec![("pencil", "🎨", "Pencil")]
+}
+
+// ===== 引数の要約 =====
+
+/// 要約に選んだ引数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpArgument {
+    pub key: String,
+    pub value: String,
+}
+
+/// `tool_input` から操作内容を最もよく表す引数を1つ選ぶ
+///
+/// 優先キーの文字列 → その他の空でない文字列 → 数値・真偽値の順。複数行の値は1行目のみ。
+pub fn summarize_input(tool_input: &Value) -> Option<McpArgument> {
+    let object = tool_input.as_object()?;
+
+    let scalar = |key: &str, value: &Value| -> Option<McpArgument> {
+        let value = match value {
+            Value::String(s) => s.lines().map(str::trim).find(|l| !l.is_empty())?.to_string(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            _ => return None,
+        };
+        Some(McpArgument {
+            key: key.to_string(),
+            value,
+        })
+    };
+
+    INFORMATIVE_KEYS
+        .iter()
+        .find_map(|key| object.get(*key).filter(|v| v.is_string()).and_then(|v| scalar(key, v)))
+        .or_else(|| {
+            object
+                .iter()
+                .filter(|(_, v)| v.is_string())
+                .find_map(|(k, v)| scalar(k, v))
+        })
+        .or_else(|| object.iter().find_map(|(k, v)| scalar(k, v)))
+}
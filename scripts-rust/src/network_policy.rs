@@ -0,0 +1,436 @@
+use crate::config::HooksConfig;
+use crate::decision::Decision;
+use crate::shell::{self, Unwrapped};
+use crate::sinks::Sinks;
+use crate::tf;
+use globset::{GlobBuilder, GlobMatcher};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// `sh -c` や `$(...)` を再帰的に解析する深さの上限
+const MAX_DEPTH: usize = 4;
+
+/// curl / wget の値を取るオプション（ホスト名だけの引数と区別するため）
+const URL_TOOL_VALUE_OPTIONS: &[&str] = &[
+    "-o", "--output", "-O", "--output-document", "-P", "--directory-prefix", "-H", "--header", "-d", "--data",
+    "--data-raw", "--data-binary", "--data-urlencode", "--post-data", "-X", "--request", "-u", "--user", "-A",
+    "--user-agent", "-U", "-e", "--referer", "-F", "--form", "-T", "--upload-file", "-w", "--write-out", "-b",
+    "--cookie", "-c", "--cookie-jar", "-m", "--max-time", "--connect-timeout", "-K", "--config", "--cacert",
+    "--cert", "--key", "-r", "--range", "-t", "--tries", "--timeout", "-i", "--input-file",
+];
+
+/// 接続先を取り出すプログラム（解析できないコマンドにこれらの名前があれば確認する）
+const NETWORK_PROGRAMS: &[&str] = &[
+    "curl", "wget", "http", "https", "xh", "aria2c", "git", "npm", "pnpm", "yarn", "bun", "npx", "pnpx", "bunx", "pip",
+    "pip3", "uv", "cargo", "ssh", "sftp", "mosh", "scp", "rsync", "nc", "ncat", "netcat", "telnet", "ping",
+];
+
+/// ssh の値を取るオプション
+const SSH_VALUE_OPTIONS: &[&str] = &[
+    "-b", "-c", "-D", "-E", "-e", "-F", "-I", "-i", "-J", "-L", "-l", "-m", "-O", "-o", "-p", "-P", "-Q", "-R", "-S",
+    "-W", "-w",
+];
+
+// ===== 設定 =====
+
+/// `claude-hooks.json` の `network_policy`
+///
+/// `deny` に当たるホストは常に拒否し、`allow` が空でなければ `allow` に当たらないホストも拒否する。
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct NetworkPolicyConfig {
+    /// 許可するドメイン（`*.github.com` のようなglob）
+    pub allow: Vec<String>,
+    /// 拒否するドメイン（`allow` より優先）
+    pub deny: Vec<String>,
+    /// 違反を記録するJSONLファイル（省略時は `~/.claude/network-violations.jsonl`）
+    pub log_path: Option<String>,
+}
+
+impl NetworkPolicyConfig {
+    /// 違反ログのパス（`log_path` が無くホームディレクトリも分からない場合はNone）
+    pub fn log_path(&self, home: Option<&Path>) -> Option<PathBuf> {
+        match &self.log_path {
+            Some(path) => Some(PathBuf::from(path)),
+            None => Some(home?.join(".claude/network-violations.jsonl")),
+        }
+    }
+}
+
+// ===== 接続先の抽出 =====
+
+/// 接続先のホストと、それを含むURL・コマンド
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Destination {
+    pub host: String,
+    pub origin: String,
+}
+
+impl Destination {
+    fn new(host: &str, origin: &str) -> Destination {
+        Destination {
+            host: host.to_string(),
+            origin: origin.to_string(),
+        }
+    }
+}
+
+/// ツール入力から接続先を取り出す
+///
+/// WebSearchは `allowed_domains` を指定した場合のみ接続先とみなす（検索結果の取得先は決まらないため）。
+pub fn destinations(tool_name: &str, tool_input: &Value) -> Vec<Destination> {
+    match tool_name {
+        "WebFetch" => tool_input
+            .get("url")
+            .and_then(|v| v.as_str())
+            .and_then(|url| Some(Destination::new(&host_from_url(url)?, url)))
+            .into_iter()
+            .collect(),
+        "WebSearch" => tool_input
+            .get("allowed_domains")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|d| d.as_str())
+            .filter_map(|domain| Some(Destination::new(&normalize_host(domain)?, domain)))
+            .collect(),
+        "Bash" => bash_destinations(tool_input).found,
+        _ => Vec::new(),
+    }
+}
+
+/// Bashコマンドの解析結果
+#[derive(Debug, Default)]
+struct BashDestinations {
+    found: Vec<Destination>,
+    /// 解析できなかったスクリプト（クォートの閉じ忘れ・深すぎる入れ子）
+    unparsed: Vec<String>,
+    /// 接続するが接続先を取り出せなかったコマンド（`curl "$URL"`, `git push origin` など）のプログラム名とコマンド
+    hostless: Vec<(String, String)>,
+}
+
+fn bash_destinations(tool_input: &Value) -> BashDestinations {
+    let command = tool_input.get("command").and_then(|v| v.as_str()).unwrap_or("");
+    let mut result = BashDestinations::default();
+    command_destinations(command, 0, &mut result);
+    result
+}
+
+fn command_destinations(script: &str, depth: usize, result: &mut BashDestinations) {
+    if depth > MAX_DEPTH {
+        result.unparsed.push(script.to_string());
+        return;
+    }
+    let Ok(parsed) = shell::parse(script) else {
+        result.unparsed.push(script.to_string());
+        return;
+    };
+    for command in &parsed.commands {
+        let unwrapped = shell::unwrap_command(&command.argv);
+        if let Some(inline) = shell::inline_script(&unwrapped) {
+            command_destinations(&inline, depth + 1, result);
+            continue;
+        }
+        let Some(hosts) = program_hosts(&unwrapped) else {
+            continue;
+        };
+        let origin = command.argv.join(" ");
+        if hosts.is_empty() {
+            result.hostless.push((unwrapped.program().to_string(), origin));
+            continue;
+        }
+        for host in hosts {
+            if !result.found.iter().any(|d| d.host == host && d.origin == origin) {
+                result.found.push(Destination::new(&host, &origin));
+            }
+        }
+    }
+    for substitution in &parsed.substitutions {
+        command_destinations(substitution, depth + 1, result);
+    }
+}
+
+/// 解析できなかったスクリプトに含まれる接続先を取り出すプログラム名
+pub fn network_program_in(script: &str) -> Option<&'static str> {
+    script
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        .find_map(|word| NETWORK_PROGRAMS.iter().find(|p| **p == word).copied())
+}
+
+/// コマンドごとの接続先ホスト（接続しないコマンドは `None`、接続先を取り出せない場合は空）
+fn program_hosts(command: &Unwrapped) -> Option<Vec<String>> {
+    let args = command.args();
+    let subcommand = args.iter().find(|a| !a.starts_with('-')).map(|s| s.as_str()).unwrap_or("");
+    let urls = || args.iter().filter_map(|a| host_from_url(a)).collect::<Vec<_>>();
+
+    let hosts = match command.program() {
+        "curl" | "wget" | "http" | "https" | "xh" | "aria2c" => {
+            let mut hosts = urls();
+            hosts.extend(positionals(args, URL_TOOL_VALUE_OPTIONS).filter_map(host_from_bare));
+            hosts
+        }
+        "git" => match subcommand {
+            // `HEAD:main` のようなrefspecと区別するため、scp形式は `user@` かドメインを含むものに限る
+            "clone" | "fetch" | "pull" | "push" | "ls-remote" | "submodule" | "remote" => {
+                let hosts: Vec<String> = args
+                    .iter()
+                    .filter_map(|a| {
+                        let target = a.split(':').next().unwrap_or("");
+                        let scp = (target.contains('@') || target.contains('.')).then(|| host_from_scp(a)).flatten();
+                        host_from_url(a).or(scp)
+                    })
+                    .collect();
+                // `git remote -v` / `git submodule status` のように接続しない使い方もあるので、URLが無ければ接続しないとみなす
+                if hosts.is_empty() && matches!(subcommand, "submodule" | "remote") {
+                    return None;
+                }
+                hosts
+            }
+            _ => return None,
+        },
+        program @ ("npm" | "pnpm" | "yarn" | "bun" | "npx" | "pnpx" | "bunx") => {
+            let installs = matches!(
+                subcommand,
+                "install" | "i" | "add" | "ci" | "update" | "upgrade" | "up" | "dlx" | "x" | "publish" | "exec"
+            ) || matches!(program, "npx" | "pnpx" | "bunx")
+                || (program == "yarn" && subcommand.is_empty());
+            if !installs {
+                return None;
+            }
+            let default = if program == "yarn" { "registry.yarnpkg.com" } else { "registry.npmjs.org" };
+            registry_hosts(args, &["--registry"], default)
+        }
+        "pip" | "pip3" | "uv" if matches!(subcommand, "install" | "download" | "pip" | "add" | "sync") => {
+            registry_hosts(args, &["-i", "--index-url", "--extra-index-url", "--index"], "pypi.org")
+        }
+        "cargo" if matches!(subcommand, "install" | "publish" | "search" | "fetch") => {
+            registry_hosts(args, &["--index"], "crates.io")
+        }
+        "ssh" | "sftp" | "mosh" => positionals(args, SSH_VALUE_OPTIONS)
+            .next()
+            .and_then(|target| normalize_host(target.rsplit('@').next().unwrap_or(target)))
+            .into_iter()
+            .collect(),
+        "scp" | "rsync" => args.iter().filter_map(|a| host_from_url(a).or_else(|| host_from_scp(a))).collect(),
+        "nc" | "ncat" | "netcat" | "telnet" | "ping" => {
+            positionals(args, &["-p", "-s", "-w", "-c", "-i", "-W"]).next().and_then(normalize_host).into_iter().collect()
+        }
+        _ => return None,
+    };
+    Some(hosts)
+}
+
+/// 値を取るオプションとその値を除いた引数
+fn positionals<'a>(args: &'a [String], value_options: &'a [&str]) -> impl Iterator<Item = &'a str> {
+    let mut skip = false;
+    args.iter().filter_map(move |arg| {
+        if skip {
+            skip = false;
+            return None;
+        }
+        if arg.starts_with('-') {
+            skip = value_options.contains(&arg.as_str());
+            return None;
+        }
+        Some(arg.as_str())
+    })
+}
+
+/// `--registry URL` / `--registry=URL` の指定（無ければ既定のレジストリ）と、引数のURL（tarballやgitリポジトリ）
+fn registry_hosts(args: &[String], options: &[&str], default: &str) -> Vec<String> {
+    let mut registries = Vec::new();
+    for (i, arg) in args.iter().enumerate() {
+        for option in options {
+            let value = if arg == option {
+                args.get(i + 1).map(|s| s.as_str())
+            } else {
+                arg.strip_prefix(&format!("{}=", option))
+            };
+            registries.extend(value.and_then(host_from_url));
+        }
+    }
+    if registries.is_empty() {
+        registries.push(default.to_string());
+    }
+    let others = args.iter().filter(|a| !a.starts_with('-')).filter_map(|a| host_from_url(a));
+    let mut hosts = registries;
+    for host in others {
+        if !hosts.contains(&host) {
+            hosts.push(host);
+        }
+    }
+    hosts
+}
+
+/// `https://user@Example.com:8080/path` → `example.com`
+pub fn host_from_url(text: &str) -> Option<String> {
+    let (scheme, rest) = text.split_once("://")?;
+    if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c)) {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    normalize_host(host)
+}
+
+/// curlの `example.com/path` のようにスキームを省略したURL
+fn host_from_bare(text: &str) -> Option<String> {
+    let host = text.split(['/', '?', '#']).next()?;
+    let name = host.split(':').next()?;
+    let looks_like_host = name.contains('.')
+        && name.rsplit('.').next().is_some_and(|tld| tld.chars().all(|c| c.is_ascii_alphanumeric()) && !tld.is_empty())
+        && !name.starts_with('.');
+    if looks_like_host {
+        normalize_host(host)
+    } else {
+        None
+    }
+}
+
+/// `git@github.com:org/repo.git` / `host:path`（scp形式）
+fn host_from_scp(text: &str) -> Option<String> {
+    if text.contains("://") || text.starts_with(['/', '.', '~', '-']) {
+        return None;
+    }
+    let (target, path) = text.split_once(':')?;
+    if target.is_empty() || path.starts_with("//") || target.contains('/') {
+        return None;
+    }
+    normalize_host(target.rsplit('@').next()?)
+}
+
+/// 小文字にし、ポート・IPv6の括弧・末尾の `.` を取り除く
+pub fn normalize_host(host: &str) -> Option<String> {
+    let host = host.trim();
+    let host = if let Some(rest) = host.strip_prefix('[') {
+        rest.split(']').next()?
+    } else {
+        host.split(':').next()?
+    };
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let valid = !host.is_empty() && host.chars().all(|c| c.is_ascii_alphanumeric() || "-._:".contains(c));
+    valid.then_some(host)
+}
+
+// ===== ポリシー =====
+
+/// ポリシー違反（拒否した接続先と判定）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub destination: Destination,
+    pub decision: Decision,
+}
+
+/// 設定のパターンと、コンパイルしたglob
+#[derive(Debug, Clone)]
+struct HostPattern {
+    source: String,
+    matcher: GlobMatcher,
+}
+
+impl HostPattern {
+    fn compile(source: &str) -> Option<HostPattern> {
+        let source = source.trim();
+        match GlobBuilder::new(source).case_insensitive(true).build() {
+            Ok(glob) => Some(HostPattern {
+                source: source.to_string(),
+                matcher: glob.compile_matcher(),
+            }),
+            Err(err) => {
+                eprintln!("Invalid network pattern {}: {}", source, err);
+                None
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NetworkPolicy {
+    allow: Vec<HostPattern>,
+    deny: Vec<HostPattern>,
+}
+
+impl NetworkPolicy {
+    pub fn new(config: &NetworkPolicyConfig) -> NetworkPolicy {
+        let compile = |patterns: &[String]| patterns.iter().filter_map(|p| HostPattern::compile(p)).collect();
+        NetworkPolicy {
+            allow: compile(&config.allow),
+            deny: compile(&config.deny),
+        }
+    }
+
+    pub fn from_config(config: &HooksConfig) -> NetworkPolicy {
+        NetworkPolicy::new(&config.network_policy)
+    }
+
+    /// ホストを拒否する場合はその理由
+    pub fn check_host(&self, host: &str) -> Option<String> {
+        if let Some(rule) = self.deny.iter().find(|p| p.matcher.is_match(host)) {
+            return Some(tf("policy.network.denied", &[("host", host), ("rule", &rule.source)]));
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|p| p.matcher.is_match(host)) {
+            return Some(tf("policy.network.not_allowed", &[("host", host)]));
+        }
+        None
+    }
+
+    /// ツール入力の接続先を評価し、最初に拒否した接続先を返す
+    ///
+    /// Bashコマンドを解析できず、その中に接続先を取り出すプログラム名がある場合は確認を求める。
+    /// `allow` が空でなければ、接続先を取り出せなかったコマンド（`curl "$URL"` など）も確認を求める。
+    pub fn check_tool(&self, tool_name: &str, tool_input: &Value) -> Option<Violation> {
+        let BashDestinations { found, unparsed, hostless } = match tool_name {
+            "Bash" => bash_destinations(tool_input),
+            _ => BashDestinations {
+                found: destinations(tool_name, tool_input),
+                ..Default::default()
+            },
+        };
+        let denied = found.into_iter().find_map(|destination| {
+            let reason = self.check_host(&destination.host)?;
+            Some(Violation {
+                destination,
+                decision: Decision::deny(reason),
+            })
+        });
+        if denied.is_some() || (self.allow.is_empty() && self.deny.is_empty()) {
+            return denied;
+        }
+        let unparsed = unparsed.into_iter().find_map(|script| {
+            let program = network_program_in(&script)?;
+            Some(Violation {
+                destination: Destination::new("", &script),
+                decision: Decision::ask(tf("policy.network.unparsed", &[("program", program)])),
+            })
+        });
+        if unparsed.is_some() || self.allow.is_empty() {
+            return unparsed;
+        }
+        hostless.into_iter().next().map(|(program, origin)| Violation {
+            destination: Destination::new("", &origin),
+            decision: Decision::ask(tf("policy.network.no_host", &[("program", &program)])),
+        })
+    }
+}
+
+// ===== 違反ログ =====
+
+/// 違反をJSONLで追記する
+pub fn log_violation(path: &Path, session_id: &str, tool_name: &str, violation: &Violation, sinks: &dyn Sinks) -> io::Result<()> {
+    let entry = violation_entry(&sinks.now().to_rfc3339(), session_id, tool_name, violation);
+    sinks.append_file(path, &format!("{}\n", entry))
+}
+
+/// 違反ログの1行分
+pub fn violation_entry(timestamp: &str, session_id: &str, tool_name: &str, violation: &Violation) -> Value {
+    json!({
+        "timestamp": timestamp,
+        "session_id": session_id,
+        "tool_name": tool_name,
+        "host": violation.destination.host,
+        "origin": violation.destination.origin,
+        "reason": violation.decision.reason,
+    })
+}
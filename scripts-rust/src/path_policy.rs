@@ -0,0 +1,338 @@
+use crate::bash_risk::normalize;
+use crate::decision::Decision;
+use crate::sinks::Sinks;
+use crate::tf;
+use globset::{GlobBuilder, GlobMatcher};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// ===== 設定 =====
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    Read,
+    Write,
+    /// 読み書きの両方
+    #[default]
+    Any,
+}
+
+impl Access {
+    fn covers(self, access: Access) -> bool {
+        self == Access::Any || self == access
+    }
+}
+
+/// 拒否するパスのルール
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PathRule {
+    /// パスのglob（`~/.ssh`, `**/*.pem`, `.env`, `./secrets/**` など）
+    pub path: String,
+    #[serde(default)]
+    pub access: Access,
+    /// 拒否の理由（省略時はルールから生成）
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// `claude-hooks.json` の `path_policy`
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct PathPolicyConfig {
+    /// cwd の外への書き込みを拒否する
+    pub deny_write_outside_cwd: bool,
+    /// `deny_write_outside_cwd` の例外（例: `/tmp/**`）
+    pub allow_write: Vec<String>,
+    pub deny: Vec<PathRule>,
+}
+
+/// settings.json の `permissions.deny` から `Read(...)` / `Edit(...)` / `Write(...)` のルールを取り出す
+///
+/// settings.json の書式に合わせて `//path` は絶対パス、`/path` は settings.json のディレクトリからの相対パスとして扱う。
+pub fn rules_from_settings(settings: &Value, settings_dir: &Path) -> Vec<PathRule> {
+    let deny = settings.pointer("/permissions/deny").and_then(|v| v.as_array());
+    deny.into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.as_str()?;
+            let (tool, pattern) = entry.strip_suffix(')')?.split_once('(')?;
+            let access = match tool {
+                "Read" => Access::Read,
+                "Edit" | "Write" | "MultiEdit" | "NotebookEdit" => Access::Write,
+                _ => return None,
+            };
+            let path = if let Some(absolute) = pattern.strip_prefix("//") {
+                format!("/{}", absolute)
+            } else if let Some(relative) = pattern.strip_prefix('/') {
+                settings_dir.join(relative).to_string_lossy().into_owned()
+            } else {
+                pattern.to_string()
+            };
+            Some(PathRule {
+                path,
+                access,
+                reason: None,
+            })
+        })
+        .collect()
+}
+
+// ===== パスの解決 =====
+
+/// パスを解決する基準（cwd とホームディレクトリ、どちらもシンボリックリンク解決済み）
+///
+/// シンボリックリンクは `Sinks::resolve_path` で解決する。
+#[derive(Debug, Clone)]
+pub struct PathContext {
+    pub cwd: PathBuf,
+    pub home: Option<PathBuf>,
+}
+
+impl PathContext {
+    pub fn new(cwd: &Path, home: Option<&Path>, sinks: &dyn Sinks) -> PathContext {
+        PathContext {
+            cwd: resolve_with(cwd, sinks),
+            home: home.map(|home| resolve_with(home, sinks)),
+        }
+    }
+
+    /// `~` を展開し、cwd からの相対パスを絶対パスにして、シンボリックリンクと `..` を解決する
+    pub fn resolve(&self, path: &str, sinks: &dyn Sinks) -> PathBuf {
+        resolve_with(&self.absolute(path), sinks)
+    }
+
+    fn absolute(&self, path: &str) -> PathBuf {
+        if path == "~" || path.starts_with("~/") {
+            if let Some(home) = &self.home {
+                return home.join(path[1..].trim_start_matches('/'));
+            }
+        }
+        self.cwd.join(path)
+    }
+}
+
+fn resolve_with(path: &Path, sinks: &dyn Sinks) -> PathBuf {
+    PathBuf::from(sinks.resolve_path(&path.to_string_lossy()))
+}
+
+/// 存在する最も深い祖先までシンボリックリンクを解決し、残り（未作成の部分）は字句的に `..` を解決する
+pub fn resolve_symlinks(path: &Path) -> PathBuf {
+    for ancestor in path.ancestors() {
+        if let Ok(canonical) = fs::canonicalize(ancestor) {
+            let rest = path.strip_prefix(ancestor).unwrap_or(Path::new(""));
+            return normalize(&canonical.join(rest));
+        }
+    }
+    normalize(path)
+}
+
+// ===== パターン =====
+
+/// パスのglob（ディレクトリを指定した場合は配下も含む）
+#[derive(Debug, Clone)]
+struct Pattern {
+    source: String,
+    /// 絶対パス（または `**/` から始まる）に展開したglob
+    expanded: String,
+    matchers: Vec<GlobMatcher>,
+}
+
+impl Pattern {
+    /// - `~/...`: ホームディレクトリから
+    /// - `/...`: 絶対パス
+    /// - `/` を含まない名前（`.env`, `*.pem`）: 任意の階層
+    /// - それ以外（`./x`, `src/*.rs`）: cwd から
+    fn compile(source: &str, context: &PathContext, sinks: &dyn Sinks) -> Option<Pattern> {
+        let trimmed = source.trim_end_matches('/');
+        let expanded = if trimmed.starts_with('/') || trimmed.starts_with('~') {
+            context.absolute(trimmed).to_string_lossy().into_owned()
+        } else if !trimmed.contains('/') {
+            format!("**/{}", trimmed)
+        } else {
+            context.cwd.join(trimmed.trim_start_matches("./")).to_string_lossy().into_owned()
+        };
+        let expanded = resolve_literal_prefix(&expanded, sinks);
+
+        let mut matchers = Vec::new();
+        for glob in [expanded.clone(), format!("{}/**", expanded)] {
+            match GlobBuilder::new(&glob).literal_separator(true).build() {
+                Ok(glob) => matchers.push(glob.compile_matcher()),
+                Err(err) => {
+                    eprintln!("Invalid path pattern {}: {}", source, err);
+                    return None;
+                }
+            }
+        }
+        Some(Pattern {
+            source: source.to_string(),
+            expanded,
+            matchers,
+        })
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        self.matchers.iter().any(|m| m.is_match(path))
+    }
+
+    /// `dir` の配下にマッチするパスがありうるか（`dir` がglobのリテラル部分の祖先、またはリテラル部分の配下でglobが続く）
+    fn may_match_under(&self, dir: &Path) -> bool {
+        let parts: Vec<&str> = self.expanded.split('/').collect();
+        let literal = parts.iter().position(|p| is_glob(p)).unwrap_or(parts.len());
+        if literal == 0 {
+            // `**/.env` のように任意の階層
+            return true;
+        }
+        let prefix = PathBuf::from(parts[..literal].join("/"));
+        prefix.starts_with(dir) || (literal < parts.len() && dir.starts_with(&prefix))
+    }
+}
+
+/// globの前のリテラル部分のシンボリックリンクを解決する（macOSの `/tmp` → `/private/tmp` など）
+fn resolve_literal_prefix(pattern: &str, sinks: &dyn Sinks) -> String {
+    if !pattern.starts_with('/') {
+        return pattern.to_string();
+    }
+    let parts: Vec<&str> = pattern.split('/').collect();
+    let literal = parts.iter().position(|p| is_glob(p)).unwrap_or(parts.len());
+    let prefix = resolve_with(Path::new(&parts[..literal].join("/")), sinks);
+    let rest = &parts[literal..];
+    if rest.is_empty() {
+        prefix.to_string_lossy().into_owned()
+    } else {
+        format!("{}/{}", prefix.to_string_lossy().trim_end_matches('/'), rest.join("/"))
+    }
+}
+
+fn is_glob(part: &str) -> bool {
+    part.contains(['*', '?', '[', '{'])
+}
+
+// ===== ポリシー =====
+
+/// Read / Write / Edit / Glob / Grep のパスを評価するポリシー
+#[derive(Debug, Clone)]
+pub struct PathPolicy {
+    context: PathContext,
+    deny: Vec<(PathRule, Pattern)>,
+    deny_write_outside_cwd: bool,
+    allow_write: Vec<Pattern>,
+}
+
+impl PathPolicy {
+    pub fn new(config: &PathPolicyConfig, settings_rules: &[PathRule], context: PathContext, sinks: &dyn Sinks) -> PathPolicy {
+        let deny = config
+            .deny
+            .iter()
+            .chain(settings_rules)
+            .filter_map(|rule| Some((rule.clone(), Pattern::compile(&rule.path, &context, sinks)?)))
+            .collect();
+        let allow_write = config.allow_write.iter().filter_map(|p| Pattern::compile(p, &context, sinks)).collect();
+        PathPolicy {
+            deny,
+            deny_write_outside_cwd: config.deny_write_outside_cwd,
+            allow_write,
+            context,
+        }
+    }
+
+    /// `claude-hooks.json` と `~/.claude/settings.json` のルールを読み込む
+    pub fn load(cwd: &str, sinks: &dyn Sinks) -> PathPolicy {
+        let context = PathContext::new(Path::new(cwd), sinks.home().as_deref(), sinks);
+        let settings_rules = sinks
+            .home()
+            .map(|home| home.join(".claude"))
+            .and_then(|dir| {
+                let content = sinks.read_file(&dir.join("settings.json")).ok()?;
+                let settings: Value = serde_json::from_str(&content).ok()?;
+                Some(rules_from_settings(&settings, &dir))
+            })
+            .unwrap_or_default();
+        PathPolicy::new(&sinks.config().path_policy, &settings_rules, context, sinks)
+    }
+
+    /// ツール入力の全てのパスを評価する（拒否も確認もしない場合は `None`）
+    ///
+    /// globを指定しないGrepは、検索するディレクトリの配下に読み取りを拒否するパスがありうる場合に確認を求める。
+    pub fn check_tool(&self, tool_name: &str, tool_input: &Value, sinks: &dyn Sinks) -> Option<Decision> {
+        tool_paths(tool_name, tool_input)
+            .into_iter()
+            .find_map(|(access, path)| self.check_path(access, &path, sinks))
+            .or_else(|| self.check_search_root(tool_name, tool_input, sinks))
+    }
+
+    fn check_search_root(&self, tool_name: &str, tool_input: &Value, sinks: &dyn Sinks) -> Option<Decision> {
+        let field = |key: &str| tool_input.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+        if tool_name != "Grep" || field("glob").is_some() {
+            return None;
+        }
+        let root = self.context.resolve(field("path").unwrap_or("."), sinks);
+        let (_, pattern) = self
+            .deny
+            .iter()
+            .find(|(rule, pattern)| rule.access.covers(Access::Read) && pattern.may_match_under(&root))?;
+        let display = root.to_string_lossy();
+        Some(Decision::ask(tf("policy.path.ask_search", &[("path", &display), ("rule", &pattern.source)])))
+    }
+
+    pub fn check_path(&self, access: Access, path: &str, sinks: &dyn Sinks) -> Option<Decision> {
+        let resolved = self.context.resolve(path, sinks);
+        let display = resolved.to_string_lossy();
+
+        for (rule, pattern) in &self.deny {
+            if rule.access.covers(access) && pattern.matches(&resolved) {
+                let reason = match (&rule.reason, access) {
+                    (Some(reason), _) => tf("policy.path.custom", &[("reason", reason), ("path", &display)]),
+                    (None, Access::Write) => {
+                        tf("policy.path.deny_write", &[("path", &display), ("rule", &pattern.source)])
+                    }
+                    (None, _) => tf("policy.path.deny_read", &[("path", &display), ("rule", &pattern.source)]),
+                };
+                return Some(Decision::deny(reason));
+            }
+        }
+
+        let outside = !resolved.starts_with(&self.context.cwd);
+        if access == Access::Write
+            && self.deny_write_outside_cwd
+            && outside
+            && !self.allow_write.iter().any(|p| p.matches(&resolved))
+        {
+            let cwd = self.context.cwd.to_string_lossy();
+            return Some(Decision::deny(tf("policy.path.outside_cwd", &[("path", &display), ("cwd", &cwd)])));
+        }
+        None
+    }
+}
+
+/// ツール入力から評価するパスを取り出す
+///
+/// Glob / Grep は検索するディレクトリに加えて、`ディレクトリ/パターン` も読み取りとして評価する
+/// （`**/*.pem` のような拒否ルールに当たる検索を止めるため）。
+pub fn tool_paths(tool_name: &str, tool_input: &Value) -> Vec<(Access, String)> {
+    let field = |key: &str| tool_input.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+    match tool_name {
+        "Read" => field("file_path").map(|p| vec![(Access::Read, p.to_string())]).unwrap_or_default(),
+        "Write" | "Edit" | "MultiEdit" => {
+            field("file_path").map(|p| vec![(Access::Write, p.to_string())]).unwrap_or_default()
+        }
+        "NotebookEdit" => field("notebook_path").map(|p| vec![(Access::Write, p.to_string())]).unwrap_or_default(),
+        "Glob" | "Grep" => {
+            let root = field("path").unwrap_or(".");
+            let pattern = if tool_name == "Glob" { field("pattern") } else { field("glob") };
+            let mut paths = vec![(Access::Read, root.to_string())];
+            if let Some(pattern) = pattern {
+                let joined = if pattern.starts_with('/') {
+                    pattern.to_string()
+                } else {
+                    format!("{}/{}", root.trim_end_matches('/'), pattern)
+                };
+                paths.push((Access::Read, joined));
+            }
+            paths
+        }
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,141 @@
+use crate::session_file_name;
+use crate::sinks::Sinks;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// ===== プラン解決 =====
+
+/// プラン内容の取得元
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanSource {
+    /// ExitPlanModeの `tool_input.plan`
+    ToolInput,
+    /// セッションのトランスクリプト（ExitPlanMode、またはplansディレクトリへのWrite/Edit）
+    Transcript,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPlan {
+    pub content: String,
+    pub source: PlanSource,
+}
+
+/// セッションのプランを解決する
+///
+/// `tool_input.plan` → トランスクリプトの順で探す。
+/// 別セッションのプランを誤って拾わないよう、plansディレクトリの最新ファイルにはフォールバックしない。
+/// `plans_dir` が分からない（HOMEが無い）場合は、トランスクリプトのExitPlanModeのみを使う。
+pub fn resolve_plan(
+    tool_input: &serde_json::Value,
+    transcript_path: Option<&str>,
+    plans_dir: Option<&Path>,
+    sinks: &dyn Sinks,
+) -> Option<ResolvedPlan> {
+    if let Some(plan) = tool_input.get("plan").and_then(|v| v.as_str()) {
+        if !plan.trim().is_empty() {
+            return Some(ResolvedPlan {
+                content: plan.to_string(),
+                source: PlanSource::ToolInput,
+            });
+        }
+    }
+
+    let content = transcript_path.and_then(|path| plan_from_transcript(path, plans_dir, sinks).ok().flatten())?;
+    Some(ResolvedPlan {
+        content,
+        source: PlanSource::Transcript,
+    })
+}
+
+/// トランスクリプトから最新のプランを取得する
+///
+/// ExitPlanModeの `input.plan` と、plansディレクトリへのWrite/Editのうち最後のものを採用する。
+pub fn plan_from_transcript(transcript_path: &str, plans_dir: Option<&Path>, sinks: &dyn Sinks) -> io::Result<Option<String>> {
+    enum Found {
+        Plan(String),
+        File(String),
+    }
+
+    let transcript = sinks.read_file(Path::new(transcript_path))?;
+    let mut latest: Option<Found> = None;
+
+    for line in transcript.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: serde_json::Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        if entry.get("type").and_then(|v| v.as_str()) != Some("assistant") {
+            continue;
+        }
+        let items = match entry.pointer("/message/content").and_then(|v| v.as_array()) {
+            Some(items) => items,
+            None => continue,
+        };
+        for item in items {
+            if item.get("type").and_then(|v| v.as_str()) != Some("tool_use") {
+                continue;
+            }
+            let input = item.get("input");
+            match item.get("name").and_then(|v| v.as_str()) {
+                Some("ExitPlanMode") => {
+                    if let Some(plan) = input.and_then(|i| i.get("plan")).and_then(|v| v.as_str()) {
+                        latest = Some(Found::Plan(plan.to_string()));
+                    }
+                }
+                Some("Write") | Some("Edit") | Some("MultiEdit") => {
+                    if let Some(path) = input.and_then(|i| i.get("file_path")).and_then(|v| v.as_str()) {
+                        if plans_dir.is_some_and(|dir| Path::new(path).starts_with(dir)) {
+                            latest = Some(Found::File(path.to_string()));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(match latest {
+        Some(Found::Plan(plan)) => Some(plan),
+        // ファイルは編集後の最終状態をディスクから読む
+        Some(Found::File(path)) => sinks.read_file(Path::new(&path)).ok(),
+        None => None,
+    })
+}
+
+// ===== プラン履歴と差分 =====
+
+/// セッションごとの直前のプランを保存するディレクトリ（`~/.claude/plan-history`）
+pub fn history_dir(home: &Path) -> PathBuf {
+    home.join(".claude/plan-history")
+}
+
+/// 同じセッションで前回通知したプランを読み込む
+pub fn load_previous_plan(history_dir: &Path, session_id: &str, sinks: &dyn Sinks) -> Option<String> {
+    sinks.read_file(&history_path(history_dir, session_id)).ok()
+}
+
+/// 今回のプランを次回の差分計算用に保存する
+pub fn save_plan(history_dir: &Path, session_id: &str, content: &str, sinks: &dyn Sinks) -> io::Result<()> {
+    sinks.write_file(&history_path(history_dir, session_id), content)
+}
+
+/// セッションのプラン履歴ファイルのパス
+pub fn history_path(history_dir: &Path, session_id: &str) -> PathBuf {
+    history_dir.join(format!("{}.md", session_file_name(session_id)))
+}
+
+/// 前回のプランとの差分をunified diff形式で返す（変更がない場合はNone）
+pub fn plan_diff(previous: &str, current: &str) -> Option<String> {
+    if previous == current {
+        return None;
+    }
+    let diff = similar::TextDiff::from_lines(previous, current)
+        .unified_diff()
+        .context_radius(1)
+        .header("previous", "revised")
+        .to_string();
+    Some(diff)
+}
@@ -0,0 +1,321 @@
+use crate::install::project_override;
+use crate::text::truncate_chars;
+use crate::{t, tf};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::ops::Range;
+
+// ===== 設定 =====
+
+/// `claude-hooks.json` の `prompt`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct PromptConfig {
+    /// シークレットを含むプロンプトをブロックする
+    pub block_secrets: bool,
+    /// `#issue-123` / `@file:path` を展開する
+    pub macros: bool,
+    /// Issueのリンク（`{number}` を置き換える）。省略時はGitリモートの `/issues/{number}`
+    pub issue_url: Option<String>,
+    /// `@file:` で渡すファイルの上限文字数
+    pub max_file_chars: usize,
+    /// プロジェクトのルート（`~/` 可）ごとの上書き
+    pub projects: HashMap<String, PromptProjectConfig>,
+}
+
+impl Default for PromptConfig {
+    fn default() -> PromptConfig {
+        PromptConfig {
+            block_secrets: false,
+            macros: true,
+            issue_url: None,
+            max_file_chars: 8000,
+            projects: HashMap::new(),
+        }
+    }
+}
+
+/// プロジェクトごとの上書き（省略した項目は全体の設定に従う）
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct PromptProjectConfig {
+    pub block_secrets: Option<bool>,
+    pub macros: Option<bool>,
+    pub issue_url: Option<String>,
+}
+
+/// プロジェクトの上書きを適用した設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptSettings {
+    pub block_secrets: bool,
+    pub macros: bool,
+    pub issue_url: Option<String>,
+    pub max_file_chars: usize,
+}
+
+impl PromptConfig {
+    pub fn for_project(&self, project: &str, home: Option<&str>) -> PromptSettings {
+        let overrides = project_override(&self.projects, project, home)
+            .cloned()
+            .unwrap_or_default();
+        PromptSettings {
+            block_secrets: overrides.block_secrets.unwrap_or(self.block_secrets),
+            macros: overrides.macros.unwrap_or(self.macros),
+            issue_url: overrides.issue_url.or_else(|| self.issue_url.clone()),
+            max_file_chars: self.max_file_chars,
+        }
+    }
+}
+
+// ===== シークレットの検出 =====
+
+/// プロンプト中のシークレット
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretMatch {
+    /// シークレットの種類（`AWS access key` など）
+    pub kind: &'static str,
+    /// プロンプト中の位置（バイト）
+    pub range: Range<usize>,
+}
+
+/// 接頭辞で見分けるトークン（種類、接頭辞、接頭辞に続く最小の文字数）
+const TOKEN_PREFIXES: &[(&str, &str, usize)] = &[
+    ("AWS access key", "AKIA", 16),
+    ("AWS access key", "ASIA", 16),
+    ("GitHub token", "ghp_", 30),
+    ("GitHub token", "gho_", 30),
+    ("GitHub token", "ghu_", 30),
+    ("GitHub token", "ghs_", 30),
+    ("GitHub token", "ghr_", 30),
+    ("GitHub token", "github_pat_", 30),
+    ("Slack token", "xoxb-", 10),
+    ("Slack token", "xoxp-", 10),
+    ("Slack token", "xoxa-", 10),
+    ("Slack webhook URL", "https://hooks.slack.com/services/", 20),
+    ("Anthropic API key", "sk-ant-", 20),
+    ("OpenAI API key", "sk-proj-", 20),
+    ("Stripe secret key", "sk_live_", 16),
+    ("Google API key", "AIza", 30),
+];
+
+/// `名前=値` / `名前: 値` の形で値をシークレットとみなす名前（小文字、部分一致）
+const SECRET_NAMES: &[&str] = &["password", "passwd", "secret", "api_key", "apikey", "access_token", "auth_token", "private_key"];
+
+/// `名前=値` の値とみなす最小の文字数
+const MIN_ASSIGNED_SECRET: usize = 8;
+
+/// シークレットらしい文字列を出現順に返す
+pub fn find_secrets(prompt: &str) -> Vec<SecretMatch> {
+    let mut matches: Vec<SecretMatch> = Vec::new();
+
+    for &(kind, prefix, min_len) in TOKEN_PREFIXES {
+        for (start, _) in prompt.match_indices(prefix) {
+            // 単語の途中（`xAKIA...`）は除く
+            if prompt[..start].chars().next_back().is_some_and(is_token_char) {
+                continue;
+            }
+            let rest = &prompt[start + prefix.len()..];
+            let len = rest.find(|c: char| !is_token_char(c) && c != '/').unwrap_or(rest.len());
+            if len >= min_len {
+                matches.push(SecretMatch {
+                    kind,
+                    range: start..start + prefix.len() + len,
+                });
+            }
+        }
+    }
+
+    for (start, _) in prompt.match_indices("-----BEGIN ") {
+        let header_end = prompt[start..].find('\n').map_or(prompt.len(), |i| start + i);
+        if prompt[start..header_end].contains("PRIVATE KEY-----") {
+            // 終わりの行が無い場合は末尾まで
+            let end = prompt[header_end..]
+                .find("PRIVATE KEY-----")
+                .map_or(prompt.len(), |i| header_end + i + "PRIVATE KEY-----".len());
+            matches.push(SecretMatch {
+                kind: "Private key",
+                range: start..end,
+            });
+        }
+    }
+
+    matches.extend(assigned_secrets(prompt));
+
+    // 重なる検出は先に見つかった（長い）方を残す
+    matches.sort_by_key(|m| (m.range.start, std::cmp::Reverse(m.range.end)));
+    let mut result: Vec<SecretMatch> = Vec::new();
+    for m in matches {
+        if result.last().is_none_or(|last| m.range.start >= last.range.end) {
+            result.push(m);
+        }
+    }
+    result
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// `password=...` / `API_KEY: "..."` の値
+fn assigned_secrets(prompt: &str) -> Vec<SecretMatch> {
+    let lower = prompt.to_ascii_lowercase();
+    let mut matches = Vec::new();
+    for name in SECRET_NAMES {
+        for (start, _) in lower.match_indices(name) {
+            let mut pos = start + name.len();
+            // `DB_PASSWORD` のような名前の続き
+            pos += lower[pos..].find(|c: char| !is_token_char(c)).unwrap_or(lower.len() - pos);
+            let after = &prompt[pos..];
+            let trimmed = after.trim_start_matches([' ', '"', '\'']);
+            let Some(value) = trimmed.strip_prefix(['=', ':']) else {
+                continue;
+            };
+            let value_start = prompt.len() - value.trim_start_matches([' ', '"', '\'']).len();
+            let value_text = &prompt[value_start..];
+            let len = value_text
+                .find(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == ',' || c == ';')
+                .unwrap_or(value_text.len());
+            let value = &value_text[..len];
+            // `$VAR` / `<password>` / `{{secret}}` のようなプレースホルダーは除く
+            if len >= MIN_ASSIGNED_SECRET && !value.starts_with(['$', '<', '{', '*']) {
+                matches.push(SecretMatch {
+                    kind: "Credential",
+                    range: value_start..value_start + len,
+                });
+            }
+        }
+    }
+    matches
+}
+
+/// シークレットを `[REDACTED]` に置き換える（Slackに送るプロンプト用）
+pub fn redact(prompt: &str, secrets: &[SecretMatch]) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for secret in secrets {
+        out.push_str(&prompt[last..secret.range.start]);
+        out.push_str("[REDACTED]");
+        last = secret.range.end;
+    }
+    out.push_str(&prompt[last..]);
+    out
+}
+
+/// プロンプトをブロックする理由（シークレットの種類の一覧）
+pub fn block_reason(secrets: &[SecretMatch]) -> String {
+    let mut kinds: Vec<&str> = Vec::new();
+    for secret in secrets {
+        if !kinds.contains(&secret.kind) {
+            kinds.push(secret.kind);
+        }
+    }
+    tf("prompt.blocked", &[("kinds", &kinds.join(", "))])
+}
+
+/// 標準出力に書き出すUserPromptSubmitフックのブロックの出力JSON
+pub fn block_output(reason: &str) -> Value {
+    json!({
+        "decision": "block",
+        "reason": reason,
+    })
+}
+
+/// 標準出力に書き出すUserPromptSubmitフックの `additionalContext` の出力JSON
+pub fn context_output(context: &str) -> Value {
+    json!({
+        "hookSpecificOutput": {
+            "hookEventName": "UserPromptSubmit",
+            "additionalContext": context,
+        }
+    })
+}
+
+// ===== マクロ =====
+
+/// プロンプト中のマクロ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Macro {
+    /// `#issue-123`
+    Issue(u64),
+    /// `@file:src/main.rs`
+    File(String),
+}
+
+impl Macro {
+    pub fn source(&self) -> String {
+        match self {
+            Macro::Issue(number) => format!("#issue-{}", number),
+            Macro::File(path) => format!("@file:{}", path),
+        }
+    }
+}
+
+/// プロンプト中のマクロを出現順に返す（重複は除く）
+pub fn find_macros(prompt: &str) -> Vec<Macro> {
+    let mut macros: Vec<(usize, Macro)> = Vec::new();
+
+    for (start, _) in prompt.match_indices("#issue-") {
+        let digits: String = prompt[start + 7..].chars().take_while(|c| c.is_ascii_digit()).collect();
+        if let Ok(number) = digits.parse() {
+            macros.push((start, Macro::Issue(number)));
+        }
+    }
+    for (start, _) in prompt.match_indices("@file:") {
+        if prompt[..start].chars().next_back().is_some_and(|c| !c.is_whitespace() && c != '(') {
+            continue;
+        }
+        let path: String = prompt[start + 6..]
+            .chars()
+            .take_while(|c| !c.is_whitespace())
+            .collect();
+        // 文末の句読点は含めない
+        let path = path.trim_end_matches(['.', ',', ';', ':', ')', '!', '?']);
+        if !path.is_empty() {
+            macros.push((start, Macro::File(path.to_string())));
+        }
+    }
+
+    macros.sort_by_key(|(start, _)| *start);
+    let mut unique: Vec<Macro> = Vec::new();
+    for (_, m) in macros {
+        if !unique.contains(&m) {
+            unique.push(m);
+        }
+    }
+    unique
+}
+
+/// Issueのリンク（`issue_url` のテンプレート、無ければGitリモートから）
+pub fn issue_link(number: u64, template: Option<&str>, remote_url: Option<&str>) -> Option<String> {
+    match (template, remote_url) {
+        (Some(template), _) => Some(template.replace("{number}", &number.to_string())),
+        (None, Some(remote)) => Some(format!("{}/issues/{}", remote.trim_end_matches('/'), number)),
+        (None, None) => None,
+    }
+}
+
+/// `@file:` で渡すファイルの内容（上限を超える場合は末尾を省略）
+pub fn file_block(content: &str, max_chars: usize) -> String {
+    let content = truncate_chars(content.trim_end(), max_chars);
+    // 内容にコードブロックが含まれていても閉じないように、より長いフェンスを使う
+    let longest = content
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}\n{}\n{}", fence, content, fence)
+}
+
+/// 展開したマクロから `additionalContext` を組み立てる（空の場合はNone）
+pub fn build_context(expansions: &[(String, String)]) -> Option<String> {
+    if expansions.is_empty() {
+        return None;
+    }
+    let sections: Vec<String> = expansions
+        .iter()
+        .map(|(source, body)| format!("### {}\n{}", source, body))
+        .collect();
+    Some(format!("## {}\n\n{}", t("prompt.heading"), sections.join("\n\n")))
+}
@@ -0,0 +1,184 @@
+use crate::config::HooksConfig;
+use crate::git::{DiffStatEntry, GitContext};
+use crate::handlers;
+use crate::sinks::{FakeSinks, Outputs, Sinks};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// フィクスチャで `now` を省略した場合の時刻
+pub const DEFAULT_NOW: &str = "2026-01-01T09:00:00+09:00";
+
+// ===== フィクスチャ =====
+
+/// 記録したフックの入力と、リプレイ時の環境・期待する出力
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Fixture {
+    /// 実行するハンドラー（バイナリ名）
+    pub handler: String,
+    /// フックの標準入力
+    pub input: Value,
+    #[serde(default)]
+    pub env: FixtureEnv,
+    /// 期待する出力（未設定の場合は比較しない）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect: Option<Outputs>,
+}
+
+/// リプレイ時にハンドラーから見える環境
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct FixtureEnv {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home: Option<String>,
+    /// RFC 3339形式の現在時刻（省略時は `DEFAULT_NOW`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub now: Option<String>,
+    /// Gitリポジトリの状態（Gitリポジトリでない場合は省略）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitContext>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toplevel: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diff: Vec<DiffStatEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus_url: Option<String>,
+    /// `claude-hooks.json` の内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<Value>,
+    /// 読み込めるファイル（パス → 内容）
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, String>,
+    /// 入力の `transcript_path` に置くトランスクリプト（1要素がJSONLの1行）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub transcript: Vec<Value>,
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> io::Result<Fixture> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, content + "\n")
+    }
+
+    /// フィクスチャの環境を再現した `FakeSinks`
+    pub fn sinks(&self) -> Result<FakeSinks, String> {
+        let env = &self.env;
+        let now = env.now.as_deref().unwrap_or(DEFAULT_NOW);
+        let config: HooksConfig = match &env.config {
+            Some(config) => serde_json::from_value(config.clone()).map_err(|e| format!("invalid config: {}", e))?,
+            None => HooksConfig::default(),
+        };
+        let mut sinks = FakeSinks::new();
+        sinks.config = config;
+        sinks.home = env.home.as_ref().map(PathBuf::from);
+        sinks.now = DateTime::parse_from_rfc3339(now).map_err(|e| format!("invalid now {}: {}", now, e))?;
+        sinks.git = env.git.clone();
+        sinks.toplevel = env.toplevel.clone();
+        sinks.diff = env.diff.clone();
+        sinks.focus_url = env.focus_url.clone();
+        for (path, content) in &env.files {
+            sinks.add_file(path, content);
+        }
+        if let Some(path) = self.input.get("transcript_path").and_then(|p| p.as_str()) {
+            if !env.transcript.is_empty() {
+                let lines: Vec<String> = env.transcript.iter().map(|line| line.to_string()).collect();
+                sinks.add_file(path, &(lines.join("\n") + "\n"));
+            }
+        }
+        Ok(sinks)
+    }
+
+    /// ハンドラーを実行して出力を返す（ハンドラーのエラーは `Outputs::error` に入る）
+    pub fn replay(&self) -> Result<Outputs, String> {
+        let sinks = self.sinks()?;
+        let result = handlers::run(&self.handler, &self.input.to_string(), &sinks);
+        let mut outputs = sinks.outputs();
+        if let Err(err) = result {
+            outputs.error = Some(err.to_string());
+        }
+        Ok(outputs)
+    }
+}
+
+// ===== 記録 =====
+
+/// フックの標準入力と、その時点の環境をフィクスチャとして `dir/<handler>/` に保存する
+///
+/// `config_path` は `claude-hooks.json` のパス（内容をそのまま `env.config` に記録する）。
+pub fn record(dir: &Path, handler: &str, input: &str, sinks: &dyn Sinks, config_path: Option<&Path>) -> io::Result<PathBuf> {
+    let input: Value = serde_json::from_str(input).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let cwd = input.get("cwd").and_then(|v| v.as_str());
+    let home = sinks.home();
+    let now = sinks.now();
+
+    let mut env = FixtureEnv {
+        home: home.as_ref().map(|h| h.to_string_lossy().into_owned()),
+        now: Some(now.to_rfc3339()),
+        git: cwd.and_then(|cwd| sinks.git_context(cwd)),
+        toplevel: cwd.and_then(|cwd| sinks.git_toplevel(cwd)),
+        diff: cwd.and_then(|cwd| sinks.git_diff_numstat(cwd)).unwrap_or_default(),
+        focus_url: sinks.focus_url(),
+        config: config_path
+            .and_then(|path| sinks.read_file(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok()),
+        ..FixtureEnv::default()
+    };
+    if let Some(path) = input.get("transcript_path").and_then(|p| p.as_str()) {
+        if let Ok(content) = sinks.read_file(Path::new(path)) {
+            env.transcript = content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect();
+        }
+    }
+    // SubagentStop はサブエージェント自身のトランスクリプトも読む
+    if let Some(path) = input.get("agent_transcript_path").and_then(|p| p.as_str()) {
+        if let Ok(content) = sinks.read_file(Path::new(path)) {
+            env.files.insert(path.to_string(), content);
+        }
+    }
+    // path-policy-check は settings.json の拒否ルールを、subagent-slack はフックの登録を使う
+    if let Some(settings) = home.map(|h| h.join(".claude/settings.json")) {
+        if matches!(handler, "path-policy-check" | "subagent-slack") {
+            if let Ok(content) = sinks.read_file(&settings) {
+                env.files.insert(settings.to_string_lossy().into_owned(), content);
+            }
+        }
+    }
+
+    let fixture = Fixture {
+        handler: handler.to_string(),
+        input,
+        env,
+        expect: None,
+    };
+    let path = dir
+        .join(handler)
+        .join(format!("{}.json", now.format("%Y%m%d-%H%M%S%.3f")));
+    fixture.save(&path)?;
+    Ok(path)
+}
+
+/// ディレクトリ以下のフィクスチャ（`*.json`）をパス順に集める
+pub fn find_fixtures(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            found.extend(find_fixtures(&path)?);
+        } else if path.extension().is_some_and(|e| e == "json") {
+            found.push(path);
+        }
+    }
+    found.sort();
+    Ok(found)
+}
@@ -0,0 +1,194 @@
+use crate::git::GitContext;
+use crate::install::{expand_home, project_override};
+use crate::text::truncate_chars;
+use crate::{session_file_name, t, tf, TaskLogEntry};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 直近のタスクのプロンプトの上限文字数
+const TASK_PROMPT_LIMIT: usize = 120;
+/// 直近のタスクの応答の上限文字数
+const TASK_RESPONSE_LIMIT: usize = 200;
+
+// ===== 設定 =====
+
+/// `claude-hooks.json` の `session_context`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct SessionContextConfig {
+    /// `projects` に無いプロジェクトで有効にするか
+    pub enabled: bool,
+    /// プロジェクトのルート（`~/` 可）ごとの有効・無効
+    pub projects: HashMap<String, bool>,
+    /// チームのメモのファイル（相対パスはプロジェクトのルートから）
+    pub notes_file: Option<String>,
+    /// 表示する直近のタスクの件数
+    pub recent_tasks: usize,
+    /// `additionalContext` 全体の上限文字数
+    pub max_chars: usize,
+}
+
+impl Default for SessionContextConfig {
+    fn default() -> SessionContextConfig {
+        SessionContextConfig {
+            enabled: true,
+            projects: HashMap::new(),
+            notes_file: None,
+            recent_tasks: 3,
+            max_chars: 4000,
+        }
+    }
+}
+
+impl SessionContextConfig {
+    pub fn is_enabled(&self, project: &str, home: Option<&str>) -> bool {
+        project_override(&self.projects, project, home)
+            .copied()
+            .unwrap_or(self.enabled)
+    }
+
+    /// チームのメモのパス
+    pub fn notes_path(&self, project: &str, home: Option<&str>) -> Option<PathBuf> {
+        let file = self.notes_file.as_deref().filter(|f| !f.is_empty())?;
+        if file.starts_with('/') || file.starts_with("~/") {
+            Some(expand_home(file, home))
+        } else {
+            Some(Path::new(project).join(file))
+        }
+    }
+}
+
+// ===== Gitの状態 =====
+
+/// ブランチ・作業ツリー・upstreamとの差分
+pub fn git_text(git: &GitContext) -> String {
+    let mut lines = vec![tf("session.branch", &[("branch", &git.branch_label())])];
+    if let Some(worktree) = &git.worktree {
+        lines.push(tf("session.worktree", &[("worktree", worktree)]));
+    }
+    lines.push(if git.is_dirty() {
+        tf(
+            "session.status",
+            &[
+                ("staged", &git.staged.to_string()),
+                ("modified", &git.modified.to_string()),
+                ("untracked", &git.untracked.to_string()),
+                ("conflicted", &git.conflicted.to_string()),
+            ],
+        )
+    } else {
+        t("session.clean").to_string()
+    });
+    if let Some(upstream) = &git.upstream {
+        lines.push(tf(
+            "session.upstream",
+            &[
+                ("upstream", upstream),
+                ("ahead", &git.ahead.to_string()),
+                ("behind", &git.behind.to_string()),
+            ],
+        ));
+    }
+    lines.join("\n")
+}
+
+// ===== プルリクエストのキャッシュ =====
+
+/// `gh pr list --json number,title,url,headRefName,isDraft` の1件
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub head_ref_name: String,
+    #[serde(default)]
+    pub is_draft: bool,
+    /// `--json` に含めた場合のみ（"OPEN" / "CLOSED" / "MERGED"）
+    #[serde(default)]
+    pub state: Option<String>,
+}
+
+impl PullRequest {
+    pub fn line(&self) -> String {
+        let key = if self.is_draft { "session.pr_draft" } else { "session.pr" };
+        tf(key, &[("number", &self.number.to_string()), ("title", &self.title), ("url", &self.url)])
+    }
+}
+
+/// `~/.claude/pr-cache/<owner>_<repo>.json`（リモートのWeb URLから）
+pub fn pr_cache_path(home: &Path, remote_url: &str) -> Option<PathBuf> {
+    let without_scheme = remote_url.split_once("://").map_or(remote_url, |(_, rest)| rest);
+    let (_, repo) = without_scheme.split_once('/')?;
+    let repo = repo.trim_matches('/').trim_end_matches(".git");
+    if repo.is_empty() {
+        return None;
+    }
+    Some(home.join(".claude/pr-cache").join(format!("{}.json", session_file_name(repo))))
+}
+
+/// キャッシュから `branch` のオープンなプルリクエストを探す
+pub fn open_pull_request(cache: &str, branch: &str) -> Option<PullRequest> {
+    let pulls: Vec<PullRequest> = serde_json::from_str(cache).ok()?;
+    pulls
+        .into_iter()
+        .find(|pr| pr.head_ref_name == branch && pr.state.as_deref().is_none_or(|s| s.eq_ignore_ascii_case("open")))
+}
+
+// ===== 直近のタスク =====
+
+/// `task-complete.log` のうち `cwd` で完了したタスクを、古い順に最大 `limit` 件
+pub fn recent_tasks(entries: &[TaskLogEntry], cwd: &str, limit: usize) -> Vec<TaskLogEntry> {
+    let matching: Vec<&TaskLogEntry> = entries.iter().filter(|e| e.directory.as_deref() == Some(cwd)).collect();
+    let skip = matching.len().saturating_sub(limit);
+    matching.into_iter().skip(skip).cloned().collect()
+}
+
+pub fn tasks_text(tasks: &[TaskLogEntry]) -> String {
+    tasks
+        .iter()
+        .map(|task| {
+            tf(
+                "session.task",
+                &[
+                    ("timestamp", &task.timestamp),
+                    ("prompt", &truncate_chars(&one_line(&task.user_prompt), TASK_PROMPT_LIMIT)),
+                    ("response", &truncate_chars(&one_line(&task.assistant), TASK_RESPONSE_LIMIT)),
+                ],
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// ===== 出力 =====
+
+/// 見出しと本文の組から `additionalContext` を組み立てる（空の場合はNone）
+pub fn build(sections: &[(String, String)], max_chars: usize) -> Option<String> {
+    let sections: Vec<String> = sections
+        .iter()
+        .filter(|(_, body)| !body.trim().is_empty())
+        .map(|(heading, body)| format!("### {}\n{}", heading, body.trim()))
+        .collect();
+    if sections.is_empty() {
+        return None;
+    }
+    let context = format!("## {}\n\n{}", t("session.heading"), sections.join("\n\n"));
+    Some(truncate_chars(&context, max_chars))
+}
+
+/// 標準出力に書き出すSessionStartフックの出力JSON
+pub fn hook_output(context: &str) -> Value {
+    json!({
+        "hookSpecificOutput": {
+            "hookEventName": "SessionStart",
+            "additionalContext": context,
+        }
+    })
+}
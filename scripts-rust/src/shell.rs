@@ -0,0 +1,370 @@
+// ===== 字句解析 =====
+
+/// シェルのトークン（クォート・エスケープは除去済み）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Word(String),
+    /// `;` `&&` `||` `|` `&` などの区切り、`>` `2>` `<<` などのリダイレクト
+    Op(String),
+}
+
+/// トークン列と、`$(...)` / バッククォート内のコマンド
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lexed {
+    pub tokens: Vec<Token>,
+    pub substitutions: Vec<String>,
+}
+
+/// 長いものから順に照合する演算子
+const OPERATORS: &[&str] = &[
+    "&>>", "<<<", "<<-", "&&", "||", ";;", "|&", "&>", ">>", "<<", ">&", "<&", ">|", ";", "&", "|", "<", ">", "(", ")",
+];
+
+/// POSIXシェルに近い規則でコマンド文字列をトークンに分割する
+///
+/// シングル/ダブルクォート、バックスラッシュ、コメント、ヒアドキュメント、
+/// `2>` のようなファイルディスクリプタ付きリダイレクトに対応する。
+/// `$(...)` とバッククォートの中身は `substitutions` に入れ、単語には元の表記のまま残す。
+pub fn tokenize(input: &str) -> Result<Lexed, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut lexed = Lexed::default();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut heredoc_next = false;
+    let mut heredocs: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    // 単語を確定する（ヒアドキュメントの区切り文字はトークンにしない）
+    let flush = |word: &mut String, in_word: &mut bool, heredoc_next: &mut bool, heredocs: &mut Vec<String>, tokens: &mut Vec<Token>| {
+        if !*in_word {
+            return;
+        }
+        if *heredoc_next {
+            heredocs.push(word.clone());
+            *heredoc_next = false;
+        }
+        tokens.push(Token::Word(std::mem::take(word)));
+        *in_word = false;
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' => {
+                flush(&mut word, &mut in_word, &mut heredoc_next, &mut heredocs, &mut lexed.tokens);
+                i += 1;
+            }
+            '\n' => {
+                flush(&mut word, &mut in_word, &mut heredoc_next, &mut heredocs, &mut lexed.tokens);
+                lexed.tokens.push(Token::Op(";".to_string()));
+                i += 1;
+                // ヒアドキュメントの本文を読み飛ばす
+                for delimiter in heredocs.drain(..) {
+                    while i < chars.len() {
+                        let end = chars[i..].iter().position(|&c| c == '\n').map_or(chars.len(), |p| i + p);
+                        let line: String = chars[i..end].iter().collect();
+                        i = (end + 1).min(chars.len());
+                        if line.trim() == delimiter {
+                            break;
+                        }
+                    }
+                }
+            }
+            '#' if !in_word => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '\'')
+                    .ok_or("unterminated single quote")?;
+                word.extend(&chars[i + 1..i + 1 + end]);
+                i += end + 2;
+            }
+            '"' => {
+                in_word = true;
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err("unterminated double quote".to_string()),
+                        Some('"') => {
+                            i += 1;
+                            break;
+                        }
+                        Some('\\') if matches!(chars.get(i + 1), Some('$' | '`' | '"' | '\\' | '\n')) => {
+                            if chars[i + 1] != '\n' {
+                                word.push(chars[i + 1]);
+                            }
+                            i += 2;
+                        }
+                        Some('$') if chars.get(i + 1) == Some(&'(') => {
+                            i = read_substitution(&chars, i, &mut word, &mut lexed.substitutions)?;
+                        }
+                        Some('`') => {
+                            i = read_backquote(&chars, i, &mut word, &mut lexed.substitutions)?;
+                        }
+                        Some(&c) => {
+                            word.push(c);
+                            i += 1;
+                        }
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(&next) = chars.get(i + 1) {
+                    if next != '\n' {
+                        word.push(next);
+                    }
+                }
+                i += 2;
+            }
+            '$' if chars.get(i + 1) == Some(&'(') => {
+                in_word = true;
+                i = read_substitution(&chars, i, &mut word, &mut lexed.substitutions)?;
+            }
+            '`' => {
+                in_word = true;
+                i = read_backquote(&chars, i, &mut word, &mut lexed.substitutions)?;
+            }
+            ';' | '&' | '|' | '<' | '>' | '(' | ')' => {
+                let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+                let op = OPERATORS.iter().find(|op| rest.starts_with(*op)).copied().unwrap_or(";");
+                // `2>` のように数字だけの単語の直後のリダイレクトはファイルディスクリプタ
+                let fd = if in_word && matches!(c, '<' | '>') && !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()) {
+                    in_word = false;
+                    std::mem::take(&mut word)
+                } else {
+                    flush(&mut word, &mut in_word, &mut heredoc_next, &mut heredocs, &mut lexed.tokens);
+                    String::new()
+                };
+                if op == "<<" || op == "<<-" {
+                    heredoc_next = true;
+                }
+                lexed.tokens.push(Token::Op(format!("{}{}", fd, op)));
+                i += op.len();
+            }
+            _ => {
+                in_word = true;
+                word.push(c);
+                i += 1;
+            }
+        }
+    }
+    flush(&mut word, &mut in_word, &mut heredoc_next, &mut heredocs, &mut lexed.tokens);
+    Ok(lexed)
+}
+
+/// `$(...)` を読み取る（`$((...))` の算術式はコマンドとして扱わない）
+fn read_substitution(chars: &[char], start: usize, word: &mut String, substitutions: &mut Vec<String>) -> Result<usize, String> {
+    let mut depth = 0;
+    let mut i = start + 1;
+    let mut quote: Option<char> = None;
+    while i < chars.len() {
+        let c = chars[i];
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => i += 1,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '\\') => i += 1,
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth -= 1;
+                if depth == 0 {
+                    let inner: String = chars[start + 2..i].iter().collect();
+                    word.extend(&chars[start..=i]);
+                    if !inner.starts_with('(') {
+                        substitutions.push(inner);
+                    }
+                    return Ok(i + 1);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    Err("unterminated command substitution".to_string())
+}
+
+fn read_backquote(chars: &[char], start: usize, word: &mut String, substitutions: &mut Vec<String>) -> Result<usize, String> {
+    let end = chars[start + 1..]
+        .iter()
+        .position(|&c| c == '`')
+        .ok_or("unterminated backquote")?;
+    substitutions.push(chars[start + 1..start + 1 + end].iter().collect());
+    word.extend(&chars[start..start + end + 2]);
+    Ok(start + end + 2)
+}
+
+// ===== 構文解析 =====
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    /// `>`, `>>`, `2>`, `&>`, `<`, `<<` など
+    pub op: String,
+    pub target: String,
+}
+
+impl Redirect {
+    /// ファイルへの書き込みか（`2>&1` のようなディスクリプタの複製は除く）
+    pub fn is_write(&self) -> bool {
+        self.op.contains('>') && !self.op.ends_with(">&")
+    }
+}
+
+/// パイプや `;` `&&` で区切られた1つのコマンド
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimpleCommand {
+    pub argv: Vec<String>,
+    pub redirects: Vec<Redirect>,
+    /// 直前のコマンドからパイプで入力を受け取るか
+    pub piped: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedCommand {
+    pub commands: Vec<SimpleCommand>,
+    /// `$(...)` / バッククォート内のコマンド文字列
+    pub substitutions: Vec<String>,
+}
+
+/// コマンド文字列を単純コマンドの列に分解する
+pub fn parse(input: &str) -> Result<ParsedCommand, String> {
+    let lexed = tokenize(input)?;
+    let mut commands = Vec::new();
+    let mut current = SimpleCommand::default();
+    let mut tokens = lexed.tokens.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Word(word) => current.argv.push(word),
+            Token::Op(op) if op.contains('<') || op.contains('>') => {
+                let target = match tokens.peek() {
+                    Some(Token::Word(_)) => match tokens.next() {
+                        Some(Token::Word(word)) => word,
+                        _ => String::new(),
+                    },
+                    _ => String::new(),
+                };
+                current.redirects.push(Redirect { op, target });
+            }
+            Token::Op(op) => {
+                let piped = op == "|" || op == "|&";
+                if !current.argv.is_empty() || !current.redirects.is_empty() {
+                    commands.push(std::mem::take(&mut current));
+                }
+                current.piped = piped;
+            }
+        }
+    }
+    if !current.argv.is_empty() || !current.redirects.is_empty() {
+        commands.push(current);
+    }
+
+    Ok(ParsedCommand {
+        commands,
+        substitutions: lexed.substitutions,
+    })
+}
+
+// ===== コマンドの正規化 =====
+
+/// `sudo` `env` `nohup` などのラッパーと環境変数の代入を取り除いた実際のコマンド
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unwrapped<'a> {
+    /// 取り除いたラッパーのコマンド名（`sudo` など）
+    pub wrappers: Vec<&'a str>,
+    pub argv: &'a [String],
+}
+
+impl Unwrapped<'_> {
+    /// 実行されるプログラム名（パスを除く）
+    pub fn program(&self) -> &str {
+        self.argv.first().map(|p| program_name(p)).unwrap_or("")
+    }
+
+    pub fn args(&self) -> &[String] {
+        self.argv.get(1..).unwrap_or(&[])
+    }
+}
+
+/// `/usr/bin/rm` → `rm`
+pub fn program_name(word: &str) -> &str {
+    word.rsplit('/').next().unwrap_or(word)
+}
+
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// ラッパーと、その引数を取るオプション
+fn wrapper_options(program: &str) -> Option<&'static [&'static str]> {
+    match program {
+        "sudo" | "doas" => Some(&["-u", "-g", "-U", "-C", "-h", "-p", "-r", "-t"]),
+        "env" => Some(&["-u", "-C", "-S"]),
+        "nice" => Some(&["-n"]),
+        "timeout" => Some(&["-s", "-k", "--signal", "--kill-after"]),
+        "xargs" => Some(&["-I", "-L", "-n", "-P", "-d", "-s", "-E", "-a"]),
+        "nohup" | "time" | "command" | "exec" | "builtin" | "stdbuf" | "caffeinate" => Some(&[]),
+        _ => None,
+    }
+}
+
+/// ラッパーを取り除く
+pub fn unwrap_command(argv: &[String]) -> Unwrapped<'_> {
+    let mut wrappers = Vec::new();
+    let mut i = 0;
+    while i < argv.len() {
+        if is_assignment(&argv[i]) {
+            i += 1;
+            continue;
+        }
+        let program = program_name(&argv[i]);
+        let options = match wrapper_options(program) {
+            Some(options) => options,
+            None => break,
+        };
+        wrappers.push(program);
+        i += 1;
+        while i < argv.len() && argv[i].starts_with('-') {
+            if options.contains(&argv[i].as_str()) {
+                i += 1;
+            }
+            i += 1;
+        }
+        // timeout の時間指定
+        if program == "timeout" && i < argv.len() {
+            i += 1;
+        }
+    }
+    Unwrapped {
+        wrappers,
+        argv: &argv[i.min(argv.len())..],
+    }
+}
+
+/// `bash -c '...'` や `eval ...` で実行される文字列
+pub fn inline_script(command: &Unwrapped) -> Option<String> {
+    match command.program() {
+        "sh" | "bash" | "zsh" | "dash" | "ksh" | "fish" => {
+            let args = command.args();
+            let position = args
+                .iter()
+                .position(|a| a.starts_with('-') && !a.starts_with("--") && a.contains('c'))?;
+            args.get(position + 1).cloned()
+        }
+        "eval" => Some(command.args().join(" ")),
+        _ => None,
+    }
+}
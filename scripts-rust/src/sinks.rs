@@ -0,0 +1,409 @@
+use crate::config::HooksConfig;
+use crate::git::{self, DiffStatEntry, GitContext};
+use crate::slack::{self, SlackThread};
+use crate::focus;
+use crate::path_policy::resolve_symlinks;
+use crate::{get_activation_bundle_id, post_to_slack_thread, send_notification};
+use crate::system::{RealSystem, System};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// ===== ハンドラーの出力 =====
+
+/// デスクトップ通知（terminal-notifier）の内容
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DesktopNotification {
+    pub title: String,
+    pub subtitle: String,
+    pub message: String,
+    pub sound: String,
+}
+
+/// Slackに送るメッセージ
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SlackPost {
+    pub title: String,
+    /// ラベルと値の組
+    pub fields: Vec<(String, String)>,
+    /// Markdown本文の見出し（`post_to_slack_markdown` 相当で送る場合）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub markdown: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub button_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread: Option<SessionThread>,
+}
+
+/// セッションのスレッド（Botモードのみ）
+///
+/// セッションで最初に送信したメッセージをスレッドの親として記録し、`reply` の場合はそのスレッドに返信する。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SessionThread {
+    pub session_id: String,
+    pub reply: bool,
+}
+
+impl SlackPost {
+    pub fn new(title: String, fields: &[(&str, &str)]) -> SlackPost {
+        SlackPost {
+            title,
+            fields: fields.iter().map(|(l, v)| (l.to_string(), v.to_string())).collect(),
+            body_label: None,
+            markdown: None,
+            button_url: None,
+            thread: None,
+        }
+    }
+
+    /// Markdown本文を付ける
+    pub fn with_markdown(mut self, label: &str, markdown: &str) -> SlackPost {
+        self.body_label = Some(label.to_string());
+        self.markdown = Some(markdown.to_string());
+        self
+    }
+
+    pub fn with_button(mut self, url: Option<String>) -> SlackPost {
+        self.button_url = url;
+        self
+    }
+
+    /// セッションのスレッドに記録する（`reply` の場合はスレッドに返信する）
+    pub fn in_session_thread(mut self, session_id: &str, reply: bool) -> SlackPost {
+        self.thread = Some(SessionThread {
+            session_id: session_id.to_string(),
+            reply,
+        });
+        self
+    }
+
+    /// `post_to_slack_rich` / `post_to_slack_markdown` 相当の内容を送信する
+    pub fn send(&self, system: &dyn System) -> Result<(), String> {
+        let fields: Vec<(&str, &str)> = self.fields.iter().map(|(l, v)| (l.as_str(), v.as_str())).collect();
+        let markdown = match (&self.body_label, &self.markdown) {
+            (Some(label), Some(markdown)) => Some((label.as_str(), markdown.as_str())),
+            _ => None,
+        };
+
+        // セッションのスレッドの親メッセージ
+        let thread_file = match (&self.thread, system.home_dir()) {
+            (Some(thread), Some(home)) => Some(slack::thread_path(&home, &thread.session_id)),
+            _ => None,
+        };
+        let parent: Option<SlackThread> = match (&self.thread, &thread_file) {
+            (Some(thread), Some(path)) if thread.reply => system
+                .read_to_string(path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok()),
+            _ => None,
+        };
+
+        let posted = post_to_slack_thread(
+            system,
+            &self.title,
+            &fields,
+            markdown,
+            self.button_url.as_deref(),
+            parent.as_ref(),
+        )?;
+
+        // セッションで最初に送信したメッセージをスレッドの親として記録する
+        if let (Some(path), Some(posted), None) = (&thread_file, posted, &parent) {
+            if system.read_to_string(path).is_err() {
+                let content = serde_json::to_string(&posted).map_err(|e| e.to_string())?;
+                system
+                    .write_file(path, &content)
+                    .map_err(|e| format!("Failed to save Slack thread: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// ===== 入出力先 =====
+
+/// ハンドラーが使う外部の入出力（デスクトップ通知、Slack、ファイル、時刻、Git）
+///
+/// バイナリは `RealSinks` を、テストとリプレイは記録用の `FakeSinks` を使う。
+/// `RealSinks` は `System` を経由するため、`FakeSystem` と組み合わせると送信されるコマンドやHTTPリクエストまで確認できる。
+pub trait Sinks {
+    fn config(&self) -> &HooksConfig;
+    fn home(&self) -> Option<PathBuf>;
+    /// `$TMPDIR`（設定されていない場合はNone）
+    fn temp_dir(&self) -> Option<PathBuf>;
+    fn now(&self) -> DateTime<FixedOffset>;
+
+    /// `cwd` のGit状態（Gitリポジトリでない場合はNone）
+    fn git_context(&self, cwd: &str) -> Option<GitContext>;
+    fn git_toplevel(&self, cwd: &str) -> Option<String>;
+    fn git_diff_numstat(&self, cwd: &str) -> Option<Vec<DiffStatEntry>>;
+    /// シンボリックリンクを解決したパス（macOSの `/var` → `/private/var` など。`git rev-parse --show-toplevel` と比較する）
+    fn resolve_path(&self, path: &str) -> String;
+
+    /// Slackのリンクで開くセッションのURL（`x-claude://focus?...`、iTerm2は `x-claude-iterm://`）
+    fn focus_url(&self) -> Option<String>;
+    fn notify(&self, notification: &DesktopNotification) -> io::Result<()>;
+    fn post_slack(&self, post: &SlackPost) -> Result<(), String>;
+
+    fn read_file(&self, path: &Path) -> io::Result<String>;
+    /// ファイルを書き込む（親ディレクトリが無ければ作る）
+    fn write_file(&self, path: &Path, content: &str) -> io::Result<()>;
+    /// ファイルに追記する（親ディレクトリが無ければ作る）
+    fn append_file(&self, path: &Path, content: &str) -> io::Result<()>;
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    /// `claude-hooks` のサブコマンドをバックグラウンドで実行する
+    fn spawn_hooks(&self, args: &[&str]) -> io::Result<()>;
+    /// フックの標準出力に1行書く（PreToolUseの判定や表示用のテキスト）
+    fn print(&self, line: &str);
+}
+
+/// 実際の環境（設定ファイル・Gitを含むすべての入出力は `System` を経由する）
+#[derive(Debug, Default)]
+pub struct RealSinks<S: System = RealSystem> {
+    pub system: S,
+    /// `system` から最初に使うときに読み込む設定
+    config: OnceLock<HooksConfig>,
+}
+
+impl<S: System> RealSinks<S> {
+    pub const fn new(system: S) -> RealSinks<S> {
+        RealSinks {
+            system,
+            config: OnceLock::new(),
+        }
+    }
+}
+
+impl<S: System> Sinks for RealSinks<S> {
+    fn config(&self) -> &HooksConfig {
+        self.config.get_or_init(|| HooksConfig::load(&self.system))
+    }
+
+    fn home(&self) -> Option<PathBuf> {
+        self.system.home_dir()
+    }
+
+    fn temp_dir(&self) -> Option<PathBuf> {
+        self.system.env_var("TMPDIR").map(PathBuf::from)
+    }
+
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.system.now()
+    }
+
+    fn git_context(&self, cwd: &str) -> Option<GitContext> {
+        GitContext::collect(&self.system, cwd)
+    }
+
+    fn git_toplevel(&self, cwd: &str) -> Option<String> {
+        git::toplevel(&self.system, cwd)
+    }
+
+    fn git_diff_numstat(&self, cwd: &str) -> Option<Vec<DiffStatEntry>> {
+        git::diff_numstat(&self.system, cwd)
+    }
+
+    fn resolve_path(&self, path: &str) -> String {
+        resolve_symlinks(Path::new(path)).to_string_lossy().into_owned()
+    }
+
+    fn focus_url(&self) -> Option<String> {
+        focus::button_url(&self.system, cfg!(target_os = "macos"))
+    }
+
+    fn notify(&self, n: &DesktopNotification) -> io::Result<()> {
+        let bundle_id = get_activation_bundle_id(&self.system);
+        send_notification(&self.system, &n.title, &n.message, &n.subtitle, &bundle_id, &n.sound)
+    }
+
+    fn post_slack(&self, post: &SlackPost) -> Result<(), String> {
+        post.send(&self.system)
+    }
+
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        self.system.read_to_string(path)
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> io::Result<()> {
+        self.system.write_file(path, content)
+    }
+
+    fn append_file(&self, path: &Path, content: &str) -> io::Result<()> {
+        self.system.append_file(path, content)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.system.remove_file(path)
+    }
+
+    fn spawn_hooks(&self, args: &[&str]) -> io::Result<()> {
+        // フックのバイナリと同じディレクトリの `claude-hooks`（無ければPATHから探す）
+        let program = env::current_exe()
+            .ok()
+            .and_then(|exe| Some(exe.parent()?.join("claude-hooks")))
+            .filter(|path| path.exists())
+            .map_or_else(|| "claude-hooks".to_string(), |path| path.to_string_lossy().into_owned());
+        self.system.spawn(&program, args)
+    }
+
+    fn print(&self, line: &str) {
+        println!("{}", line);
+    }
+}
+
+// ===== 記録用の環境 =====
+
+/// ハンドラーが出力した内容
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Outputs {
+    pub desktop: Vec<DesktopNotification>,
+    pub slack: Vec<SlackPost>,
+    pub stdout: Vec<String>,
+    /// 書き込まれたファイルの最終的な内容
+    pub files: BTreeMap<String, String>,
+    /// 削除されたファイル
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+    /// バックグラウンドで実行した `claude-hooks` の引数
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spawned: Vec<Vec<String>>,
+    /// ハンドラーがエラーを返した場合のメッセージ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 外部に出力せずに記録する環境（ファイルはメモリ上に置く）
+#[derive(Debug, Default)]
+pub struct FakeSinks {
+    pub config: HooksConfig,
+    pub home: Option<PathBuf>,
+    pub temp_dir: Option<PathBuf>,
+    pub now: DateTime<FixedOffset>,
+    pub git: Option<GitContext>,
+    pub toplevel: Option<String>,
+    pub diff: Vec<DiffStatEntry>,
+    pub focus_url: Option<String>,
+    files: RefCell<BTreeMap<PathBuf, String>>,
+    outputs: RefCell<Outputs>,
+}
+
+impl FakeSinks {
+    pub fn new() -> FakeSinks {
+        FakeSinks::default()
+    }
+
+    /// 読み込み用のファイルを置く
+    pub fn add_file(&self, path: impl Into<PathBuf>, content: &str) {
+        self.files.borrow_mut().insert(path.into(), content.to_string());
+    }
+
+    pub fn outputs(&self) -> Outputs {
+        self.outputs.borrow().clone()
+    }
+
+    fn record_file(&self, path: &Path, content: String) {
+        self.outputs
+            .borrow_mut()
+            .files
+            .insert(path.to_string_lossy().into_owned(), content.clone());
+        self.files.borrow_mut().insert(path.to_path_buf(), content);
+    }
+}
+
+impl Sinks for FakeSinks {
+    fn config(&self) -> &HooksConfig {
+        &self.config
+    }
+
+    fn home(&self) -> Option<PathBuf> {
+        self.home.clone()
+    }
+
+    fn temp_dir(&self) -> Option<PathBuf> {
+        self.temp_dir.clone()
+    }
+
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.now
+    }
+
+    fn git_context(&self, _cwd: &str) -> Option<GitContext> {
+        self.git.clone()
+    }
+
+    fn git_toplevel(&self, _cwd: &str) -> Option<String> {
+        self.toplevel.clone()
+    }
+
+    fn git_diff_numstat(&self, _cwd: &str) -> Option<Vec<DiffStatEntry>> {
+        self.toplevel.as_ref().map(|_| self.diff.clone())
+    }
+
+    fn resolve_path(&self, path: &str) -> String {
+        path.to_string()
+    }
+
+    fn focus_url(&self) -> Option<String> {
+        self.focus_url.clone()
+    }
+
+    fn notify(&self, notification: &DesktopNotification) -> io::Result<()> {
+        self.outputs.borrow_mut().desktop.push(notification.clone());
+        Ok(())
+    }
+
+    fn post_slack(&self, post: &SlackPost) -> Result<(), String> {
+        self.outputs.borrow_mut().slack.push(post.clone());
+        Ok(())
+    }
+
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        self.files
+            .borrow()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> io::Result<()> {
+        self.record_file(path, content.to_string());
+        Ok(())
+    }
+
+    fn append_file(&self, path: &Path, content: &str) -> io::Result<()> {
+        let existing = self.files.borrow().get(path).cloned().unwrap_or_default();
+        self.record_file(path, existing + content);
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        if self.files.borrow_mut().remove(path).is_none() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, path.display().to_string()));
+        }
+        let mut outputs = self.outputs.borrow_mut();
+        let name = path.to_string_lossy().into_owned();
+        outputs.files.remove(&name);
+        outputs.removed.push(name);
+        Ok(())
+    }
+
+    fn spawn_hooks(&self, args: &[&str]) -> io::Result<()> {
+        self.outputs
+            .borrow_mut()
+            .spawned
+            .push(args.iter().map(|a| a.to_string()).collect());
+        Ok(())
+    }
+
+    fn print(&self, line: &str) {
+        self.outputs.borrow_mut().stdout.push(line.to_string());
+    }
+}
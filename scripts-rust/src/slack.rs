@@ -0,0 +1,390 @@
+use crate::focus::ITERM2_URL_PREFIX;
+use crate::slack_markdown::{split_text, MAX_BLOCKS, SECTION_TEXT_LIMIT};
+use crate::text;
+use crate::{session_file_name, t, tf};
+use crate::system::{HttpBody, HttpRequest, System};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// sectionブロックのfieldsの各textの上限文字数
+pub const FIELD_TEXT_LIMIT: usize = 2000;
+
+/// sectionブロック1つのfieldsの上限数
+pub const MAX_SECTION_FIELDS: usize = 10;
+
+/// headerブロックのtextの上限文字数
+pub const HEADER_TEXT_LIMIT: usize = 150;
+
+/// Incoming Webhookで連続送信するメッセージ数の上限
+pub const MAX_WEBHOOK_PAGES: usize = 5;
+
+const SLACK_API: &str = "https://slack.com/api";
+
+// ===== 送信モード =====
+
+/// Slackへの送信方法
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlackMode {
+    /// 未設定（送信しない）
+    Disabled,
+    /// Incoming Webhook（続きは別メッセージとして送信）
+    Webhook { url: String },
+    /// Bot Token + チャンネル（続きはスレッドに返信、全文をファイルとして添付可能）
+    Bot {
+        token: String,
+        channel: String,
+        upload_snippets: bool,
+    },
+}
+
+impl SlackMode {
+    /// 環境変数から送信モードを決定する
+    ///
+    /// `CLAUDE_CODE_SLACK_BOT_TOKEN` と `CLAUDE_CODE_SLACK_CHANNEL` が設定されていればBotモード、
+    /// そうでなければ `CLAUDE_CODE_SLACK_WEBHOOK_URL` のWebhookモードを使う。
+    pub fn from_env(system: &dyn System) -> SlackMode {
+        let non_empty = |key: &str| system.env_var(key).filter(|v| !v.is_empty());
+
+        if let (Some(token), Some(channel)) = (
+            non_empty("CLAUDE_CODE_SLACK_BOT_TOKEN"),
+            non_empty("CLAUDE_CODE_SLACK_CHANNEL"),
+        ) {
+            let upload_snippets = non_empty("CLAUDE_CODE_SLACK_UPLOAD_SNIPPETS")
+                .map(|v| v == "1" || v == "true")
+                .unwrap_or(false);
+            return SlackMode::Bot {
+                token,
+                channel,
+                upload_snippets,
+            };
+        }
+
+        match non_empty("CLAUDE_CODE_SLACK_WEBHOOK_URL") {
+            Some(url) => SlackMode::Webhook { url },
+            None => SlackMode::Disabled,
+        }
+    }
+}
+
+// ===== メッセージ構築とページ分割 =====
+
+/// 送信するメッセージの内容
+pub struct SlackMessage<'a> {
+    pub title: &'a str,
+    pub fields: &'a [(&'a str, &'a str)],
+    /// Markdownなどから変換済みの本文ブロック
+    pub body: Vec<Value>,
+    pub button_url: Option<&'a str>,
+    /// ファイルとして添付する場合の全文（本文の元テキスト）
+    pub full_text: Option<&'a str>,
+    /// 返信先のスレッド（Botモードのみ。Webhookモードでは通常のメッセージとして送信する）
+    pub thread: Option<&'a SlackThread>,
+}
+
+// ===== セッションのスレッド =====
+
+/// Botモードで送信したメッセージ（スレッドの親）の位置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SlackThread {
+    pub channel: String,
+    pub ts: String,
+}
+
+/// セッションのスレッドの親メッセージを保存するファイル（`~/.claude/slack-threads/<session_id>.json`）
+pub fn thread_path(home: &Path, session_id: &str) -> PathBuf {
+    home.join(".claude/slack-threads").join(format!("{}.json", session_file_name(session_id)))
+}
+
+/// メッセージを上限内に収まるページ（1ページ = 1回の送信分のブロック列）に分割する
+///
+/// `FIELD_TEXT_LIMIT` を超えるフィールドは切り詰めずに本文ブロックへ移し、
+/// 残りのフィールドは `MAX_SECTION_FIELDS` 個ずつのsectionに分け、`MAX_BLOCKS` を超える本文は続きのページに送る。
+pub fn build_pages(message: &SlackMessage) -> Vec<Vec<Value>> {
+    let mut head = vec![json!({
+        "type": "header",
+        "text": {
+            "type": "plain_text",
+            "text": text::truncate_chars(message.title, HEADER_TEXT_LIMIT),
+        }
+    })];
+
+    let (short_fields, long_fields): (Vec<_>, Vec<_>) = message
+        .fields
+        .iter()
+        .partition(|(label, value)| label.chars().count() + value.chars().count() + 3 <= FIELD_TEXT_LIMIT);
+
+    for chunk in short_fields.chunks(MAX_SECTION_FIELDS) {
+        let field_elements: Vec<_> = chunk
+            .iter()
+            .map(|(label, value)| {
+                json!({
+                    "type": "mrkdwn",
+                    "text": format!("*{}*\n{}", label, value)
+                })
+            })
+            .collect();
+        head.push(json!({
+            "type": "section",
+            "fields": field_elements
+        }));
+    }
+
+    let mut body = Vec::new();
+    for (label, value) in long_fields {
+        body.push(section(&format!("*{}*", label)));
+        body.extend(long_text_blocks(value));
+    }
+    body.extend(message.body.iter().cloned());
+
+    // セッションを開くリンク（Incoming Webhookはactionsブロック非対応のためsectionで実装）
+    let link = message.button_url.map(|url| {
+        let label = if url.starts_with(ITERM2_URL_PREFIX) {
+            t("slack.open_iterm2")
+        } else {
+            t("slack.open_session")
+        };
+        section(&format!(":computer: <{}|{}>", url, label))
+    });
+
+    // 1ページ目: ヘッダー + フィールド + 本文の先頭 + リンク
+    let first_capacity = MAX_BLOCKS - head.len() - usize::from(link.is_some());
+    let mut pages = Vec::new();
+    let mut rest = body.as_slice();
+
+    let take = rest.len().min(first_capacity);
+    let mut first = head;
+    first.extend(rest[..take].iter().cloned());
+    rest = &rest[take..];
+    first.extend(link);
+    pages.push(first);
+
+    // 2ページ目以降: 続きの表示 + 本文
+    while !rest.is_empty() {
+        let take = rest.len().min(MAX_BLOCKS - 1);
+        let mut page = vec![json!({
+            "type": "context",
+            "elements": [{"type": "mrkdwn", "text": ""}]
+        })];
+        page.extend(rest[..take].iter().cloned());
+        rest = &rest[take..];
+        pages.push(page);
+    }
+
+    let total = pages.len();
+    if total > 1 {
+        // 総ページ数が確定してから続きの表示を埋める
+        for (i, page) in pages.iter_mut().enumerate().skip(1) {
+            let continued = tf(
+                "slack.continued",
+                &[("title", message.title), ("page", &(i + 1).to_string()), ("total", &total.to_string())],
+            );
+            page[0]["elements"][0]["text"] = json!(format!("_{}_", continued));
+        }
+    }
+
+    pages
+}
+
+/// ページ数を `max_pages` に制限する（超えた分は最後のページの末尾で、上限と全文を添付する方法を表示）
+///
+/// Incoming Webhookではファイルを添付できないため、Botモードのように続きをアップロードできない。
+pub fn limit_pages(mut pages: Vec<Vec<Value>>, max_pages: usize) -> Vec<Vec<Value>> {
+    if pages.len() <= max_pages || max_pages == 0 {
+        return pages;
+    }
+    let mut omitted: usize = pages[max_pages..].iter().map(|p| p.len() - 1).sum();
+    pages.truncate(max_pages);
+    if let Some(last) = pages.last_mut() {
+        if last.len() >= MAX_BLOCKS {
+            last.pop();
+            omitted += 1;
+        }
+        last.push(json!({
+            "type": "context",
+            "elements": [{"type": "mrkdwn", "text": tf("slack.truncated", &[("count", &omitted.to_string()), ("max", &max_pages.to_string())])}]
+        }));
+    }
+    pages
+}
+
+fn section(text: &str) -> Value {
+    json!({
+        "type": "section",
+        "text": {
+            "type": "mrkdwn",
+            "text": text,
+        }
+    })
+}
+
+/// 長いテキストを上限内のsectionブロック列にする（コードブロックは分割後もフェンスで囲む）
+fn long_text_blocks(value: &str) -> Vec<Value> {
+    let code = value
+        .strip_prefix("```\n")
+        .and_then(|v| v.strip_suffix("\n```"));
+    match code {
+        Some(code) => split_text(code, SECTION_TEXT_LIMIT - 8)
+            .iter()
+            .map(|part| section(&format!("```\n{}\n```", part)))
+            .collect(),
+        None => split_text(value, SECTION_TEXT_LIMIT)
+            .iter()
+            .map(|part| section(part))
+            .collect(),
+    }
+}
+
+// ===== 送信 =====
+
+/// 送信モードに応じてメッセージを送信する
+///
+/// Botモードでは最初のメッセージの位置（スレッドに返信した場合は返信先のスレッド）を返す。
+pub fn send(system: &dyn System, mode: &SlackMode, message: &SlackMessage) -> Result<Option<SlackThread>, String> {
+    match mode {
+        SlackMode::Disabled => Ok(None),
+        SlackMode::Webhook { url } => {
+            for page in limit_pages(build_pages(message), MAX_WEBHOOK_PAGES) {
+                post_webhook(system, url, json!({ "blocks": page }))?;
+            }
+            Ok(None)
+        }
+        SlackMode::Bot {
+            token,
+            channel,
+            upload_snippets,
+        } => {
+            let pages = build_pages(message);
+            let paginated = pages.len() > 1;
+            let mut pages = pages.into_iter();
+
+            // スレッドへの返信では続きのページも同じスレッドに送る
+            let (channel, parent_ts) = match message.thread {
+                Some(thread) => (thread.channel.as_str(), Some(thread.ts.as_str())),
+                None => (channel.as_str(), None),
+            };
+            let first = pages.next().unwrap_or_default();
+            let (channel_id, ts) = post_bot_message(system, token, channel, message.title, first, parent_ts)?;
+            let thread = SlackThread {
+                channel: channel_id,
+                ts: parent_ts.map(str::to_string).unwrap_or(ts),
+            };
+
+            // 全文ファイルを添付する場合、続きのページはスレッドに送らない
+            if *upload_snippets && paginated {
+                if let Some(text) = message.full_text {
+                    upload_snippet(system, token, &thread.channel, &thread.ts, message.title, text)?;
+                    return Ok(Some(thread));
+                }
+            }
+            for page in pages {
+                post_bot_message(system, token, &thread.channel, message.title, page, Some(&thread.ts))?;
+            }
+            Ok(Some(thread))
+        }
+    }
+}
+
+fn post_webhook(system: &dyn System, url: &str, payload: Value) -> Result<(), String> {
+    let request = HttpRequest::new(url, HttpBody::Json(payload)).header("Content-Type", "application/json");
+    system
+        .http_post(&request)
+        .map(|_| ())
+        .map_err(|e| format!("Slack POST failed: {}", e))
+}
+
+/// chat.postMessageで送信し、(チャンネルID, ts) を返す
+fn post_bot_message(
+    system: &dyn System,
+    token: &str,
+    channel: &str,
+    fallback_text: &str,
+    blocks: Vec<Value>,
+    thread_ts: Option<&str>,
+) -> Result<(String, String), String> {
+    let mut payload = json!({
+        "channel": channel,
+        "text": fallback_text,
+        "blocks": blocks,
+    });
+    if let Some(ts) = thread_ts {
+        payload["thread_ts"] = json!(ts);
+    }
+
+    let response = call_api(system, token, "chat.postMessage", payload)?;
+    let channel_id = response["channel"].as_str().unwrap_or(channel).to_string();
+    let ts = response["ts"]
+        .as_str()
+        .ok_or("Slack chat.postMessage returned no ts")?
+        .to_string();
+    Ok((channel_id, ts))
+}
+
+/// 全文をテキストファイルとしてスレッドにアップロードする（files.uploadV2相当の3ステップ）
+fn upload_snippet(
+    system: &dyn System,
+    token: &str,
+    channel_id: &str,
+    thread_ts: &str,
+    title: &str,
+    text: &str,
+) -> Result<(), String> {
+    let form = vec![
+        ("filename".to_string(), "full-text.md".to_string()),
+        ("length".to_string(), text.len().to_string()),
+    ];
+    let request = HttpRequest::new(&format!("{}/files.getUploadURLExternal", SLACK_API), HttpBody::Form(form))
+        .header("Authorization", &format!("Bearer {}", token));
+    let response = system
+        .http_post(&request)
+        .map_err(|e| format!("Slack files.getUploadURLExternal failed: {}", e))?;
+    let response = parse_response(&response)?;
+    check_ok("files.getUploadURLExternal", &response)?;
+
+    let upload_url = response["upload_url"].as_str().ok_or("Slack returned no upload_url")?;
+    let file_id = response["file_id"].as_str().ok_or("Slack returned no file_id")?;
+
+    system
+        .http_post(&HttpRequest::new(upload_url, HttpBody::Bytes(text.as_bytes().to_vec())))
+        .map_err(|e| format!("Slack file upload failed: {}", e))?;
+
+    call_api(
+        system,
+        token,
+        "files.completeUploadExternal",
+        json!({
+            "files": [{"id": file_id, "title": title}],
+            "channel_id": channel_id,
+            "thread_ts": thread_ts,
+        }),
+    )
+    .map(|_| ())
+}
+
+fn call_api(system: &dyn System, token: &str, method: &str, payload: Value) -> Result<Value, String> {
+    let request = HttpRequest::new(&format!("{}/{}", SLACK_API, method), HttpBody::Json(payload))
+        .header("Authorization", &format!("Bearer {}", token))
+        .header("Content-Type", "application/json; charset=utf-8");
+    let response = system
+        .http_post(&request)
+        .map_err(|e| format!("Slack {} failed: {}", method, e))?;
+    let response = parse_response(&response)?;
+    check_ok(method, &response)?;
+    Ok(response)
+}
+
+fn parse_response(body: &str) -> Result<Value, String> {
+    serde_json::from_str(body).map_err(|e| format!("Slack response parse failed: {}", e))
+}
+
+fn check_ok(method: &str, response: &Value) -> Result<(), String> {
+    if response["ok"].as_bool() == Some(true) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Slack {} error: {}",
+            method,
+            response["error"].as_str().unwrap_or("unknown")
+        ))
+    }
+}
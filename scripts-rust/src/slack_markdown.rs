@@ -0,0 +1,418 @@
+use serde_json::{json, Value};
+
+/// Slackのsectionブロックのtextの上限文字数
+pub const SECTION_TEXT_LIMIT: usize = 3000;
+
+/// 1メッセージあたりのブロック数の上限
+pub const MAX_BLOCKS: usize = 50;
+
+// ===== Markdown → Block Kit 変換 =====
+
+enum Chunk {
+    Heading(String),
+    Text(Vec<String>),
+    Code(String),
+    Divider,
+}
+
+/// MarkdownをSlack Block Kitのブロック列に変換する
+///
+/// 見出しは太字のsection、コードブロックと表は ``` で囲んだsection、水平線はdividerになる。
+/// 各sectionは `SECTION_TEXT_LIMIT` を超えないよう行単位で分割される。
+pub fn markdown_to_blocks(markdown: &str) -> Vec<Value> {
+    parse_chunks(markdown)
+        .into_iter()
+        .flat_map(|chunk| match chunk {
+            Chunk::Heading(text) => vec![section(&format!("*{}*", text))],
+            Chunk::Text(lines) => split_text(&lines.join("\n"), SECTION_TEXT_LIMIT)
+                .iter()
+                .map(|text| section(text))
+                .collect(),
+            Chunk::Code(code) => split_text(&escape(&code), SECTION_TEXT_LIMIT - 8)
+                .iter()
+                .map(|part| section(&format!("```\n{}\n```", part)))
+                .collect(),
+            Chunk::Divider => vec![json!({"type": "divider"})],
+        })
+        .collect()
+}
+
+fn section(text: &str) -> Value {
+    json!({
+        "type": "section",
+        "text": {
+            "type": "mrkdwn",
+            "text": text,
+        }
+    })
+}
+
+fn parse_chunks(markdown: &str) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut text: Vec<String> = Vec::new();
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut i = 0;
+
+    fn flush(chunks: &mut Vec<Chunk>, text: &mut Vec<String>) {
+        while text.last().is_some_and(|l| l.is_empty()) {
+            text.pop();
+        }
+        if !text.is_empty() {
+            chunks.push(Chunk::Text(std::mem::take(text)));
+        }
+    }
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+
+        // コードブロック
+        if let Some(fence) = code_fence(trimmed) {
+            flush(&mut chunks, &mut text);
+            let mut code = Vec::new();
+            i += 1;
+            while i < lines.len() && !lines[i].trim_start().starts_with(fence) {
+                code.push(lines[i]);
+                i += 1;
+            }
+            chunks.push(Chunk::Code(code.join("\n")));
+            i += 1;
+            continue;
+        }
+
+        // 見出し
+        if let Some(heading) = heading_text(trimmed) {
+            flush(&mut chunks, &mut text);
+            chunks.push(Chunk::Heading(inline_to_mrkdwn(&heading.replace("**", ""))));
+            i += 1;
+            continue;
+        }
+
+        // 水平線
+        if is_horizontal_rule(trimmed) {
+            flush(&mut chunks, &mut text);
+            chunks.push(Chunk::Divider);
+            i += 1;
+            continue;
+        }
+
+        // 表（ヘッダー行の次が区切り行の場合）
+        if trimmed.starts_with('|') && lines.get(i + 1).is_some_and(|next| is_table_separator(next)) {
+            flush(&mut chunks, &mut text);
+            let mut rows = Vec::new();
+            while i < lines.len() && lines[i].trim_start().starts_with('|') {
+                if !is_table_separator(lines[i]) {
+                    rows.push(table_cells(lines[i]));
+                }
+                i += 1;
+            }
+            chunks.push(Chunk::Code(render_table(&rows)));
+            continue;
+        }
+
+        // 空行は段落の区切りとして1行だけ残す
+        if trimmed.is_empty() {
+            if text.last().is_some_and(|l| !l.is_empty()) {
+                text.push(String::new());
+            }
+            i += 1;
+            continue;
+        }
+
+        text.push(convert_line(line));
+        i += 1;
+    }
+
+    flush(&mut chunks, &mut text);
+    chunks
+}
+
+fn code_fence(line: &str) -> Option<&'static str> {
+    if line.starts_with("```") {
+        Some("```")
+    } else if line.starts_with("~~~") {
+        Some("~~~")
+    } else {
+        None
+    }
+}
+
+fn heading_text(line: &str) -> Option<String> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim().to_string())
+}
+
+fn is_horizontal_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && (compact.chars().all(|c| c == '-')
+            || compact.chars().all(|c| c == '*')
+            || compact.chars().all(|c| c == '_'))
+}
+
+fn is_table_separator(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with('|')
+        && trimmed.contains('-')
+        && trimmed.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let inner = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let inner = inner.strip_suffix('|').unwrap_or(inner);
+    inner
+        .split('|')
+        .map(|cell| cell.trim().replace("**", "").replace('`', ""))
+        .collect()
+}
+
+/// 表を等幅で揃えたテキストにする
+fn render_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| {
+            rows.iter()
+                .filter_map(|r| r.get(c))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut lines = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = (0..columns)
+            .map(|c| {
+                let cell = row.get(c).map(|s| s.as_str()).unwrap_or("");
+                let pad = widths[c] - cell.chars().count();
+                format!("{}{}", cell, " ".repeat(pad))
+            })
+            .collect();
+        lines.push(cells.join(" | ").trim_end().to_string());
+        if i == 0 && rows.len() > 1 {
+            let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+            lines.push(rule.join("-+-"));
+        }
+    }
+    lines.join("\n")
+}
+
+/// 箇条書き・引用・通常の行を変換する
+fn convert_line(line: &str) -> String {
+    let indent = line.len() - line.trim_start().len();
+    let trimmed = line.trim_start();
+    let pad = "    ".repeat(indent / 2);
+
+    if let Some(quote) = trimmed.strip_prefix('>') {
+        return format!("> {}", inline_to_mrkdwn(quote.trim_start()));
+    }
+
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(item) = trimmed.strip_prefix(marker) {
+            let bullet = if indent >= 2 { "◦" } else { "•" };
+            let item = if let Some(rest) = item.strip_prefix("[ ] ") {
+                format!("☐ {}", inline_to_mrkdwn(rest))
+            } else if let Some(rest) = item.strip_prefix("[x] ").or_else(|| item.strip_prefix("[X] ")) {
+                format!("☑ {}", inline_to_mrkdwn(rest))
+            } else {
+                inline_to_mrkdwn(item)
+            };
+            return format!("{}{} {}", pad, bullet, item);
+        }
+    }
+
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(item) = trimmed[digits..].strip_prefix(". ") {
+            return format!("{}{}. {}", pad, &trimmed[..digits], inline_to_mrkdwn(item));
+        }
+    }
+
+    inline_to_mrkdwn(trimmed)
+}
+
+// ===== インライン要素 =====
+
+/// インラインのMarkdown記法をSlack mrkdwnに変換する
+///
+/// `**bold**` → `*bold*`、`*italic*` → `_italic_`、`~~strike~~` → `~strike~`、
+/// `[text](url)` → `<url|text>`。インラインコード内はエスケープのみ行う。
+pub fn inline_to_mrkdwn(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('`') {
+        let (before, after) = rest.split_at(start);
+        result.push_str(&convert_inline_segment(before));
+        match after[1..].find('`') {
+            Some(end) => {
+                result.push('`');
+                result.push_str(&escape(&after[1..=end]));
+                result.push('`');
+                rest = &after[end + 2..];
+            }
+            None => {
+                result.push_str(&escape(after));
+                rest = "";
+            }
+        }
+    }
+    result.push_str(&convert_inline_segment(rest));
+    result
+}
+
+fn convert_inline_segment(text: &str) -> String {
+    // リンクは強調の変換から保護する（URLの `__` や `*` を区切りとして扱わない）
+    let mut links = Vec::new();
+    let s = convert_links(&escape(text), &mut links);
+    restore_links(&convert_emphasis(&s), &links)
+}
+
+/// 太字・斜体・取り消し線を変換する
+fn convert_emphasis(text: &str) -> String {
+    // 太字を一時的なマーカーに置き換えてから斜体を変換する
+    const BOLD: &str = "\u{1}";
+    let s = replace_delimited(text, "**", BOLD, BOLD);
+    let s = replace_delimited(&s, "__", BOLD, BOLD);
+    let s = replace_delimited(&s, "*", "_", "_");
+    let s = replace_delimited(&s, "~~", "~", "~");
+    s.replace(BOLD, "*")
+}
+
+/// `convert_links` がリンクの位置に置くマーカー（`\u{2}<番号>\u{3}`）
+const LINK_START: char = '\u{2}';
+const LINK_END: char = '\u{3}';
+
+fn restore_links(text: &str, links: &[String]) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(LINK_START) {
+        let Some(end) = rest[start..].find(LINK_END).map(|i| start + i) else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        match rest[start + 1..end].parse::<usize>().ok().and_then(|i| links.get(i)) {
+            Some(link) => result.push_str(link),
+            None => result.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// mrkdwnの制御文字（`&` `<` `>`）をエスケープする
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// `[text](url)` と `![alt](url)` を `<url|text>` に変換し、`links` に入れてマーカーに置き換える
+fn convert_links(text: &str, links: &mut Vec<String>) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        let label_end = rest[open..].find("](").map(|i| open + i);
+        let url_end = label_end.and_then(|le| rest[le..].find(')').map(|i| le + i));
+        match (label_end, url_end) {
+            (Some(le), Some(ue)) => {
+                let is_image = open > 0 && rest[..open].ends_with('!');
+                let prefix_end = if is_image { open - 1 } else { open };
+                let label = &rest[open + 1..le];
+                let url = &rest[le + 2..ue];
+                result.push_str(&rest[..prefix_end]);
+                if url.contains(' ') || url.is_empty() {
+                    result.push_str(&rest[prefix_end..=ue]);
+                } else {
+                    result.push_str(&format!("{}{}{}", LINK_START, links.len(), LINK_END));
+                    links.push(format!("<{}|{}>", url, convert_emphasis(label)));
+                }
+                rest = &rest[ue + 1..];
+            }
+            _ => break,
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// `delim` で囲まれた部分を `open`/`close` で囲み直す（前後が空白の場合は対象外）
+fn replace_delimited(text: &str, delim: &str, open: &str, close: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(delim) {
+        let inner_start = start + delim.len();
+        let inner = &rest[inner_start..];
+        let valid_open = inner.chars().next().is_some_and(|c| !c.is_whitespace());
+        let end = if valid_open { inner.find(delim) } else { None };
+        match end {
+            Some(end) if end > 0 && !inner[..end].ends_with(char::is_whitespace) => {
+                result.push_str(&rest[..start]);
+                result.push_str(open);
+                result.push_str(&inner[..end]);
+                result.push_str(close);
+                rest = &inner[end + delim.len()..];
+            }
+            _ => {
+                result.push_str(&rest[..inner_start]);
+                rest = inner;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+// ===== 分割 =====
+
+/// テキストを行単位で `limit` 文字以下の塊に分割する（1行が長すぎる場合は文字単位で分割）
+pub fn split_text(text: &str, limit: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for line in text.split('\n') {
+        let line_len = line.chars().count();
+        let needed = if current.is_empty() { line_len } else { line_len + 1 };
+
+        if current_len + needed > limit && !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+
+        if line_len > limit {
+            let chars: Vec<char> = line.chars().collect();
+            for piece in chars.chunks(limit) {
+                if !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+                current = piece.iter().collect();
+                current_len = piece.len();
+            }
+            continue;
+        }
+
+        if !current.is_empty() {
+            current.push('\n');
+            current_len += 1;
+        }
+        current.push_str(line);
+        current_len += line_len;
+    }
+
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
@@ -0,0 +1,279 @@
+use crate::text::truncate_chars;
+use crate::{last_assistant_message, session_file_name, t, tf, user_prompt_text, TranscriptMessage};
+use chrono::DateTime;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Slackに載せる最終出力の要約の上限文字数
+pub const SUMMARY_LIMIT: usize = 1200;
+
+/// `subagent_type` が省略された場合のサブエージェント
+const DEFAULT_SUBAGENT_TYPE: &str = "general-purpose";
+
+// ===== 設定 =====
+
+/// `claude-hooks.json` の `subagents`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct SubagentConfig {
+    /// `agents` に無いサブエージェントを通知するか
+    pub enabled: bool,
+    /// サブエージェントの種類（`subagent_type`）ごとの有効・無効
+    pub agents: HashMap<String, bool>,
+    /// Botモードで、セッションの最初のメッセージのスレッドに返信する
+    pub thread: bool,
+}
+
+impl Default for SubagentConfig {
+    fn default() -> SubagentConfig {
+        SubagentConfig {
+            enabled: true,
+            agents: HashMap::new(),
+            thread: false,
+        }
+    }
+}
+
+impl SubagentConfig {
+    pub fn is_enabled(&self, subagent_type: &str) -> bool {
+        self.agents.get(subagent_type).copied().unwrap_or(self.enabled)
+    }
+}
+
+// ===== Task呼び出し =====
+
+/// 親セッションのTaskツールの呼び出し
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskCall {
+    pub subagent_type: String,
+    pub description: String,
+    pub prompt: String,
+}
+
+impl TaskCall {
+    /// Taskツールの `tool_input`
+    pub fn from_input(tool_input: &Value) -> TaskCall {
+        let field = |name: &str| tool_input.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let subagent_type = field("subagent_type");
+        TaskCall {
+            subagent_type: if subagent_type.is_empty() {
+                DEFAULT_SUBAGENT_TYPE.to_string()
+            } else {
+                subagent_type
+            },
+            description: field("description"),
+            prompt: field("prompt"),
+        }
+    }
+}
+
+/// 親セッションのトランスクリプトから、まだ結果（tool_result）の無いTask呼び出しを古い順に返す
+pub fn pending_tasks(transcript: &str) -> Vec<TaskCall> {
+    let mut calls: Vec<(String, TaskCall)> = Vec::new();
+    let mut finished: HashSet<String> = HashSet::new();
+
+    for entry in transcript.lines().filter_map(|line| serde_json::from_str::<Value>(line).ok()) {
+        let Some(content) = entry.pointer("/message/content").and_then(|c| c.as_array()) else {
+            continue;
+        };
+        for item in content {
+            match item.get("type").and_then(|v| v.as_str()) {
+                Some("tool_use") if item.get("name").and_then(|v| v.as_str()) == Some("Task") => {
+                    let id = item.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+                    let input = item.get("input").cloned().unwrap_or(Value::Null);
+                    calls.push((id.to_string(), TaskCall::from_input(&input)));
+                }
+                Some("tool_result") => {
+                    if let Some(id) = item.get("tool_use_id").and_then(|v| v.as_str()) {
+                        finished.insert(id.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    calls
+        .into_iter()
+        .filter(|(id, _)| !finished.contains(id))
+        .map(|(_, call)| call)
+        .collect()
+}
+
+/// トランスクリプトの最初と最後の `timestamp` の間隔（ミリ秒）
+pub fn transcript_duration_ms(transcript: &str) -> Option<u64> {
+    let timestamps: Vec<_> = transcript
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|entry| DateTime::parse_from_rfc3339(entry.get("timestamp")?.as_str()?).ok())
+        .collect();
+    let first = timestamps.iter().min()?;
+    let last = timestamps.iter().max()?;
+    u64::try_from((*last - *first).num_milliseconds()).ok()
+}
+
+/// トランスクリプトの最初のユーザーメッセージ（サブエージェントに渡されたプロンプト）
+fn first_prompt(transcript: &str) -> Option<String> {
+    transcript
+        .lines()
+        .filter_map(|line| serde_json::from_str::<TranscriptMessage>(line).ok())
+        .find_map(|msg| user_prompt_text(&msg))
+}
+
+// ===== 報告内容 =====
+
+/// 終了したサブエージェント1回分の報告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubagentReport {
+    pub agent_id: Option<String>,
+    pub subagent_type: String,
+    pub description: String,
+    pub duration_ms: Option<u64>,
+    pub tool_uses: Option<u64>,
+    pub tokens: Option<u64>,
+    /// サブエージェントの最終出力
+    pub output: String,
+}
+
+impl SubagentReport {
+    /// PostToolUse(Task) の `tool_input` と `tool_response` から
+    pub fn from_tool_use(tool_input: &Value, tool_response: &Value) -> SubagentReport {
+        let call = TaskCall::from_input(tool_input);
+        let number = |name: &str| tool_response.get(name).and_then(|v| v.as_u64());
+        SubagentReport {
+            agent_id: tool_response.get("agentId").and_then(|v| v.as_str()).map(str::to_string),
+            subagent_type: call.subagent_type,
+            description: call.description,
+            duration_ms: number("totalDurationMs"),
+            tool_uses: number("totalToolUseCount"),
+            tokens: number("totalTokens"),
+            output: response_text(tool_response),
+        }
+    }
+
+    /// SubagentStop: サブエージェントのトランスクリプトと、Task呼び出しを探す親のトランスクリプトから
+    ///
+    /// 並列に実行中のTaskが複数ある場合はプロンプトが一致するものを、無ければ最後の呼び出しを使う。
+    pub fn from_transcripts(agent_id: Option<&str>, agent_transcript: &str, parent_transcript: Option<&str>) -> SubagentReport {
+        let pending = parent_transcript.map(pending_tasks).unwrap_or_default();
+        let prompt = first_prompt(agent_transcript);
+        let call = pending
+            .iter()
+            .find(|call| Some(&call.prompt) == prompt.as_ref())
+            .or(pending.last())
+            .cloned()
+            .unwrap_or_else(|| TaskCall::from_input(&Value::Null));
+        SubagentReport {
+            agent_id: agent_id.map(str::to_string),
+            subagent_type: call.subagent_type,
+            description: call.description,
+            duration_ms: transcript_duration_ms(agent_transcript),
+            tool_uses: None,
+            tokens: None,
+            output: last_assistant_message(agent_transcript),
+        }
+    }
+
+    /// 所要時間と使用量（`2m 05s · 12 tool uses · 34,567 tokens`）
+    pub fn stats_text(&self) -> String {
+        let mut parts = vec![self.duration_ms.map(format_duration).unwrap_or_else(|| t("fallback.not_available").to_string())];
+        if let Some(count) = self.tool_uses {
+            parts.push(tf("subagent.tool_uses", &[("count", &count.to_string())]));
+        }
+        if let Some(tokens) = self.tokens {
+            parts.push(tf("subagent.tokens", &[("count", &format_count(tokens))]));
+        }
+        parts.join(" · ")
+    }
+
+    /// 最終出力の要約（先頭 `SUMMARY_LIMIT` 文字）
+    pub fn summary(&self) -> String {
+        let output = self.output.trim();
+        if output.is_empty() {
+            return t("fallback.subagent_output").to_string();
+        }
+        truncate_chars(output, SUMMARY_LIMIT)
+    }
+}
+
+/// Taskの `tool_response`（`content` のtextブロック、または文字列）の本文
+fn response_text(tool_response: &Value) -> String {
+    if let Some(text) = tool_response.as_str() {
+        return text.to_string();
+    }
+    let content = tool_response.get("content").unwrap_or(tool_response);
+    if let Some(text) = content.as_str() {
+        return text.to_string();
+    }
+    content
+        .as_array()
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter(|b| b.get("type").and_then(|v| v.as_str()) == Some("text"))
+                .filter_map(|b| b.get("text").and_then(|v| v.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// `45s` / `2m 05s` / `1h 02m`
+pub fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// 3桁区切りの数値
+fn format_count(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// ===== 重複の防止 =====
+
+/// セッションで報告済みのサブエージェントID（1行に1つ）を記録するファイル
+///
+/// SubagentStopとPostToolUse(Task)の両方を登録しても、同じサブエージェントは1度だけ報告する。
+/// 記録するのはSlackに送信できた後（失敗した場合は次のイベントで再送する）。
+pub fn reported_path(home: &Path, session_id: &str) -> PathBuf {
+    home.join(".claude/subagent-reports")
+        .join(format!("{}.log", session_file_name(session_id)))
+}
+
+pub fn is_reported(log: &str, agent_id: &str) -> bool {
+    log.lines().any(|line| line.trim() == agent_id)
+}
+
+/// `settings.json` のPostToolUseに、Taskに一致する `subagent-slack` が登録されているか
+///
+/// 登録されている場合、SubagentStopでは報告せず、使用量も分かるPostToolUse(Task)の報告を使う。
+/// matcherは空・`*`、または `|` 区切りの `Task` を含むものを一致とみなす。
+pub fn reports_on_task(settings: &Value) -> bool {
+    let Some(groups) = settings.pointer("/hooks/PostToolUse").and_then(|v| v.as_array()) else {
+        return false;
+    };
+    let is_subagent_slack = |hook: &Value| {
+        let command = hook.get("command").and_then(|v| v.as_str()).unwrap_or_default();
+        command.split_whitespace().next().is_some_and(|program| program.ends_with("subagent-slack"))
+    };
+    groups.iter().any(|group| {
+        let matcher = group.get("matcher").and_then(|v| v.as_str()).unwrap_or_default();
+        let matches_task = matcher.is_empty() || matcher == "*" || matcher.split('|').any(|m| m.trim() == "Task");
+        let hooks = group.get("hooks").and_then(|v| v.as_array());
+        matches_task && hooks.is_some_and(|hooks| hooks.iter().any(is_subagent_slack))
+    })
+}
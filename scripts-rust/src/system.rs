@@ -0,0 +1,332 @@
+use chrono::{DateTime, FixedOffset, Local};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+// ===== 型定義 =====
+
+/// 外部コマンドの実行結果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ProcessOutput {
+    /// 正常終了した出力
+    pub fn ok(stdout: &str) -> ProcessOutput {
+        ProcessOutput {
+            success: true,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+        }
+    }
+
+    /// 異常終了した出力
+    pub fn failed() -> ProcessOutput {
+        ProcessOutput::default()
+    }
+}
+
+/// HTTP POSTの本文
+#[derive(Debug, Clone, PartialEq)]
+pub enum HttpBody {
+    Json(Value),
+    Form(Vec<(String, String)>),
+    Bytes(Vec<u8>),
+}
+
+/// HTTP POSTのリクエスト
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: HttpBody,
+}
+
+impl HttpRequest {
+    pub fn new(url: &str, body: HttpBody) -> HttpRequest {
+        HttpRequest {
+            url: url.to_string(),
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> HttpRequest {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+// ===== システム =====
+
+/// 環境変数・外部コマンド・ファイル・時刻・HTTPへのアクセス
+///
+/// 検出・通知・トランスクリプトの処理はこのトレイトを経由し、テストでは `FakeSystem` に差し替える。
+pub trait System {
+    /// 環境変数（設定されていれば空文字列でも `Some`）
+    fn env_var(&self, key: &str) -> Option<String>;
+
+    fn home_dir(&self) -> Option<PathBuf> {
+        self.env_var("HOME").map(PathBuf::from)
+    }
+
+    fn now(&self) -> DateTime<FixedOffset>;
+
+    /// 外部コマンドを実行して終了を待つ
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<ProcessOutput>;
+    /// 外部コマンドを終了を待たずにバックグラウンドで実行する（標準入出力は閉じる）
+    fn spawn(&self, program: &str, args: &[&str]) -> io::Result<()>;
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    /// ディレクトリ直下のエントリのパス
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+    fn modified(&self, path: &Path) -> io::Result<SystemTime>;
+    /// ファイルを書き込む（親ディレクトリが無ければ作る）
+    fn write_file(&self, path: &Path, content: &str) -> io::Result<()>;
+    /// ファイルに追記する（親ディレクトリが無ければ作る）
+    fn append_file(&self, path: &Path, content: &str) -> io::Result<()>;
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// POSTしてレスポンスの本文を返す（2xx以外はエラー）
+    fn http_post(&self, request: &HttpRequest) -> Result<String, String>;
+}
+
+/// 実際のOS
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealSystem;
+
+impl System for RealSystem {
+    fn env_var(&self, key: &str) -> Option<String> {
+        env::var(key).ok()
+    }
+
+    fn now(&self) -> DateTime<FixedOffset> {
+        Local::now().fixed_offset()
+    }
+
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<ProcessOutput> {
+        let output = Command::new(program).args(args).output()?;
+        Ok(ProcessOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+
+    fn spawn(&self, program: &str, args: &[&str]) -> io::Result<()> {
+        Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map(|_| ())
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(path)?.map(|entry| entry.map(|e| e.path())).collect()
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        fs::metadata(path)?.modified()
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)
+    }
+
+    fn append_file(&self, path: &Path, content: &str) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(content.as_bytes())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn http_post(&self, request: &HttpRequest) -> Result<String, String> {
+        let mut call = ureq::post(&request.url);
+        for (name, value) in &request.headers {
+            call = call.set(name, value);
+        }
+        let response = match &request.body {
+            HttpBody::Json(json) => call.send_json(json.clone()),
+            HttpBody::Form(form) => {
+                let pairs: Vec<(&str, &str)> = form.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+                call.send_form(&pairs)
+            }
+            HttpBody::Bytes(bytes) => call.send_bytes(bytes),
+        };
+        response
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())
+    }
+}
+
+// ===== テスト用のシステム =====
+
+/// メモリ上の環境変数・ファイルと、登録した外部コマンド・HTTPレスポンスで動くシステム
+///
+/// 実行したコマンドと送信したHTTPリクエストは記録され、`commands()` / `requests()` で確認できる。
+#[derive(Debug, Default)]
+pub struct FakeSystem {
+    pub env: HashMap<String, String>,
+    pub now: DateTime<FixedOffset>,
+    /// コマンドライン（`ps -p 42`）またはプログラム名（`ps`）ごとの実行結果
+    pub processes: HashMap<String, ProcessOutput>,
+    files: RefCell<BTreeMap<PathBuf, (String, SystemTime)>>,
+    commands: RefCell<Vec<Vec<String>>>,
+    http_responses: RefCell<VecDeque<Result<String, String>>>,
+    requests: RefCell<Vec<HttpRequest>>,
+}
+
+impl FakeSystem {
+    pub fn new() -> FakeSystem {
+        FakeSystem::default()
+    }
+
+    pub fn with_env(mut self, key: &str, value: &str) -> FakeSystem {
+        self.env.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_file(self, path: impl Into<PathBuf>, content: &str) -> FakeSystem {
+        self.with_file_modified(path, content, SystemTime::UNIX_EPOCH)
+    }
+
+    pub fn with_file_modified(self, path: impl Into<PathBuf>, content: &str, modified: SystemTime) -> FakeSystem {
+        self.files.borrow_mut().insert(path.into(), (content.to_string(), modified));
+        self
+    }
+
+    pub fn with_process(mut self, command: &str, output: ProcessOutput) -> FakeSystem {
+        self.processes.insert(command.to_string(), output);
+        self
+    }
+
+    /// 次のHTTP POSTのレスポンス（登録が無い場合は空の本文）
+    pub fn push_http_response(&self, response: Result<&str, &str>) {
+        self.http_responses
+            .borrow_mut()
+            .push_back(response.map(str::to_string).map_err(str::to_string));
+    }
+
+    /// 実行したコマンド（プログラム名と引数）
+    pub fn commands(&self) -> Vec<Vec<String>> {
+        self.commands.borrow().clone()
+    }
+
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.borrow().clone()
+    }
+
+    pub fn file(&self, path: impl AsRef<Path>) -> Option<String> {
+        self.files.borrow().get(path.as_ref()).map(|(content, _)| content.clone())
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, path.display().to_string())
+    }
+}
+
+impl System for FakeSystem {
+    fn env_var(&self, key: &str) -> Option<String> {
+        self.env.get(key).cloned()
+    }
+
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.now
+    }
+
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<ProcessOutput> {
+        let mut command = vec![program.to_string()];
+        command.extend(args.iter().map(|a| a.to_string()));
+        let line = command.join(" ");
+        self.commands.borrow_mut().push(command);
+        self.processes
+            .get(&line)
+            .or_else(|| self.processes.get(program))
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{}: command not found", program)))
+    }
+
+    fn spawn(&self, program: &str, args: &[&str]) -> io::Result<()> {
+        let mut command = vec![program.to_string()];
+        command.extend(args.iter().map(|a| a.to_string()));
+        self.commands.borrow_mut().push(command);
+        Ok(())
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.file(path).ok_or_else(|| FakeSystem::not_found(path))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        // 直下のファイルと、より深いファイルを含むサブディレクトリ
+        let entries: BTreeSet<PathBuf> = self
+            .files
+            .borrow()
+            .keys()
+            .filter_map(|p| p.strip_prefix(path).ok()?.components().next())
+            .map(|first| path.join(first))
+            .collect();
+        if entries.is_empty() {
+            return Err(FakeSystem::not_found(path));
+        }
+        Ok(entries.into_iter().collect())
+    }
+
+    fn modified(&self, path: &Path) -> io::Result<SystemTime> {
+        self.files
+            .borrow()
+            .get(path)
+            .map(|(_, modified)| *modified)
+            .ok_or_else(|| FakeSystem::not_found(path))
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> io::Result<()> {
+        let modified = SystemTime::from(self.now);
+        self.files
+            .borrow_mut()
+            .insert(path.to_path_buf(), (content.to_string(), modified));
+        Ok(())
+    }
+
+    fn append_file(&self, path: &Path, content: &str) -> io::Result<()> {
+        let existing = self.file(path).unwrap_or_default();
+        self.write_file(path, &(existing + content))
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.files
+            .borrow_mut()
+            .remove(path)
+            .map(|_| ())
+            .ok_or_else(|| FakeSystem::not_found(path))
+    }
+
+    fn http_post(&self, request: &HttpRequest) -> Result<String, String> {
+        self.requests.borrow_mut().push(request.clone());
+        self.http_responses.borrow_mut().pop_front().unwrap_or(Ok(String::new()))
+    }
+}
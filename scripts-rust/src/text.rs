@@ -0,0 +1,95 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// 省略記号
+pub const ELLIPSIS: &str = "...";
+
+// ===== 計測 =====
+
+/// 東アジアの全角文字を2として数えた表示幅
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// 書記素クラスタ（見た目の1文字）の数
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+// ===== 切り詰め =====
+
+/// 書記素クラスタ数が `max` を超える場合、省略記号を含めて `max` 以内に切り詰める
+pub fn truncate_chars(text: &str, max: usize) -> String {
+    if grapheme_count(text) <= max {
+        return text.to_string();
+    }
+    let keep = max.saturating_sub(grapheme_count(ELLIPSIS));
+    let head: String = text.graphemes(true).take(keep).collect();
+    format!("{}{}", head, ellipsis_within(max))
+}
+
+/// 表示幅が `max_width` を超える場合、省略記号を含めて `max_width` 以内に切り詰める
+pub fn truncate_width(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    let budget = max_width.saturating_sub(display_width(ELLIPSIS));
+    format!("{}{}", take_width(text.graphemes(true), budget).concat(), ellipsis_within(max_width))
+}
+
+/// 中央を省略して先頭と末尾を残す（コマンドやパス向け）
+pub fn truncate_middle(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_string();
+    }
+    let budget = max_width.saturating_sub(display_width(ELLIPSIS));
+    if budget == 0 {
+        return ellipsis_within(max_width).to_string();
+    }
+    let head_budget = budget.div_ceil(2);
+    let tail_budget = budget - head_budget;
+
+    let head = take_width(text.graphemes(true), head_budget).concat();
+    let mut tail = take_width(text.graphemes(true).rev(), tail_budget);
+    tail.reverse();
+    format!("{}{}{}", head, ELLIPSIS, tail.concat())
+}
+
+/// パスを短縮する（可能な限りファイル名を残し、ディレクトリ部分を中央省略する）
+pub fn shorten_path(path: &str, max_width: usize) -> String {
+    if display_width(path) <= max_width {
+        return path.to_string();
+    }
+    let (dir, file) = match path.rfind('/') {
+        Some(i) => (&path[..=i], &path[i + 1..]),
+        None => return truncate_middle(path, max_width),
+    };
+    let file_width = display_width(file);
+    let ellipsis_width = display_width(ELLIPSIS);
+    if file_width + ellipsis_width + 1 >= max_width {
+        return truncate_middle(path, max_width);
+    }
+    let dir_budget = max_width - file_width - ellipsis_width - 1;
+    let head = take_width(dir.graphemes(true), dir_budget).concat();
+    format!("{}{}/{}", head, ELLIPSIS, file)
+}
+
+/// 表示幅が `budget` に収まるまで書記素クラスタを集める
+fn take_width<'a>(graphemes: impl Iterator<Item = &'a str>, budget: usize) -> Vec<&'a str> {
+    let mut result = Vec::new();
+    let mut width = 0;
+    for g in graphemes {
+        let w = display_width(g);
+        if width + w > budget {
+            break;
+        }
+        width += w;
+        result.push(g);
+    }
+    result
+}
+
+/// 上限が省略記号より短い場合でも上限を超えない省略記号
+fn ellipsis_within(max: usize) -> &'static str {
+    &ELLIPSIS[..ELLIPSIS.len().min(max)]
+}
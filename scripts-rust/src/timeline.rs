@@ -0,0 +1,274 @@
+use crate::mcp::McpServerRegistry;
+use crate::subagent::format_duration;
+use crate::text::truncate_chars;
+use crate::{session_file_name, t, tf, user_prompt_text, ToolSummary, TranscriptMessage};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// タイムラインに表示するプロンプト・プラン・応答の上限文字数
+const LABEL_LIMIT: usize = 100;
+
+/// 連続するツール呼び出しをこの件数を超えたらまとめる
+const TOOL_RUN_LIMIT: usize = 5;
+
+// ===== イベント =====
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// ユーザーのプロンプト
+    Prompt,
+    ToolCall,
+    /// 権限リクエスト（permission-notificationが記録する）
+    Permission,
+    /// ExitPlanModeで提示したプラン
+    Plan,
+    /// プロンプトに対する最後の応答
+    Completion,
+}
+
+impl EventKind {
+    fn icon(self) -> &'static str {
+        match self {
+            EventKind::Prompt => "💬 ",
+            // ツールの1行表示には絵文字が含まれる
+            EventKind::ToolCall => "",
+            EventKind::Permission => "🔐 ",
+            EventKind::Plan => "📋 ",
+            EventKind::Completion => "✅ ",
+        }
+    }
+}
+
+/// タイムラインの1イベント
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimelineEvent {
+    /// RFC 3339
+    pub timestamp: String,
+    pub kind: EventKind,
+    pub label: String,
+}
+
+impl TimelineEvent {
+    pub fn new(timestamp: DateTime<FixedOffset>, kind: EventKind, label: &str) -> TimelineEvent {
+        TimelineEvent {
+            timestamp: timestamp.to_rfc3339(),
+            kind,
+            label: label.to_string(),
+        }
+    }
+
+    pub fn time(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.timestamp).ok()
+    }
+}
+
+// ===== トランスクリプトから =====
+
+/// トランスクリプトのプロンプト・ツール呼び出し・プラン・応答（`timestamp` の無いエントリは除く）
+pub fn transcript_events(transcript: &str, cwd: &str, registry: &McpServerRegistry) -> Vec<TimelineEvent> {
+    let mut events: Vec<TimelineEvent> = Vec::new();
+    // 次のプロンプトまでの最後の応答
+    let mut last_reply: Option<TimelineEvent> = None;
+
+    for line in transcript.lines() {
+        let Ok(entry) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let Some(timestamp) = entry
+            .get("timestamp")
+            .and_then(|v| v.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        else {
+            continue;
+        };
+
+        if let Some(prompt) = serde_json::from_value::<TranscriptMessage>(entry.clone())
+            .ok()
+            .as_ref()
+            .and_then(user_prompt_text)
+        {
+            events.extend(last_reply.take());
+            events.push(TimelineEvent::new(timestamp, EventKind::Prompt, &short_label(&prompt)));
+            continue;
+        }
+
+        if entry.get("type").and_then(|v| v.as_str()) != Some("assistant") {
+            continue;
+        }
+        let content = entry.pointer("/message/content").cloned().unwrap_or(Value::Null);
+        for item in content.as_array().into_iter().flatten() {
+            match item.get("type").and_then(|v| v.as_str()) {
+                Some("tool_use") => {
+                    let name = item.get("name").and_then(|v| v.as_str()).unwrap_or_default();
+                    let input = item.get("input");
+                    let event = match input.and_then(|i| i.get("plan")).and_then(|p| p.as_str()) {
+                        Some(plan) if name == "ExitPlanMode" => {
+                            TimelineEvent::new(timestamp, EventKind::Plan, &plan_title(plan))
+                        }
+                        _ => TimelineEvent::new(
+                            timestamp,
+                            EventKind::ToolCall,
+                            &ToolSummary::build(name, input, cwd, registry).short_line(),
+                        ),
+                    };
+                    events.push(event);
+                }
+                Some("text") => {
+                    let text = item.get("text").and_then(|v| v.as_str()).unwrap_or_default();
+                    if !text.trim().is_empty() {
+                        last_reply = Some(TimelineEvent::new(timestamp, EventKind::Completion, &short_label(text)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(text) = content.as_str().filter(|text| !text.trim().is_empty()) {
+            last_reply = Some(TimelineEvent::new(timestamp, EventKind::Completion, &short_label(text)));
+        }
+    }
+
+    events.extend(last_reply);
+    events
+}
+
+/// プランの見出し（無ければ最初の行）
+fn plan_title(plan: &str) -> String {
+    let lines = || plan.lines().map(str::trim).filter(|l| !l.is_empty());
+    let title = lines()
+        .find(|l| l.starts_with('#'))
+        .map(|l| l.trim_start_matches('#').trim())
+        .or_else(|| lines().next())
+        .unwrap_or_default();
+    short_label(title)
+}
+
+fn short_label(text: &str) -> String {
+    truncate_chars(&text.split_whitespace().collect::<Vec<_>>().join(" "), LABEL_LIMIT)
+}
+
+// ===== セッション中に記録したイベント =====
+
+/// `~/.claude/session-events/<session_id>.jsonl`（トランスクリプトに残らない権限リクエストを記録する）
+pub fn events_path(home: &Path, session_id: &str) -> PathBuf {
+    home.join(".claude/session-events")
+        .join(format!("{}.jsonl", session_file_name(session_id)))
+}
+
+/// `events_path` の内容（1行に1イベント）
+pub fn recorded_events(log: &str) -> Vec<TimelineEvent> {
+    log.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+// ===== タイムライン =====
+
+/// 終了したセッションのタイムライン（`~/.claude/session-timelines/` に保存する内容）
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Timeline {
+    pub session_id: String,
+    pub cwd: String,
+    /// SessionEndの `reason`（"clear" / "logout" / "prompt_input_exit" / "other"）
+    pub reason: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub events: Vec<TimelineEvent>,
+}
+
+impl Timeline {
+    /// イベントを時刻順に並べる（同じ時刻の場合は渡した順）
+    pub fn new(session_id: &str, cwd: &str, reason: Option<&str>, events: Vec<TimelineEvent>) -> Timeline {
+        let mut events: Vec<(DateTime<FixedOffset>, TimelineEvent)> =
+            events.into_iter().filter_map(|e| Some((e.time()?, e))).collect();
+        events.sort_by_key(|(time, _)| *time);
+        let duration_ms = match (events.first(), events.last()) {
+            (Some((first, _)), Some((last, _))) => u64::try_from((*last - *first).num_milliseconds()).ok(),
+            _ => None,
+        };
+        Timeline {
+            session_id: session_id.to_string(),
+            cwd: cwd.to_string(),
+            reason: reason.map(str::to_string),
+            duration_ms,
+            events: events.into_iter().map(|(_, e)| e).collect(),
+        }
+    }
+
+    pub fn count(&self, kind: EventKind) -> usize {
+        self.events.iter().filter(|e| e.kind == kind).count()
+    }
+
+    /// イベントの件数（`3 prompts · 12 tool calls · 1 permission request · 1 plan`）
+    pub fn stats_text(&self) -> String {
+        let counts = [
+            (EventKind::Prompt, "timeline.prompts"),
+            (EventKind::ToolCall, "timeline.tool_calls"),
+            (EventKind::Permission, "timeline.permissions"),
+            (EventKind::Plan, "timeline.plans"),
+        ];
+        counts
+            .iter()
+            .map(|(kind, key)| (self.count(*kind), *key))
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, key)| tf(key, &[("count", &count.to_string())]))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+
+    /// 時刻（`offset` のタイムゾーン）と前のイベントからの経過時間付きの一覧（Markdown）
+    ///
+    /// 連続するツール呼び出しが `TOOL_RUN_LIMIT` 件を超える場合は先頭だけを表示してまとめる。
+    pub fn markdown(&self, offset: FixedOffset) -> String {
+        let mut rows: Vec<(EventKind, String)> = Vec::new();
+        let mut previous: Option<DateTime<FixedOffset>> = None;
+        for event in &self.events {
+            let Some(time) = event.time() else {
+                continue;
+            };
+            let delta = previous.and_then(|p| u64::try_from((time - p).num_milliseconds()).ok());
+            previous = Some(time);
+
+            let label = format!("{}{}", event.kind.icon(), event.label);
+            let time = time.with_timezone(&offset).format("%H:%M:%S").to_string();
+            rows.push((
+                event.kind,
+                match delta {
+                    Some(ms) => tf(
+                        "timeline.line_delta",
+                        &[("time", &time), ("label", &label), ("delta", &format_duration(ms))],
+                    ),
+                    None => tf("timeline.line", &[("time", &time), ("label", &label)]),
+                },
+            ));
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+        for run in rows.chunk_by(|a, b| a.0 == EventKind::ToolCall && b.0 == EventKind::ToolCall) {
+            if run[0].0 == EventKind::ToolCall && run.len() > TOOL_RUN_LIMIT {
+                let shown = TOOL_RUN_LIMIT - 1;
+                lines.extend(run[..shown].iter().map(|(_, line)| line.clone()));
+                lines.push(tf("timeline.more_tools", &[("count", &(run.len() - shown).to_string())]));
+            } else {
+                lines.extend(run.iter().map(|(_, line)| line.clone()));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// `~/.claude/session-timelines/<session_id>.json`
+pub fn timeline_path(home: &Path, session_id: &str) -> PathBuf {
+    home.join(".claude/session-timelines")
+        .join(format!("{}.json", session_file_name(session_id)))
+}
+
+/// 終了理由の表示
+pub fn reason_text(reason: Option<&str>) -> String {
+    match reason {
+        Some("clear") => t("timeline.reason.clear").to_string(),
+        Some("logout") => t("timeline.reason.logout").to_string(),
+        Some("prompt_input_exit") => t("timeline.reason.exit").to_string(),
+        Some(other) => other.to_string(),
+        None => t("fallback.not_available").to_string(),
+    }
+}
@@ -0,0 +1,163 @@
+use crate::config::HooksConfig;
+use crate::mcp::McpServerRegistry;
+use crate::text;
+use crate::ToolSummary;
+use serde::Deserialize;
+use serde_json::Value;
+
+// ===== テンプレート定義 =====
+
+/// ツール名（またはglob）ごとの表示テンプレート
+///
+/// `templates` は先頭から順に試し、参照している `tool_input` の値が全て揃った最初のものを使う。
+/// どれも使えない場合は `label` だけを表示する。
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolTemplate {
+    /// ツール名。`*` と `?` のglobが使える（例: `mcp__pencil__*`）
+    pub tool: String,
+    #[serde(default = "default_emoji")]
+    pub emoji: String,
+    /// `{label}` に入る表示名（省略時はツール名）
+    #[serde(default)]
+    pub label: Option<String>,
+    /// `{/file_path|basename}` のように `tool_input` をJSON Pointerで参照するテンプレート
+    #[serde(default)]
+    pub templates: Vec<String>,
+}
+
+fn default_emoji() -> String {
+    "▶️".to_string()
+}
+
+impl ToolTemplate {
+    /// ツール呼び出しを1行で表示する
+    pub fn render(&self, tool_name: &str, tool_input: Option<&Value>) -> String {
+        let label = self.label.as_deref().unwrap_or(tool_name);
+        let body = self
+            .templates
+            .iter()
+            .find_map(|template| render_template(template, label, tool_name, tool_input))
+            .unwrap_or_else(|| label.to_string());
+        format!("{} {}", self.emoji, body)
+    }
+}
+
+// ===== レジストリ =====
+
+/// 設定ファイルのテンプレートを先頭から順に探す
+#[derive(Debug, Clone)]
+pub struct ToolDisplayRegistry {
+    templates: Vec<ToolTemplate>,
+    mcp_servers: McpServerRegistry,
+}
+
+impl ToolDisplayRegistry {
+    pub fn new(templates: &[ToolTemplate], mcp_servers: McpServerRegistry) -> ToolDisplayRegistry {
+        ToolDisplayRegistry {
+            templates: templates.to_vec(),
+            mcp_servers,
+        }
+    }
+
+    pub fn from_config(config: &HooksConfig) -> ToolDisplayRegistry {
+        ToolDisplayRegistry::new(&config.tool_display, McpServerRegistry::from_config(config))
+    }
+
+    /// ツール名に最初にマッチするテンプレート
+    pub fn find(&self, tool_name: &str) -> Option<&ToolTemplate> {
+        self.templates.iter().find(|t| glob_match(&t.tool, tool_name))
+    }
+
+    /// ツール呼び出しを1行で表示する（テンプレートが無いツールは `ToolSummary` の短い説明）
+    pub fn format(&self, tool_name: &str, tool_input: Option<&Value>) -> String {
+        match self.find(tool_name) {
+            Some(template) => template.render(tool_name, tool_input),
+            None => ToolSummary::build(tool_name, tool_input, "", &self.mcp_servers).short_line(),
+        }
+    }
+}
+
+// ===== テンプレート展開 =====
+
+/// テンプレートを展開する。参照した値が1つでも無ければ `None`
+///
+/// - `{label}` / `{tool}`: 表示名 / ツール名
+/// - `{/json/pointer}`: `tool_input` の値（文字列以外はJSONとして表示）
+/// - `{/pointer|filter}`: `basename`, `domain`, `middle:N`, `width:N` で整形
+pub fn render_template(template: &str, label: &str, tool_name: &str, tool_input: Option<&Value>) -> Option<String> {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let end = start + rest[start..].find('}')?;
+        let placeholder = &rest[start + 1..end];
+        output.push_str(&resolve_placeholder(placeholder, label, tool_name, tool_input)?);
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+    Some(output)
+}
+
+fn resolve_placeholder(placeholder: &str, label: &str, tool_name: &str, tool_input: Option<&Value>) -> Option<String> {
+    let mut parts = placeholder.split('|');
+    let source = parts.next().unwrap_or("");
+    let value = match source {
+        "label" => label.to_string(),
+        "tool" => tool_name.to_string(),
+        pointer if pointer.starts_with('/') => match tool_input?.pointer(pointer)? {
+            Value::Null => return None,
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        },
+        _ => return None,
+    };
+    Some(parts.fold(value, |acc, filter| apply_filter(&acc, filter)))
+}
+
+fn apply_filter(value: &str, filter: &str) -> String {
+    let (name, arg) = match filter.split_once(':') {
+        Some((name, arg)) => (name, arg.parse::<usize>().ok()),
+        None => (filter, None),
+    };
+    match (name, arg) {
+        ("basename", _) => value.split('/').next_back().unwrap_or(value).to_string(),
+        ("domain", _) => value
+            .split("://")
+            .nth(1)
+            .and_then(|s| s.split('/').next())
+            .unwrap_or(value)
+            .to_string(),
+        ("middle", Some(width)) => text::truncate_middle(value, width),
+        ("width", Some(width)) => text::truncate_width(value, width),
+        _ => value.to_string(),
+    }
+}
+
+/// `*`（任意の文字列）と `?`（任意の1文字）のみ対応したglob
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bn)) => {
+                    p = bp + 1;
+                    n = bn + 1;
+                    backtrack = Some((bp, bn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use claude_hooks::changes::session_edited_files;
+use claude_hooks::git::{parse_numstat, DiffStatEntry};
+use claude_hooks::handlers;
+use claude_hooks::i18n::Locale;
+use claude_hooks::sinks::FakeSinks;
+use claude_hooks::ChangeSummary;
+use common::{assistant, jsonl, tool_call, tool_result, user};
+use serde_json::{json, Value};
+
+fn tool_done() -> Value {
+    user(json!([tool_result("toolu_1", json!("ok"))]))
+}
+
+#[test]
+fn test_session_edited_files_since_last_prompt() {
+    Locale::init(Locale::En);
+    let transcript = jsonl(&[
+        user(json!("first task")),
+        tool_call("Write", json!({"file_path": "/repo/old.rs", "content": ""})),
+        tool_done(),
+        user(json!("second task")),
+        tool_call("Edit", json!({"file_path": "/repo/src/a.rs", "old_string": "a", "new_string": "b"})),
+        tool_done(),
+        tool_call("Read", json!({"file_path": "/repo/src/ignored.rs"})),
+        tool_done(),
+        tool_call("NotebookEdit", json!({"notebook_path": "/repo/nb.ipynb", "new_source": ""})),
+        tool_done(),
+        tool_call("Edit", json!({"file_path": "/repo/src/a.rs", "old_string": "b", "new_string": "c"})),
+        tool_done(),
+    ]);
+
+    let files = session_edited_files(&transcript);
+    assert_eq!(files, vec!["/repo/src/a.rs", "/repo/nb.ipynb"]);
+}
+
+#[test]
+fn test_from_parts_merges_session_and_diff() {
+    Locale::init(Locale::En);
+    let session = vec!["/repo/src/a.rs".to_string(), "/repo/new.txt".to_string()];
+    let diff = parse_numstat("3\t1\tsrc/a.rs\n-\t-\timage.png\n2\t0\tother.rs\n");
+    let summary = ChangeSummary::from_parts(&session, &diff, "/repo");
+
+    let paths: Vec<&str> = summary.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["src/a.rs", "new.txt", "image.png", "other.rs"]);
+    assert_eq!(summary.totals(), (5, 1));
+    assert_eq!(summary.headline(), "4 files (+5 -1)");
+    assert_eq!(
+        summary.desktop_line().as_deref(),
+        Some("📁 4 files (+5 -1): a.rs, new.txt, image.png +1 more")
+    );
+
+    let field = summary.slack_field();
+    assert!(field.contains("✏️ `src/a.rs` +3 -1"));
+    assert!(field.contains("✏️ `new.txt` (new)"));
+    assert!(field.contains("• `image.png` (binary)"));
+    assert!(field.contains("• `other.rs` +2 -0"));
+}
+
+#[test]
+fn test_empty_summary() {
+    Locale::init(Locale::En);
+    let summary = ChangeSummary::from_parts(&[], &[], "/repo");
+    assert!(summary.is_empty());
+    assert_eq!(summary.desktop_line(), None);
+    assert_eq!(summary.slack_field(), "No changes");
+}
+
+#[test]
+fn test_slack_field_limits_listed_files() {
+    Locale::init(Locale::En);
+    let diff: Vec<DiffStatEntry> = (0..13)
+        .map(|i| DiffStatEntry {
+            path: format!("f{}.rs", i),
+            added: Some(1),
+            removed: Some(0),
+        })
+        .collect();
+    let summary = ChangeSummary::from_parts(&[], &diff, "/repo");
+    let field = summary.slack_field();
+    assert!(field.contains("`f9.rs`"));
+    assert!(!field.contains("`f10.rs`"));
+    assert!(field.ends_with("… and 3 more"));
+}
+
+// ===== Stopの通知 =====
+
+const TRANSCRIPT: &str = "/Users/dev/.claude/projects/app/sess-1.jsonl";
+
+/// `root` のGitリポジトリ（`None` はGit管理外）で `lines` のトランスクリプトを持つセッション
+fn stop_sinks(root: Option<&str>, diff: &str, lines: &[Value]) -> FakeSinks {
+    let mut sinks = FakeSinks::new();
+    sinks.toplevel = root.map(str::to_string);
+    sinks.diff = parse_numstat(diff);
+    sinks.add_file(TRANSCRIPT, &jsonl(lines));
+    sinks
+}
+
+fn stop(sinks: &FakeSinks, cwd: &str) -> (String, String) {
+    let input = json!({"session_id": "sess-1", "transcript_path": TRANSCRIPT, "cwd": cwd, "hook_event_name": "Stop"});
+    handlers::task_complete_notification(&input.to_string(), sinks).unwrap();
+    let outputs = sinks.outputs();
+    let field = outputs.slack[0]
+        .fields
+        .iter()
+        .find(|(name, _)| name == "Changed Files")
+        .map(|(_, value)| value.clone())
+        .unwrap();
+    (outputs.desktop[0].message.clone(), field)
+}
+
+#[test]
+fn test_stop_summarizes_session_edits_and_git_diff() {
+    Locale::init(Locale::En);
+    let sinks = stop_sinks(
+        Some("/repo"),
+        "2\t1\tlib.rs\n2\t0\tsrc/new.rs\n",
+        &[
+            user(json!("edit lib")),
+            tool_call("Edit", json!({"file_path": "/repo/lib.rs", "old_string": "b", "new_string": "c\nd"})),
+            tool_done(),
+        ],
+    );
+    // サブディレクトリから実行してもリポジトリのルートからの相対パス
+    let (message, field) = stop(&sinks, "/repo/src");
+    assert!(message.ends_with("📁 2 files (+4 -1): lib.rs, new.rs"), "{}", message);
+    assert_eq!(field, "*2 files (+4 -1)*\n✏️ `lib.rs` +2 -1\n• `src/new.rs` +2 -0");
+}
+
+#[test]
+fn test_stop_outside_git_lists_session_edits() {
+    Locale::init(Locale::En);
+    let sinks = stop_sinks(
+        None,
+        "",
+        &[user(json!("write notes")), tool_call("Write", json!({"file_path": "/work/notes/todo.md", "content": "x"}))],
+    );
+    let (_, field) = stop(&sinks, "/work/notes");
+    assert_eq!(field, "*1 file (+0 -0)*\n✏️ `todo.md` (new)");
+}
+
+#[test]
+fn test_stop_without_changes() {
+    Locale::init(Locale::En);
+    let sinks = stop_sinks(Some("/repo"), "", &[user(json!("explain")), assistant(json!("It works like this."))]);
+    let (message, field) = stop(&sinks, "/repo");
+    assert_eq!(message, "It works like this.");
+    assert_eq!(field, "No changes");
+}
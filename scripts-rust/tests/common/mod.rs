@@ -0,0 +1,52 @@
+//! 統合テストで共有するヘルパー（時刻とトランスクリプトの行）
+//!
+//! 使うテストファイルで `mod common;` として読み込む。
+#![allow(dead_code)]
+
+use chrono::{DateTime, FixedOffset};
+use serde_json::{json, Value};
+
+// ===== 時刻 =====
+
+/// 2026-01-01 の `time`（`+09:00`）
+pub fn at(time: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(&format!("2026-01-01T{}+09:00", time)).unwrap()
+}
+
+// ===== トランスクリプト =====
+
+/// 1要素を1行としたJSONL
+pub fn jsonl(lines: &[Value]) -> String {
+    lines.iter().map(|l| l.to_string() + "\n").collect()
+}
+
+/// ユーザーのメッセージ（`content` は文字列、またはtool_resultなどのブロックの配列）
+pub fn user(content: Value) -> Value {
+    json!({"type": "user", "message": {"role": "user", "content": content}})
+}
+
+/// アシスタントのメッセージ（`content` は文字列、またはブロックの配列）
+pub fn assistant(content: Value) -> Value {
+    json!({"type": "assistant", "message": {"role": "assistant", "content": content}})
+}
+
+/// アシスタントのtool_useブロック
+pub fn tool_use(id: &str, name: &str, input: Value) -> Value {
+    json!({"type": "tool_use", "id": id, "name": name, "input": input})
+}
+
+/// tool_use（id は `toolu_1`）を1つだけ含むアシスタントのメッセージ
+pub fn tool_call(name: &str, input: Value) -> Value {
+    assistant(json!([tool_use("toolu_1", name, input)]))
+}
+
+/// ユーザーのtool_resultブロック
+pub fn tool_result(id: &str, content: Value) -> Value {
+    json!({"type": "tool_result", "tool_use_id": id, "content": content})
+}
+
+/// 行に `timestamp`（`at(time)`）を付ける
+pub fn timestamped(mut line: Value, time: &str) -> Value {
+    line["timestamp"] = json!(at(time).to_rfc3339());
+    line
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use claude_hooks::context::{
+    context_tokens, format_tokens, largest_tool_results, results_text, state_path, ContextConfig, ContextState,
+};
+use claude_hooks::i18n::Locale;
+use claude_hooks::mcp::McpServerRegistry;
+use common::{assistant, jsonl, tool_result, tool_use, user};
+use serde_json::{json, Value};
+use std::path::Path;
+
+fn usage(input: u64, cache_read: u64) -> Value {
+    let mut line = assistant(json!("ok"));
+    line["message"]["usage"] = json!({
+        "input_tokens": input,
+        "output_tokens": 100,
+        "cache_creation_input_tokens": 0,
+        "cache_read_input_tokens": cache_read,
+    });
+    line
+}
+
+// ===== 設定 =====
+
+#[test]
+fn test_percentage() {
+    let config = ContextConfig::default();
+    assert_eq!(config.percentage(0), 0);
+    assert_eq!(config.percentage(120_000), 75);
+    assert_eq!(config.percentage(500_000), 100);
+
+    let config = ContextConfig {
+        compaction_tokens: 0,
+        ..ContextConfig::default()
+    };
+    assert_eq!(config.percentage(1), 100);
+}
+
+#[test]
+fn test_crossed_threshold() {
+    let config = ContextConfig::default();
+    assert_eq!(config.crossed_threshold(None, 50), None);
+    assert_eq!(config.crossed_threshold(None, 75), Some(70));
+    assert_eq!(config.crossed_threshold(None, 95), Some(90));
+    assert_eq!(config.crossed_threshold(Some(70), 85), None);
+    assert_eq!(config.crossed_threshold(Some(70), 92), Some(90));
+    assert_eq!(config.crossed_threshold(Some(90), 99), None);
+}
+
+#[test]
+fn test_rearm_after_compaction() {
+    let config = ContextConfig::default();
+
+    let mut state = ContextState { notified: Some(90) };
+    state.rearm(30, &config);
+    assert_eq!(state.notified, None);
+
+    let mut state = ContextState { notified: Some(90) };
+    state.rearm(80, &config);
+    assert_eq!(state.notified, Some(70));
+
+    // 使用率が下がっていなければそのまま
+    let mut state = ContextState { notified: Some(70) };
+    state.rearm(85, &config);
+    assert_eq!(state.notified, Some(70));
+}
+
+#[test]
+fn test_state_parse_and_path() {
+    assert_eq!(ContextState::parse(r#"{"notified":70}"#).notified, Some(70));
+    assert_eq!(ContextState::parse("broken"), ContextState::default());
+    assert_eq!(
+        state_path(Path::new("/Users/dev"), "../sess"),
+        Path::new("/Users/dev/.claude/context-state/___sess.json")
+    );
+}
+
+// ===== トランスクリプトの解析 =====
+
+#[test]
+fn test_context_tokens_uses_last_usage() {
+    let transcript = jsonl(&[
+        usage(10, 1_000),
+        usage(20, 50_000),
+        user(json!("next")),
+    ]);
+    assert_eq!(context_tokens(&transcript), Some(50_120));
+    assert_eq!(context_tokens(""), None);
+}
+
+#[test]
+fn test_largest_tool_results() {
+    Locale::init(Locale::En);
+    let transcript = jsonl(&[
+        assistant(json!([
+            tool_use("t1", "Read", json!({"file_path": "/repo/src/small.rs"})),
+            tool_use("t2", "Read", json!({"file_path": "/repo/src/large.rs"})),
+            tool_use("t3", "Bash", json!({"command": "ls"})),
+        ])),
+        user(json!([
+            tool_result("t1", json!("x".repeat(400))),
+            tool_result("t2", json!("x".repeat(8_000))),
+            tool_result("t3", json!([{"type": "text", "text": "x".repeat(2_001)}])),
+            tool_result("unknown", json!("x")),
+        ])),
+    ]);
+    let results = largest_tool_results(&transcript, "/repo", &McpServerRegistry::default(), 2);
+    let tokens: Vec<u64> = results.iter().map(|r| r.tokens).collect();
+    assert_eq!(tokens, [2_000, 501]);
+    assert!(results[0].label.contains("large.rs"), "{}", results[0].label);
+    assert!(results_text(&results).starts_with("• "));
+}
+
+#[test]
+fn test_format_tokens() {
+    assert_eq!(format_tokens(999), "999");
+    assert_eq!(format_tokens(1_000), "1k");
+    assert_eq!(format_tokens(141_600), "142k");
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use claude_hooks::config::HooksConfig;
+use claude_hooks::dashboard::{
+    dashboard_html, discover_sessions, is_local_host, parse_request_line, session_state, sse_message, summarize_transcript,
+    SessionCache, SessionState, TranscriptSummary,
+};
+use claude_hooks::i18n::Locale;
+use claude_hooks::system::FakeSystem;
+use common::{assistant, at, jsonl, timestamped, tool_use, user};
+use serde_json::{json, Value};
+use std::time::SystemTime;
+
+const PROJECTS: &str = "/Users/dev/.claude/projects";
+
+/// `/Users/dev/src/app` で記録された行
+fn in_app(mut line: Value, time: &str, branch: Option<&str>) -> Value {
+    line = timestamped(line, time);
+    line["cwd"] = json!("/Users/dev/src/app");
+    if let Some(branch) = branch {
+        line["gitBranch"] = json!(branch);
+    }
+    line
+}
+
+fn prompt(text: &str, time: &str) -> Value {
+    in_app(user(json!(text)), time, Some("main"))
+}
+
+fn tool_call(time: &str) -> Value {
+    let mut line = assistant(json!([tool_use("t1", "Bash", json!({"command": "cargo test"}))]));
+    line["message"]["usage"] = json!({"input_tokens": 100, "cache_read_input_tokens": 79_900, "output_tokens": 0});
+    in_app(line, time, Some("feature/login"))
+}
+
+fn reply(time: &str) -> Value {
+    in_app(assistant(json!([{"type": "text", "text": "Done."}])), time, None)
+}
+
+// ===== トランスクリプト =====
+
+#[test]
+fn test_summarize_transcript() {
+    let summary = summarize_transcript(&jsonl(&[prompt("Fix the login bug", "09:00:00"), tool_call("09:00:05")]));
+    assert_eq!(summary.cwd.as_deref(), Some("/Users/dev/src/app"));
+    // 最後に記録されたブランチ
+    assert_eq!(summary.branch.as_deref(), Some("feature/login"));
+    assert_eq!(summary.last_prompt.as_deref(), Some("Fix the login bug"));
+    assert!(summary.working);
+    assert_eq!(summary.last_timestamp, Some(at("09:00:05")));
+
+    let cases = [
+        (vec![prompt("a", "09:00:00")], true),
+        (vec![prompt("a", "09:00:00"), tool_call("09:00:01")], true),
+        (vec![prompt("a", "09:00:00"), reply("09:00:01")], false),
+        (vec![tool_call("09:00:00"), prompt("[Request interrupted by user]", "09:00:01")], false),
+        (vec![], false),
+    ];
+    for (lines, working) in cases {
+        assert_eq!(summarize_transcript(&jsonl(&lines)).working, working, "{:?}", lines);
+    }
+}
+
+#[test]
+fn test_session_state() {
+    let working = TranscriptSummary {
+        working: true,
+        last_timestamp: Some(at("09:00:05")),
+        ..TranscriptSummary::default()
+    };
+    let done = TranscriptSummary {
+        working: false,
+        ..working.clone()
+    };
+    assert_eq!(session_state(&working, false, None), SessionState::Working);
+    assert_eq!(session_state(&done, false, None), SessionState::Done);
+    assert_eq!(session_state(&done, true, None), SessionState::Idle);
+    assert_eq!(session_state(&working, false, Some(at("09:00:06"))), SessionState::WaitingForPermission);
+    // 権限リクエストの後にトランスクリプトが進んでいれば処理中
+    assert_eq!(session_state(&working, false, Some(at("09:00:04"))), SessionState::Working);
+}
+
+// ===== セッションの検出 =====
+
+fn modified(time: &str) -> SystemTime {
+    SystemTime::from(at(time))
+}
+
+#[test]
+fn test_discover_sessions() {
+    let permission = json!({"timestamp": at("09:10:06").to_rfc3339(), "kind": "permission", "label": "Bash"});
+    let mut system = FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_file_modified(
+            format!("{}/-Users-dev-src-app/aaa.jsonl", PROJECTS),
+            &jsonl(&[prompt("Run the tests", "09:10:00"), tool_call("09:10:05")]),
+            modified("09:10:05"),
+        )
+        .with_file("/Users/dev/.claude/session-events/aaa.jsonl", &permission.to_string())
+        .with_file_modified(
+            format!("{}/-Users-dev-src-app/bbb.jsonl", PROJECTS),
+            &jsonl(&[prompt("Explain this", "09:20:00"), reply("09:20:10")]),
+            modified("09:20:10"),
+        )
+        .with_file("/Users/dev/.claude/idle-state/bbb.json", "{}")
+        // 期間外のセッション・トランスクリプト以外のファイルは表示しない
+        .with_file_modified(
+            format!("{}/-Users-dev-src-old/ccc.jsonl", PROJECTS),
+            &jsonl(&[prompt("Old", "09:00:00")]),
+            SystemTime::UNIX_EPOCH,
+        )
+        .with_file_modified(format!("{}/-Users-dev-src-app/notes.txt", PROJECTS), "", modified("09:30:00"));
+    system.now = at("10:00:00");
+
+    let sessions = discover_sessions(&system, &HooksConfig::default(), &SessionCache::new());
+    let summary: Vec<(&str, SessionState)> = sessions.iter().map(|s| (s.session_id.as_str(), s.state)).collect();
+    assert_eq!(summary, [("bbb", SessionState::Idle), ("aaa", SessionState::WaitingForPermission)]);
+
+    let session = &sessions[1];
+    assert_eq!(session.project, "app");
+    assert_eq!(session.cwd, "/Users/dev/src/app");
+    assert_eq!(session.branch.as_deref(), Some("feature/login"));
+    assert_eq!(session.last_prompt.as_deref(), Some("Run the tests"));
+    assert_eq!(session.context_tokens, Some(80_000));
+    assert_eq!(session.context_percent, Some(50));
+    assert_eq!(session.updated_at, "2026-01-01T09:10:05+09:00");
+    assert_eq!(sessions[0].context_tokens, None);
+}
+
+#[test]
+fn test_discover_sessions_rereads_only_changed_transcripts() {
+    let path = format!("{}/-Users-dev-src-app/aaa.jsonl", PROJECTS);
+    let mut system = FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_file_modified(&path, &jsonl(&[prompt("First", "09:10:00"), reply("09:10:10")]), modified("09:10:10"));
+    system.now = at("10:00:00");
+    let cache = SessionCache::new();
+    let last_prompt = |system: &FakeSystem| discover_sessions(system, &HooksConfig::default(), &cache)[0].last_prompt.clone();
+    assert_eq!(last_prompt(&system).as_deref(), Some("First"));
+
+    // 更新時刻が同じ間はキャッシュを使う
+    let system = system.with_file_modified(&path, &jsonl(&[prompt("Second", "09:10:00")]), modified("09:10:10"));
+    assert_eq!(last_prompt(&system).as_deref(), Some("First"));
+    // 入力待ちの状態ファイルは毎回読む
+    let system = system.with_file("/Users/dev/.claude/idle-state/aaa.json", "{}");
+    assert_eq!(discover_sessions(&system, &HooksConfig::default(), &cache)[0].state, SessionState::Idle);
+
+    let system = system.with_file_modified(&path, &jsonl(&[prompt("Second", "09:20:00")]), modified("09:20:00"));
+    assert_eq!(last_prompt(&system).as_deref(), Some("Second"));
+
+    // 期間外になったトランスクリプトは捨てる
+    let mut system = system;
+    system.now = at("23:59:59") + chrono::Duration::days(1);
+    assert!(discover_sessions(&system, &HooksConfig::default(), &cache).is_empty());
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_discover_sessions_without_projects() {
+    let system = FakeSystem::new().with_env("HOME", "/Users/dev");
+    assert!(discover_sessions(&system, &HooksConfig::default(), &SessionCache::new()).is_empty());
+}
+
+// ===== HTTP =====
+
+#[test]
+fn test_parse_request_line() {
+    assert_eq!(
+        parse_request_line("GET /events?last=1 HTTP/1.1\r\n"),
+        Some(("GET".to_string(), "/events".to_string()))
+    );
+    assert_eq!(parse_request_line("POST / HTTP/1.0"), Some(("POST".to_string(), "/".to_string())));
+    assert_eq!(parse_request_line("GET /"), None);
+    assert_eq!(parse_request_line(""), None);
+}
+
+#[test]
+fn test_is_local_host() {
+    for host in ["127.0.0.1:7878", "localhost:7878", "localhost", "[::1]:7878"] {
+        assert!(is_local_host(host), "{}", host);
+    }
+    for host in ["evil.example.com:7878", "127.0.0.1.evil.example.com", ""] {
+        assert!(!is_local_host(host), "{}", host);
+    }
+}
+
+#[test]
+fn test_sse_message() {
+    assert_eq!(sse_message("[]"), "data: []\n\n");
+    assert_eq!(sse_message("a\nb"), "data: a\ndata: b\n\n");
+}
+
+#[test]
+fn test_dashboard_html() {
+    Locale::init(Locale::En);
+    let html = dashboard_html();
+    assert!(html.contains("<title>Claude Code sessions</title>"));
+    assert!(html.contains(r#""waiting_for_permission":"Waiting for permission""#));
+    assert!(!html.contains("{{"));
+    // 外部のスクリプト・スタイルを読み込まない
+    assert!(!html.contains("src=\"http") && !html.contains("href=\"http"));
+}
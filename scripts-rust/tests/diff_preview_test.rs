@@ -0,0 +1,186 @@
+use claude_hooks::diff_preview::{apply_edits, bound, language_for, DiffPreview, MAX_PREVIEW_CHARS, MAX_PREVIEW_LINES};
+use claude_hooks::slack::{build_pages, SlackMessage, FIELD_TEXT_LIMIT};
+use claude_hooks::i18n::Locale;
+use claude_hooks::path_policy::{Access, PathRule};
+use claude_hooks::sinks::FakeSinks;
+use serde_json::json;
+
+const CWD: &str = "/work/app";
+const PATH: &str = "/work/app/src.rs";
+
+/// `src.rs` が置かれた環境
+fn setup(content: &str) -> FakeSinks {
+    let sinks = FakeSinks::new();
+    sinks.add_file(PATH, content);
+    sinks
+}
+
+#[test]
+fn test_edit_diff_against_file_on_disk() {
+    let content = (1..=20).map(|i| format!("line {}\n", i)).collect::<String>();
+    let sinks = setup(&content);
+
+    let input = json!({"file_path": PATH, "old_string": "line 10\n", "new_string": "line ten\n"});
+    let preview = DiffPreview::from_tool("Edit", &input, CWD, &sinks).unwrap();
+    assert_eq!(preview.path, "src.rs");
+    assert_eq!(preview.language, "Rust");
+    assert_eq!(preview.omitted_lines, 0);
+    assert_eq!(
+        preview.diff,
+        "--- a/src.rs\n+++ b/src.rs\n@@ -8,5 +8,5 @@\n line 8\n line 9\n-line 10\n+line ten\n line 11\n line 12"
+    );
+}
+
+#[test]
+fn test_edit_diff_falls_back_to_strings() {
+    let sinks = FakeSinks::new();
+    let input = json!({"file_path": "/nonexistent/app.ts", "old_string": "let a = 1;", "new_string": "const a = 1;"});
+    let preview = DiffPreview::from_tool("Edit", &input, "/nonexistent", &sinks).unwrap();
+    assert_eq!(preview.language, "TypeScript");
+    assert!(preview.diff.contains("-let a = 1;\n+const a = 1;"));
+
+    // old_stringがファイルに無い場合も文字列同士の差分
+    let sinks = setup("fn main() {}\n");
+    let input = json!({"file_path": PATH, "old_string": "missing", "new_string": "added"});
+    let preview = DiffPreview::from_tool("Edit", &input, CWD, &sinks).unwrap();
+    assert!(preview.diff.contains("-missing\n+added"));
+
+    // 変更が無ければ表示しない
+    let input = json!({"file_path": "/x/a.rs", "old_string": "same", "new_string": "same"});
+    assert_eq!(DiffPreview::from_tool("Edit", &input, "/x", &sinks), None);
+    assert_eq!(DiffPreview::from_tool("Read", &json!({"file_path": "/x/a.rs"}), "/x", &sinks), None);
+}
+
+#[test]
+fn test_multi_edit_applies_edits_in_order() {
+    let sinks = setup("a\nb\nc\nb\n");
+    let input = json!({
+        "file_path": PATH,
+        "edits": [
+            {"old_string": "a\n", "new_string": "A\n"},
+            {"old_string": "b", "new_string": "B", "replace_all": true}
+        ]
+    });
+    let preview = DiffPreview::from_tool("MultiEdit", &input, CWD, &sinks).unwrap();
+    assert!(preview.diff.ends_with("-a\n-b\n+A\n+B\n c\n-b\n+B"), "{}", preview.diff);
+
+    assert_eq!(apply_edits("x y x", &[("x", "z", false)]).as_deref(), Some("z y x"));
+    assert_eq!(apply_edits("x y x", &[("x", "z", true)]).as_deref(), Some("z y z"));
+    assert_eq!(apply_edits("x", &[("x", "y", false), ("x", "z", false)]), None);
+    assert_eq!(apply_edits("x", &[("", "y", false)]), None);
+}
+
+#[test]
+fn test_write_diff_against_current_contents() {
+    let sinks = setup("old\nkeep\n");
+    let input = json!({"file_path": PATH, "content": "new\nkeep\n"});
+    let preview = DiffPreview::from_tool("Write", &input, CWD, &sinks).unwrap();
+    assert!(preview.diff.contains("-old\n+new\n keep"));
+
+    // 新規ファイルは全行が追加
+    let input = json!({"file_path": "/work/app/config.yaml", "content": "a: 1\nb: 2\n"});
+    let preview = DiffPreview::from_tool("Write", &input, CWD, &sinks).unwrap();
+    assert_eq!(preview.language, "YAML");
+    assert!(preview.diff.ends_with("@@ -0,0 +1,2 @@\n+a: 1\n+b: 2"), "{}", preview.diff);
+}
+
+#[test]
+fn test_preview_is_bounded() {
+    let new: String = (0..500).map(|i| format!("+{}\n", i)).collect();
+    let input = json!({"file_path": "/nonexistent/big.txt", "content": new});
+    let preview = DiffPreview::from_tool("Write", &input, "/nonexistent", &FakeSinks::new()).unwrap();
+    assert_eq!(preview.diff.lines().count(), MAX_PREVIEW_LINES);
+    assert_eq!(preview.omitted_lines, 503 - MAX_PREVIEW_LINES);
+
+    assert_eq!(bound("a\nb\nc", 2, 100), ("a\nb".to_string(), 1));
+    assert_eq!(bound("aaaa\nbbbb\ncccc", 10, 10), ("aaaa\nbbbb".to_string(), 1));
+    // エスケープ後の長さで数える
+    assert_eq!(bound("<<\n>>", 10, 10), ("<<".to_string(), 1));
+}
+
+#[test]
+fn test_single_long_line_is_truncated() {
+    let line = format!("+{}", "<".repeat(MAX_PREVIEW_CHARS));
+    let (diff, omitted) = bound(&line, MAX_PREVIEW_LINES, MAX_PREVIEW_CHARS);
+    assert_eq!(omitted, 0);
+    assert!(diff.starts_with("+<<<") && diff.ends_with("..."), "{}", diff);
+    assert!(claude_hooks::slack_markdown::escape(&diff).chars().count() < MAX_PREVIEW_CHARS);
+
+    // 2行目以降の長い行は省略した行数に数える
+    let input = json!({"file_path": "/nonexistent/min.js", "content": "x".repeat(MAX_PREVIEW_CHARS * 2)});
+    let preview = DiffPreview::from_tool("Write", &input, "/nonexistent", &FakeSinks::new()).unwrap();
+    assert_eq!(preview.diff, "--- a/min.js\n+++ b/min.js\n@@ -0,0 +1 @@");
+    assert_eq!(preview.omitted_lines, 2);
+}
+
+#[test]
+fn test_env_edit_is_redacted_or_skipped() {
+    let env_path = "/work/app/.env";
+    let input = json!({"file_path": env_path, "old_string": "API_KEY=old\n", "new_string": "API_KEY=abcdef1234567890\n"});
+
+    // 拒否ルールが無ければシークレットを伏せて表示する
+    let sinks = FakeSinks::new();
+    sinks.add_file(env_path, "DEBUG=1\nAPI_KEY=old\n");
+    let preview = DiffPreview::from_tool("Edit", &input, CWD, &sinks).unwrap();
+    assert!(preview.diff.contains("+API_KEY=[REDACTED]"), "{}", preview.diff);
+    assert!(!preview.diff.contains("abcdef1234567890"));
+
+    // PathPolicyで拒否されたパスは表示しない
+    for access in [Access::Any, Access::Read, Access::Write] {
+        let mut sinks = FakeSinks::new();
+        sinks.add_file(env_path, "DEBUG=1\nAPI_KEY=old\n");
+        sinks.config.path_policy.deny.push(PathRule {
+            path: ".env".to_string(),
+            access,
+            reason: None,
+        });
+        assert_eq!(DiffPreview::from_tool("Edit", &input, CWD, &sinks), None, "{:?}", access);
+    }
+}
+
+#[test]
+fn test_bounded_preview_fits_in_one_slack_field() {
+    Locale::init(Locale::En);
+    let long_lines: String = (0..50).map(|i| format!("{} {}\n", i, "<&>".repeat(40))).collect();
+    let input = json!({"file_path": "/nonexistent/long_name.tsx", "content": long_lines});
+    let preview = DiffPreview::from_tool("Write", &input, "/nonexistent", &FakeSinks::new()).unwrap();
+    assert!(preview.omitted_lines > 0);
+
+    let (label, value) = (preview.slack_label(), preview.slack_value());
+    assert!(label.chars().count() + value.chars().count() + 3 <= FIELD_TEXT_LIMIT);
+    assert!(value.starts_with("```\n") && value.ends_with("\n```"), "{}", value);
+
+    // フィールドのまま送られ、本文ブロックに分割されない
+    let fields = [(label.as_str(), value.as_str())];
+    let message = SlackMessage {
+        title: "T",
+        fields: &fields,
+        body: Vec::new(),
+        button_url: None,
+        full_text: None,
+        thread: None,
+    };
+    let pages = build_pages(&message);
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0][1]["fields"][0]["text"], format!("*{}*\n{}", label, value));
+}
+
+#[test]
+fn test_slack_field() {
+    Locale::init(Locale::En);
+    let input = json!({"file_path": "/nonexistent/page.html", "old_string": "<b>", "new_string": "<i> & <u>"});
+    let preview = DiffPreview::from_tool("Edit", &input, "/nonexistent", &FakeSinks::new()).unwrap();
+    assert_eq!(preview.slack_label(), "Diff Preview (HTML)");
+    let value = preview.slack_value();
+    assert!(value.starts_with("```\n--- a/page.html"));
+    assert!(value.contains("-&lt;b&gt;\n+&lt;i&gt; &amp; &lt;u&gt;"));
+    assert!(value.ends_with("\n```"));
+
+    let many: String = (0..100).map(|i| format!("{}\n", i)).collect();
+    let input = json!({"file_path": "/nonexistent/a.txt", "content": many});
+    let value = DiffPreview::from_tool("Write", &input, "/nonexistent", &FakeSinks::new()).unwrap().slack_value();
+    assert!(value.ends_with("\n+56\n… 43 more lines\n```"), "{}", value);
+
+    assert_eq!(language_for("Makefile"), "Text");
+    assert_eq!(language_for("dir/App.TSX"), "TypeScript");
+}
@@ -0,0 +1,311 @@
+use claude_hooks::focus::{
+    button_url, decode_component, desktop_entry, encode_component, focus, register_desktop_handler, Focus, FocusTarget,
+};
+use claude_hooks::i18n::Locale;
+use claude_hooks::send_notification;
+use claude_hooks::slack::{self, SlackMessage};
+use claude_hooks::system::{FakeSystem, ProcessOutput};
+
+const VSCODE_LOCK: &str = r#"{"pid": 42, "workspaceFolders": ["/Users/dev/src/my app"], "ideName": "Visual Studio Code"}"#;
+
+/// 環境変数と検出する対象
+type DetectCase<'a> = (&'a [(&'a str, &'a str)], Option<FocusTarget>);
+
+fn focus_of(target: FocusTarget, app: Option<&str>) -> Focus {
+    Focus {
+        target,
+        app: app.map(str::to_string),
+    }
+}
+
+// ===== 検出 =====
+
+#[test]
+fn test_detect_table() {
+    let cases: &[DetectCase] = &[
+        (
+            &[("TMUX", "/private/tmp/tmux-501/default,1234,0"), ("TMUX_PANE", "%3"), ("ITERM_SESSION_ID", "w0t0p0:ABC")],
+            Some(FocusTarget::Tmux {
+                pane: "%3".to_string(),
+                socket: Some("/private/tmp/tmux-501/default".to_string()),
+            }),
+        ),
+        (&[("WEZTERM_PANE", "7")], Some(FocusTarget::WezTerm { pane: "7".to_string() })),
+        (
+            &[("KITTY_WINDOW_ID", "2"), ("KITTY_LISTEN_ON", "unix:/tmp/kitty")],
+            Some(FocusTarget::Kitty {
+                window: "2".to_string(),
+                listen_on: Some("unix:/tmp/kitty".to_string()),
+            }),
+        ),
+        (&[("ITERM_SESSION_ID", "w0t0p0:ABC-123")], Some(FocusTarget::ITerm2 { guid: "ABC-123".to_string() })),
+        (&[("ITERM_SESSION_ID", "w0t0p0")], None),
+        (&[("TMUX_PANE", "")], None),
+        (&[("TERM_PROGRAM", "Apple_Terminal")], None),
+        (&[], None),
+    ];
+    for (env, expected) in cases {
+        let system = env.iter().fold(FakeSystem::new(), |system, (k, v)| system.with_env(k, v));
+        assert_eq!(Focus::detect(&system).map(|f| f.target), *expected, "{:?}", env);
+    }
+}
+
+#[test]
+fn test_detect_app() {
+    let system = FakeSystem::new()
+        .with_env("TERM_PROGRAM", "WezTerm")
+        .with_env("WEZTERM_PANE", "1");
+    assert_eq!(Focus::detect(&system).unwrap().app.as_deref(), Some("com.github.wez.wezterm"));
+}
+
+#[test]
+fn test_detect_ide_workspace() {
+    let system = FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_env("TERM_PROGRAM", "vscode")
+        .with_file("/Users/dev/.claude/ide/42.lock", VSCODE_LOCK);
+    assert_eq!(
+        Focus::detect(&system).unwrap().target,
+        FocusTarget::Ide {
+            cli: "code".to_string(),
+            workspace: "/Users/dev/src/my app".to_string(),
+        }
+    );
+
+    // lockファイルが無い・CLIの分からないIDEは対象にしない
+    let system = FakeSystem::new().with_env("HOME", "/Users/dev").with_env("TERM_PROGRAM", "vscode");
+    assert_eq!(Focus::detect(&system), None);
+    let system = system.with_file(
+        "/Users/dev/.claude/ide/1.lock",
+        r#"{"pid": 1, "workspaceFolders": ["/a"], "ideName": "IntelliJ IDEA"}"#,
+    );
+    assert_eq!(Focus::detect(&system), None);
+}
+
+// ===== URL =====
+
+#[test]
+fn test_url_round_trip() {
+    let focuses = [
+        focus_of(
+            FocusTarget::Tmux {
+                pane: "%3".to_string(),
+                socket: Some("/tmp/tmux-501/default".to_string()),
+            },
+            Some("com.googlecode.iterm2"),
+        ),
+        focus_of(FocusTarget::Tmux { pane: "%0".to_string(), socket: None }, None),
+        focus_of(FocusTarget::Kitty { window: "2".to_string(), listen_on: None }, Some("net.kovidgoyal.kitty")),
+        focus_of(FocusTarget::WezTerm { pane: "7".to_string() }, None),
+        focus_of(
+            FocusTarget::Ide {
+                cli: "cursor".to_string(),
+                workspace: "/Users/dev/src/my app".to_string(),
+            },
+            None,
+        ),
+        focus_of(FocusTarget::ITerm2 { guid: "ABC-123".to_string() }, None),
+    ];
+    for focus in focuses {
+        assert_eq!(Focus::from_url(&focus.url()).unwrap(), focus, "{}", focus.url());
+    }
+}
+
+#[test]
+fn test_url_format() {
+    let tmux = focus_of(
+        FocusTarget::Tmux {
+            pane: "%3".to_string(),
+            socket: Some("/tmp/tmux-501/default".to_string()),
+        },
+        Some("com.googlecode.iterm2"),
+    );
+    assert_eq!(
+        tmux.url(),
+        "x-claude://focus?target=tmux&pane=%253&socket=%2Ftmp%2Ftmux-501%2Fdefault&app=com.googlecode.iterm2"
+    );
+    // iTerm2はiTerm2Switch.appのURL
+    let iterm2 = focus_of(FocusTarget::ITerm2 { guid: "ABC-123".to_string() }, Some("com.googlecode.iterm2"));
+    assert_eq!(iterm2.url(), "x-claude-iterm://switch?guid=ABC-123");
+}
+
+#[test]
+fn test_button_url_falls_back_to_iterm2_on_macos() {
+    let tmux_in_iterm2 = FakeSystem::new()
+        .with_env("TMUX_PANE", "%3")
+        .with_env("ITERM_SESSION_ID", "w0t0p0:ABC-123");
+    // macOSでは `x-claude://` を処理するアプリが無い
+    assert_eq!(button_url(&tmux_in_iterm2, true).as_deref(), Some("x-claude-iterm://switch?guid=ABC-123"));
+    assert_eq!(
+        button_url(&tmux_in_iterm2, false).as_deref(),
+        Some("x-claude://focus?target=tmux&pane=%253&app=com.googlecode.iterm2")
+    );
+
+    let wezterm = FakeSystem::new().with_env("WEZTERM_PANE", "7");
+    assert_eq!(button_url(&wezterm, true).as_deref(), Some("x-claude://focus?target=wezterm&pane=7"));
+    assert_eq!(button_url(&FakeSystem::new(), true), None);
+}
+
+#[test]
+fn test_from_url_errors() {
+    for url in [
+        "https://example.com",
+        "x-claude://focus?target=screen&pane=1",
+        "x-claude://focus?target=tmux",
+        "x-claude://focus?target=tmux&pane=%2",
+        "x-claude-iterm://switch?",
+        "x-claude-iterm://switch?guid=A%22%20then%0Ado%20shell%20script%20%22touch%20%2Ftmp%2Fx",
+        "x-claude://focus?target=iterm2&guid=A%27%3Btouch%20%2Ftmp%2Fx%3B%27",
+        "x-claude://focus?target=tmux&pane=%253%3Bid",
+        "x-claude://focus?target=tmux&pane=main",
+        "x-claude://focus?target=wezterm&pane=-1",
+        "x-claude://focus?target=kitty&window=2%20--to%20unix%3A%2Ftmp%2Fx",
+        "x-claude://focus?target=ide&cli=sh&workspace=%2Ftmp%2Fx.sh",
+        "x-claude://focus?target=ide&cli=%2Fbin%2Fsh&workspace=%2Fwork%2Fapp",
+        "x-claude://focus?target=ide&cli=code&workspace=--install-extension%3Dx",
+    ] {
+        assert!(Focus::from_url(url).is_err(), "{}", url);
+    }
+}
+
+#[test]
+fn test_percent_encoding() {
+    assert_eq!(encode_component("a b/é~"), "a%20b%2F%C3%A9~");
+    assert_eq!(decode_component("a%20b%2F%C3%A9~").unwrap(), "a b/é~");
+    assert_eq!(decode_component("a+b").unwrap(), "a b");
+    assert_eq!(decode_component("%zz"), None);
+    assert_eq!(decode_component("%FF"), None);
+}
+
+// ===== フォーカス =====
+
+#[test]
+fn test_commands() {
+    let tmux = focus_of(
+        FocusTarget::Tmux {
+            pane: "%3".to_string(),
+            socket: Some("/tmp/tmux".to_string()),
+        },
+        Some("com.googlecode.iterm2"),
+    );
+    assert_eq!(
+        tmux.commands(true),
+        [
+            vec!["open", "-b", "com.googlecode.iterm2"],
+            vec!["tmux", "-S", "/tmp/tmux", "select-window", "-t", "%3"],
+            vec!["tmux", "-S", "/tmp/tmux", "select-pane", "-t", "%3"],
+        ]
+    );
+    assert_eq!(tmux.commands(false).len(), 2);
+
+    let kitty = focus_of(
+        FocusTarget::Kitty {
+            window: "2".to_string(),
+            listen_on: Some("unix:/tmp/kitty".to_string()),
+        },
+        None,
+    );
+    assert_eq!(kitty.commands(true), [vec!["kitty", "@", "--to", "unix:/tmp/kitty", "focus-window", "--match", "id:2"]]);
+
+    let wezterm = focus_of(FocusTarget::WezTerm { pane: "7".to_string() }, None);
+    assert_eq!(wezterm.commands(false), [vec!["wezterm", "cli", "activate-pane", "--pane-id", "7"]]);
+
+    let ide = focus_of(
+        FocusTarget::Ide {
+            cli: "code".to_string(),
+            workspace: "/work/app".to_string(),
+        },
+        None,
+    );
+    assert_eq!(ide.commands(false), [vec!["code", "/work/app"]]);
+
+    let iterm2 = focus_of(FocusTarget::ITerm2 { guid: "ABC".to_string() }, None);
+    let commands = iterm2.commands(true);
+    assert_eq!(commands[0][..3], ["osascript", "-e", r#"tell application "iTerm2""#]);
+    assert!(commands[0].contains(&r#"if id of s is "ABC" then"#.to_string()));
+}
+
+#[test]
+fn test_focus_runs_commands() {
+    let system = FakeSystem::new().with_process("wezterm", ProcessOutput::ok(""));
+    focus(&system, "x-claude://focus?target=wezterm&pane=7&app=com.github.wez.wezterm", false).unwrap();
+    assert_eq!(system.commands(), [vec!["wezterm", "cli", "activate-pane", "--pane-id", "7"]]);
+
+    let system = FakeSystem::new().with_process(
+        "tmux",
+        ProcessOutput {
+            success: false,
+            stdout: String::new(),
+            stderr: "can't find pane: %9\n".to_string(),
+        },
+    );
+    let err = focus(&system, "x-claude://focus?target=tmux&pane=%259", false).unwrap_err();
+    assert_eq!(err, "tmux select-window -t %9 failed: can't find pane: %9");
+    // 最初に失敗したコマンドで止める
+    assert_eq!(system.commands().len(), 1);
+}
+
+#[test]
+fn test_notification_executes_focus() {
+    let system = FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_env("TMUX_PANE", "%3")
+        .with_env("ITERM_SESSION_ID", "w0t0p0:ABC")
+        .with_process("terminal-notifier", ProcessOutput::ok(""));
+    send_notification(&system, "T", "M", "S", "com.googlecode.iterm2", "Glass").unwrap();
+    let args = &system.commands()[0];
+    let execute = args.iter().position(|a| a == "-execute").expect("-execute");
+    assert_eq!(
+        args[execute + 1],
+        "'/Users/dev/.claude/bin/claude-hooks' focus 'x-claude://focus?target=tmux&pane=%253&app=com.googlecode.iterm2'"
+    );
+}
+
+#[test]
+fn test_slack_link_label() {
+    Locale::init(Locale::En);
+    let link = |url: &str| {
+        let message = SlackMessage {
+            title: "T",
+            fields: &[],
+            body: Vec::new(),
+            button_url: Some(url),
+            full_text: None,
+            thread: None,
+        };
+        slack::build_pages(&message)[0].last().unwrap()["text"]["text"].clone()
+    };
+    assert_eq!(link("x-claude://focus?target=wezterm&pane=1"), ":computer: <x-claude://focus?target=wezterm&pane=1|Open session>");
+    assert_eq!(link("x-claude-iterm://switch?guid=A"), ":computer: <x-claude-iterm://switch?guid=A|Open in iTerm2>");
+}
+
+// ===== Linuxの登録 =====
+
+#[test]
+fn test_desktop_entry() {
+    let entry = desktop_entry("/home/dev/.claude/bin/claude-hooks");
+    assert!(entry.contains("\nExec=\"/home/dev/.claude/bin/claude-hooks\" focus %u\n"));
+    assert!(entry.contains("\nMimeType=x-scheme-handler/x-claude;\n"));
+    assert!(desktop_entry("/a $b").contains(r#"Exec="/a \$b" focus %u"#));
+}
+
+#[test]
+fn test_register_desktop_handler() {
+    let system = FakeSystem::new()
+        .with_env("HOME", "/home/dev")
+        .with_process("xdg-mime", ProcessOutput::ok(""));
+    let path = register_desktop_handler(&system, "~/.claude/bin").unwrap();
+    assert_eq!(path.to_str().unwrap(), "/home/dev/.local/share/applications/claude-hooks-focus.desktop");
+    assert!(system.file(&path).unwrap().contains("Exec=\"/home/dev/.claude/bin/claude-hooks\" focus %u"));
+    assert_eq!(
+        system.commands(),
+        [
+            vec!["xdg-mime", "default", "claude-hooks-focus.desktop", "x-scheme-handler/x-claude"],
+            vec!["update-desktop-database", "/home/dev/.local/share/applications"],
+        ]
+    );
+
+    // xdg-mimeが無い場合はエラー
+    let system = FakeSystem::new().with_env("HOME", "/home/dev");
+    assert!(register_desktop_handler(&system, "~/.claude/bin").is_err());
+}
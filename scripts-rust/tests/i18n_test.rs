@@ -0,0 +1,169 @@
+use claude_hooks::i18n::{format_message, lookup, translate, Locale};
+use claude_hooks::system::FakeSystem;
+use claude_hooks::HooksConfig;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// メッセージキーの名前空間（ソース中の文字列リテラルからキーを拾うために使う）
+fn namespaces() -> BTreeSet<&'static str> {
+    Locale::En.catalog().iter().filter_map(|(k, _)| k.split_once('.').map(|(ns, _)| ns)).collect()
+}
+
+fn catalog_keys(locale: Locale) -> BTreeSet<&'static str> {
+    locale.catalog().iter().map(|(k, _)| *k).collect()
+}
+
+fn placeholders(template: &str) -> BTreeSet<String> {
+    template
+        .split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name.to_string()))
+        .collect()
+}
+
+/// `src/` 以下の文字列リテラルのうち、メッセージキーの形をしたものを集める
+fn keys_used_in_source() -> BTreeSet<String> {
+    fn walk(dir: &Path, namespaces: &BTreeSet<&str>, keys: &mut BTreeSet<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                walk(&path, namespaces, keys);
+            } else if path.extension().is_some_and(|e| e == "rs") && !path.ends_with("i18n.rs") {
+                let source = fs::read_to_string(&path).unwrap();
+                for ns in namespaces {
+                    let prefix = format!("\"{}.", ns);
+                    for (start, _) in source.match_indices(&prefix) {
+                        let rest = &source[start + 1..];
+                        let literal = &rest[..rest.find('"').unwrap_or(0)];
+                        let is_key = literal
+                            .chars()
+                            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.');
+                        if is_key {
+                            keys.insert(literal.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let mut keys = BTreeSet::new();
+    walk(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &namespaces(), &mut keys);
+    keys
+}
+
+#[test]
+fn test_every_locale_has_the_same_keys() {
+    let en = catalog_keys(Locale::En);
+    for locale in Locale::ALL {
+        let keys = catalog_keys(locale);
+        assert_eq!(keys.len(), locale.catalog().len(), "{:?} has duplicate keys", locale);
+        let missing: Vec<_> = en.difference(&keys).collect();
+        let extra: Vec<_> = keys.difference(&en).collect();
+        assert!(missing.is_empty(), "{:?} is missing keys: {:?}", locale, missing);
+        assert!(extra.is_empty(), "{:?} has keys not in English: {:?}", locale, extra);
+    }
+}
+
+#[test]
+fn test_placeholders_match_across_locales() {
+    for (key, en) in Locale::En.catalog() {
+        for locale in Locale::ALL {
+            let message = lookup(locale, key).unwrap();
+            assert_eq!(placeholders(message), placeholders(en), "placeholders differ for {} in {:?}", key, locale);
+        }
+    }
+}
+
+#[test]
+fn test_keys_used_in_source_exist_in_every_locale() {
+    let used = keys_used_in_source();
+    assert!(used.contains("field.session_id"));
+    for key in &used {
+        for locale in Locale::ALL {
+            assert!(lookup(locale, key).is_some(), "{} is missing from {:?}", key, locale);
+        }
+    }
+
+    let unused: Vec<_> = catalog_keys(Locale::En)
+        .into_iter()
+        .filter(|k| !used.contains(*k))
+        .collect();
+    assert!(unused.is_empty(), "unused keys: {:?}", unused);
+}
+
+#[test]
+fn test_translate_and_format() {
+    assert_eq!(translate(Locale::En, "changes.none"), "No changes");
+    assert_eq!(translate(Locale::Ja, "changes.none"), "変更なし");
+    assert_eq!(translate(Locale::Ja, "no.such.key"), "");
+    assert_eq!(
+        format_message(translate(Locale::Ja, "notify.idle.title"), &[("dir", "repo")]),
+        "Claude Code - 入力待ち (repo)"
+    );
+    assert_eq!(
+        format_message(translate(Locale::En, "slack.continued"), &[("title", "T"), ("page", "2"), ("total", "3")]),
+        "T (continued 2/3)"
+    );
+}
+
+#[test]
+fn test_from_tag() {
+    assert_eq!(Locale::from_tag("ja"), Some(Locale::Ja));
+    assert_eq!(Locale::from_tag("ja_JP.UTF-8"), Some(Locale::Ja));
+    assert_eq!(Locale::from_tag("en-US"), Some(Locale::En));
+    assert_eq!(Locale::from_tag("EN_gb"), Some(Locale::En));
+    assert_eq!(Locale::from_tag("C"), None);
+    assert_eq!(Locale::from_tag(""), None);
+}
+
+#[test]
+fn test_resolve_order() {
+    let empty = HooksConfig::default();
+    let system = FakeSystem::new();
+    assert_eq!(Locale::resolve(&empty, &system), Locale::DEFAULT);
+
+    let system = system.with_env("LANG", "en_US.UTF-8");
+    assert_eq!(Locale::resolve(&empty, &system), Locale::En);
+
+    let system = system.with_env("LC_MESSAGES", "ja_JP.UTF-8");
+    assert_eq!(Locale::resolve(&empty, &system), Locale::Ja);
+
+    let system = system.with_env("LC_ALL", "en_US.UTF-8");
+    assert_eq!(Locale::resolve(&empty, &system), Locale::En);
+
+    // 解釈できない値は読み飛ばす
+    let system = system.with_env("LC_ALL", "C");
+    assert_eq!(Locale::resolve(&empty, &system), Locale::Ja);
+
+    // 設定ファイルが最優先
+    let system = system.with_env("LC_ALL", "en_US.UTF-8");
+    let config = HooksConfig { locale: Some("ja".to_string()), ..Default::default() };
+    assert_eq!(Locale::resolve(&config, &system), Locale::Ja);
+
+    let invalid = HooksConfig { locale: Some("fr".to_string()), ..Default::default() };
+    assert_eq!(Locale::resolve(&invalid, &system), Locale::En);
+}
+
+#[test]
+fn test_load_config_file() {
+    const DEFAULT_PATH: &str = "/Users/dev/.claude/claude-hooks.json";
+    let system = FakeSystem::new().with_env("HOME", "/Users/dev");
+    assert_eq!(HooksConfig::path(&system), Some(PathBuf::from(DEFAULT_PATH)));
+    // ファイルが無い場合はデフォルト値
+    assert_eq!(HooksConfig::load(&system).locale, None);
+
+    let system = system.with_file(DEFAULT_PATH, r#"{"locale": "en"}"#);
+    assert_eq!(HooksConfig::load(&system).locale.as_deref(), Some("en"));
+    assert_eq!(HooksConfig::load_from(&system, Path::new(DEFAULT_PATH)).unwrap().locale.as_deref(), Some("en"));
+
+    // `CLAUDE_HOOKS_CONFIG` で上書きする
+    let system = system
+        .with_env("CLAUDE_HOOKS_CONFIG", "/etc/claude-hooks.json")
+        .with_file("/etc/claude-hooks.json", "not json");
+    assert_eq!(HooksConfig::path(&system), Some(PathBuf::from("/etc/claude-hooks.json")));
+    assert!(HooksConfig::load_from(&system, Path::new("/etc/claude-hooks.json")).is_err());
+    assert_eq!(HooksConfig::load(&system).locale, None);
+
+    assert_eq!(HooksConfig::path(&FakeSystem::new()), None);
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use claude_hooks::handlers;
+use claude_hooks::i18n::Locale;
+use claude_hooks::idle::{session_ended, state_path, IdleConfig, IdleLevel, IdleState};
+use claude_hooks::sinks::{FakeSinks, Sinks};
+use common::{assistant, at, jsonl, user};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const STATE: &str = "/Users/dev/.claude/idle-state/sess-1.json";
+const TRANSCRIPT: &str = "/Users/dev/.claude/projects/app/sess-1.jsonl";
+
+fn mention_config() -> IdleConfig {
+    serde_json::from_value(json!({
+        "slack_after_minutes": 5,
+        "mention_after_minutes": 15,
+        "mention": "<!subteam^S0123>"
+    }))
+    .unwrap()
+}
+
+// ===== 段階 =====
+
+#[test]
+fn test_steps() {
+    assert_eq!(IdleConfig::default().steps(), [(IdleLevel::Desktop, 0), (IdleLevel::Slack, 5)]);
+    assert_eq!(
+        mention_config().steps(),
+        [(IdleLevel::Desktop, 0), (IdleLevel::Slack, 5), (IdleLevel::Mention, 15)]
+    );
+
+    // Slackに通知しない場合はメンションもしない
+    let config: IdleConfig = serde_json::from_value(json!({"slack_after_minutes": null, "mention": "<@U1>"})).unwrap();
+    assert_eq!(config.steps(), [(IdleLevel::Desktop, 0)]);
+    // メンションはSlackより前にはしない
+    let config: IdleConfig =
+        serde_json::from_value(json!({"slack_after_minutes": 10, "mention_after_minutes": 3, "mention": "<@U1>"})).unwrap();
+    assert_eq!(config.steps()[2], (IdleLevel::Mention, 10));
+}
+
+#[test]
+fn test_due_level_and_next_step() {
+    let config = mention_config();
+    let since = at("09:00:00");
+    let cases = [
+        ("09:00:00", IdleLevel::Desktop),
+        ("09:04:59", IdleLevel::Desktop),
+        ("09:05:00", IdleLevel::Slack),
+        ("09:14:00", IdleLevel::Slack),
+        ("10:00:00", IdleLevel::Mention),
+    ];
+    for (now, expected) in cases {
+        assert_eq!(config.due_level(since, at(now)), expected, "{}", now);
+    }
+
+    assert_eq!(config.next_step(since, IdleLevel::Desktop), Some((IdleLevel::Slack, at("09:05:00"))));
+    assert_eq!(config.next_step(since, IdleLevel::Slack), Some((IdleLevel::Mention, at("09:15:00"))));
+    assert_eq!(config.next_step(since, IdleLevel::Mention), None);
+
+    // 表せないほど先の段階には到達しない
+    let config: IdleConfig = serde_json::from_value(json!({"slack_after_minutes": u64::MAX})).unwrap();
+    assert_eq!(config.due_level(since, at("23:59:59")), IdleLevel::Desktop);
+    assert_eq!(config.next_step(since, IdleLevel::Desktop), None);
+}
+
+#[test]
+fn test_state_path() {
+    assert_eq!(
+        state_path(Path::new("/Users/dev"), "a/b"),
+        Path::new("/Users/dev/.claude/idle-state/a_b.json")
+    );
+}
+
+#[test]
+fn test_session_ended() {
+    let exit = user(json!("<command-name>/exit</command-name>\n<command-message>exit</command-message>\n<command-args></command-args>"));
+    let stdout = user(json!("<local-command-stdout>Goodbye!</local-command-stdout>"));
+    assert!(session_ended(&jsonl(&[user(json!("Fix the bug")), assistant(json!("Done.")), exit.clone(), stdout.clone()])));
+    // `--resume` で再開した場合は続きがある
+    assert!(!session_ended(&jsonl(&[exit, stdout, user(json!("One more thing")), assistant(json!("Sure."))])));
+    assert!(!session_ended(&jsonl(&[user(json!("Fix the bug")), assistant(json!("Done."))])));
+    assert!(!session_ended(""));
+}
+
+// ===== idle-watch =====
+
+fn watching_sinks(now: &str) -> FakeSinks {
+    let mut sinks = FakeSinks::new();
+    sinks.home = Some(PathBuf::from("/Users/dev"));
+    sinks.now = at(now);
+    sinks.config.idle = mention_config();
+    let state = IdleState {
+        session_id: "sess-1".to_string(),
+        cwd: "/Users/dev/src/app".to_string(),
+        since: at("09:00:00").to_rfc3339(),
+        level: IdleLevel::Desktop,
+        message: "Claude is waiting for your input".to_string(),
+        transcript_path: Some(TRANSCRIPT.to_string()),
+    };
+    sinks.add_file(STATE, &serde_json::to_string(&state).unwrap());
+    sinks
+}
+
+fn saved_level(sinks: &FakeSinks) -> IdleLevel {
+    let state: IdleState = serde_json::from_str(&sinks.read_file(Path::new(STATE)).unwrap()).unwrap();
+    state.level
+}
+
+#[test]
+fn test_idle_watch_waits_until_next_step() {
+    let sinks = watching_sinks("09:01:00");
+    let wait = handlers::idle_watch("sess-1", "2026-01-01T09:00:00+09:00", &sinks).unwrap();
+    assert_eq!(wait, Some(Duration::from_secs(4 * 60)));
+    assert!(sinks.outputs().slack.is_empty());
+}
+
+#[test]
+fn test_idle_watch_escalates_to_slack_then_mention() {
+    Locale::init(Locale::En);
+    let sinks = watching_sinks("09:05:00");
+    let wait = handlers::idle_watch("sess-1", "2026-01-01T09:00:00+09:00", &sinks).unwrap();
+    assert_eq!(wait, Some(Duration::from_secs(10 * 60)));
+    assert_eq!(saved_level(&sinks), IdleLevel::Slack);
+
+    let slack = &sinks.outputs().slack[0];
+    assert_eq!(slack.title, "⏱️ Claude Code - Idle");
+    assert!(slack.fields.contains(&("Idle for".to_string(), "5m 00s".to_string())));
+
+    let mut sinks = sinks;
+    sinks.now = at("09:15:00");
+    let wait = handlers::idle_watch("sess-1", "2026-01-01T09:00:00+09:00", &sinks).unwrap();
+    assert_eq!(wait, None);
+    assert_eq!(saved_level(&sinks), IdleLevel::Mention);
+    let slack = &sinks.outputs().slack[1];
+    assert_eq!(slack.title, "🔔 Claude Code - Still waiting for input");
+    assert_eq!(slack.fields.last().unwrap(), &("Mention".to_string(), "<!subteam^S0123>".to_string()));
+}
+
+#[test]
+fn test_idle_watch_skips_to_highest_due_step() {
+    Locale::init(Locale::En);
+    let sinks = watching_sinks("09:30:00");
+    assert_eq!(handlers::idle_watch("sess-1", "2026-01-01T09:00:00+09:00", &sinks).unwrap(), None);
+    assert_eq!(sinks.outputs().slack.len(), 1);
+    assert_eq!(saved_level(&sinks), IdleLevel::Mention);
+}
+
+#[test]
+fn test_idle_watch_stops_when_cleared_or_replaced() {
+    let sinks = watching_sinks("09:30:00");
+    // 新しいアイドル状態（別の `since`）は別の `idle-watch` が通知する
+    assert_eq!(handlers::idle_watch("sess-1", "2026-01-01T08:00:00+09:00", &sinks).unwrap(), None);
+    assert!(sinks.outputs().slack.is_empty());
+
+    sinks.remove_file(Path::new(STATE)).unwrap();
+    assert_eq!(handlers::idle_watch("sess-1", "2026-01-01T09:00:00+09:00", &sinks).unwrap(), None);
+    assert!(sinks.outputs().slack.is_empty());
+}
+
+#[test]
+fn test_idle_watch_stops_when_session_ended() {
+    let sinks = watching_sinks("09:30:00");
+    sinks.add_file(
+        TRANSCRIPT,
+        &jsonl(&[assistant(json!("Done.")), user(json!("<command-name>/exit</command-name>"))]),
+    );
+    assert_eq!(handlers::idle_watch("sess-1", "2026-01-01T09:00:00+09:00", &sinks).unwrap(), None);
+    assert!(sinks.outputs().slack.is_empty());
+    assert_eq!(sinks.outputs().removed, vec![STATE.to_string()]);
+}
@@ -0,0 +1,207 @@
+use claude_hooks::doctor::{self, DoctorContext, Status};
+use claude_hooks::i18n::Locale;
+use claude_hooks::t;
+use claude_hooks::install::{
+    binaries, install_binaries, merge_hooks, project_override, read_settings, write_settings, HOOKS,
+};
+use claude_hooks::slack::SlackMode;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_executable(path: &Path, mode: u32) {
+    write_script(path, "#!/bin/sh\n", mode);
+}
+
+fn write_script(path: &Path, content: &str, mode: u32) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[test]
+fn test_merge_preserves_user_settings_and_is_idempotent() {
+    let mut settings = json!({
+        "model": "opus",
+        "permissions": {"deny": ["Bash(rm -rf /*)"]},
+        "hooks": {
+            "PreToolUse": [
+                {"matcher": "Bash", "hooks": [{"type": "command", "command": "~/.claude/scripts/deny-check.sh"}]}
+            ],
+            "Stop": [
+                {"matcher": "", "hooks": [{"type": "command", "command": "say done"}]}
+            ],
+            "PostToolUse": [
+                {"matcher": "Skill", "hooks": [{"type": "command", "command": "/Users/me/.claude/bin/exitplanmode-slack"}]}
+            ]
+        },
+        "statusLine": {"type": "command", "command": "node ~/.claude/statusline.js"}
+    });
+
+    let added = merge_hooks(&mut settings, "~/.claude/bin", Some("/Users/me")).unwrap();
+    // 別のmatcherでも同じコマンドが登録済みなら追加しない（~ を展開して比較）
+    assert_eq!(added.len(), HOOKS.len() - 1);
+    assert!(added.iter().all(|h| h.command.starts_with("~/.claude/bin/")));
+
+    // ユーザーのキー・フックはそのまま
+    assert_eq!(settings["model"], "opus");
+    assert_eq!(settings["hooks"]["PreToolUse"][0]["hooks"][0]["command"], "~/.claude/scripts/deny-check.sh");
+    // 同じmatcherのグループには追記し、無ければグループを作る
+    assert_eq!(
+        settings["hooks"]["Stop"],
+        json!([{"matcher": "", "hooks": [
+            {"type": "command", "command": "say done"},
+            {"type": "command", "command": "~/.claude/bin/task-complete-notification"},
+            {"type": "command", "command": "~/.claude/bin/context-notification"}
+        ]}])
+    );
+    assert_eq!(
+        settings["hooks"]["PostToolUse"][1],
+        json!({"matcher": "AskUserQuestion", "hooks": [
+            {"type": "command", "command": "~/.claude/bin/askuser-question-slack"},
+            {"type": "command", "command": "~/.claude/bin/askuser-answer-slack"}
+        ]})
+    );
+
+    let before = settings.clone();
+    assert_eq!(merge_hooks(&mut settings, "~/.claude/bin", Some("/Users/me")).unwrap(), vec![]);
+    assert_eq!(settings, before);
+
+    assert!(merge_hooks(&mut json!([]), "~/.claude/bin", None).is_err());
+    assert!(merge_hooks(&mut json!({"hooks": {"Stop": {}}}), "~/.claude/bin", None).is_err());
+}
+
+#[test]
+fn test_settings_file_roundtrip_and_binary_copy() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("settings.json");
+    assert_eq!(read_settings(&path).unwrap(), json!({}));
+    assert_eq!(write_settings(&path, &json!({"b": 1, "a": 2})).unwrap(), None);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{\n  \"a\": 2,\n  \"b\": 1\n}\n");
+
+    let backup = write_settings(&path, &json!({})).unwrap().unwrap();
+    assert_eq!(backup, dir.path().join("settings.json.bak"));
+    assert!(fs::read_to_string(&backup).unwrap().contains("\"b\": 1"));
+
+    // 既存のファイルのキーの順序を保ち、新しいキーは後ろに並べる
+    let original = r#"{
+  "model": "opus",
+  "hooks": {
+    "Stop": [{"matcher": "", "hooks": [{"type": "command", "command": "say done"}]}]
+  },
+  "statusLine": {"type": "command", "command": "node statusline.js"}
+}"#;
+    fs::write(&path, original).unwrap();
+    let mut settings = read_settings(&path).unwrap();
+    merge_hooks(&mut settings, "~/.claude/bin", None).unwrap();
+    write_settings(&path, &settings).unwrap();
+    let written = fs::read_to_string(&path).unwrap();
+    let position = |text: &str| written.find(text).unwrap_or_else(|| panic!("{} not in {}", text, written));
+    assert!(position("\"model\"") < position("\"hooks\"") && position("\"hooks\"") < position("\"statusLine\""));
+    assert!(position("\"Stop\"") < position("\"PreToolUse\""));
+    assert!(position("\"type\": \"command\",\n            \"command\": \"say done\"") > 0);
+    assert!(position("\"type\": \"command\",\n    \"command\": \"node statusline.js\"") > 0);
+    // 新しいグループ・フックは matcher / type を先に書く
+    assert!(position("\"matcher\": \"\",\n        \"hooks\": [\n          {\n            \"type\": \"command\",\n            \"command\": \"~/.claude/bin/permission-notification\"") > 0);
+    assert_eq!(read_settings(&path).unwrap(), settings);
+
+    let build = dir.path().join("target/release");
+    write_executable(&build.join("permission-notification"), 0o644);
+    let bin = dir.path().join("bin");
+    let copied = install_binaries(&build, &bin).unwrap();
+    assert_eq!(copied, vec![bin.join("permission-notification")]);
+    assert_eq!(fs::metadata(&copied[0]).unwrap().permissions().mode() & 0o777, 0o755);
+    // コピー先からの実行（同じファイル）は何もしない
+    assert!(install_binaries(&bin, &bin).unwrap().is_empty());
+    assert_eq!(binaries().iter().filter(|b| **b == "permission-notification").count(), 1);
+}
+
+#[test]
+fn test_doctor_checklist() {
+    Locale::init(Locale::En);
+    let dir = TempDir::new().unwrap();
+    let home = dir.path().to_str().unwrap().to_string();
+    let tools = dir.path().join("tools");
+    write_executable(&tools.join("git"), 0o755);
+    // 例の入力を標準入力で受け取り、使い捨てのHOMEで実行される（PATHは空）
+    let stop_hook = format!(
+        "#!/bin/sh\nread -r line\ncase \"$line\" in *'\"Stop\"'*) ;; *) exit 1 ;; esac\n[ -d \"$HOME\" ] && [ \"$HOME\" != \"{}\" ] || exit 1\n",
+        dir.path().display()
+    );
+    write_script(&dir.path().join(".claude/bin/task-complete-notification"), &stop_hook, 0o755);
+    write_executable(&dir.path().join(".claude/bin/user-prompt-slack"), 0o644);
+    write_script(
+        &dir.path().join(".claude/bin/session-end-summary"),
+        "#!/bin/sh\necho 'unknown field `reason`' >&2\nexit 1\n",
+        0o755,
+    );
+
+    let settings_path = dir.path().join(".claude/settings.json");
+    let settings = json!({"hooks": {
+        "Stop": [{"matcher": "", "hooks": [{"type": "command", "command": "~/.claude/bin/task-complete-notification"}]}],
+        "UserPromptSubmit": [{"matcher": "", "hooks": [
+            {"type": "command", "command": "~/.claude/bin/user-prompt-slack"},
+            {"type": "command", "command": "~/.claude/bin/permission-notification --verbose"}
+        ]}],
+        "SessionEnd": [{"matcher": "", "hooks": [{"type": "command", "command": "~/.claude/bin/session-end-summary"}]}]
+    }});
+    write_settings(&settings_path, &settings).unwrap();
+
+    let context = DoctorContext {
+        settings_path: settings_path.clone(),
+        config_path: None,
+        home: Some(home.clone()),
+        path_var: tools.to_str().unwrap().to_string(),
+        slack: SlackMode::Disabled,
+    };
+    let checks = doctor::run(&context);
+    let find = |label: &str| checks.iter().find(|c| c.label == label).unwrap_or_else(|| panic!("{}", label));
+
+    assert_eq!(find("Stop hook: ~/.claude/bin/task-complete-notification").status, Status::Ok);
+    let check = find("UserPromptSubmit hook command is not executable: ~/.claude/bin/user-prompt-slack");
+    assert_eq!(check.fix.as_deref(), Some(format!("chmod +x {}/.claude/bin/user-prompt-slack", home).as_str()));
+    let check = find("UserPromptSubmit hook command not found: ~/.claude/bin/permission-notification --verbose");
+    assert_eq!(check.fix.as_deref(), Some("Run `claude-hooks install`"));
+    assert_eq!(find("Notification hook for permission-notification is not configured").status, Status::Warning);
+    if cfg!(target_os = "macos") {
+        let check = find("terminal-notifier is not installed");
+        assert_eq!((check.status, check.fix.as_deref()), (Status::Warning, Some("brew install terminal-notifier")));
+    } else {
+        assert!(!checks.iter().any(|c| c.label.contains("terminal-notifier")));
+    }
+    assert_eq!(find(&format!("git: {}/git", tools.display())).status, Status::Ok);
+    assert_eq!(find("Slack is not configured (desktop notifications only)").status, Status::Warning);
+
+    // 登録したフックのコマンドに例の入力を渡す（見つからない・実行できないコマンドは飛ばす）
+    assert_eq!(find("~/.claude/bin/task-complete-notification handles a sample Stop event").status, Status::Ok);
+    let check = find("~/.claude/bin/session-end-summary failed on a sample SessionEnd event: unknown field `reason`");
+    assert_eq!((check.status, check.fix.as_deref()), (Status::Error, Some(t("doctor.fix_rebuild"))));
+    assert_eq!(checks.iter().filter(|c| c.label.contains("sample")).count(), 2);
+
+    let output = doctor::format_checklist(&checks);
+    assert!(output.contains("❌ UserPromptSubmit hook command is not executable: ~/.claude/bin/user-prompt-slack\n   → chmod +x "));
+    let warnings = if cfg!(target_os = "macos") { 15 } else { 14 };
+    assert!(output.ends_with(&format!("\n\n3 errors, {} warnings", warnings)), "{}", output);
+
+    // 壊れたsettings.json
+    fs::write(&settings_path, "{").unwrap();
+    let checks = doctor::run(&DoctorContext { config_path: Some(settings_path.clone()), ..context });
+    assert_eq!(checks[0].status, Status::Error);
+    assert!(checks.iter().any(|c| c.label.starts_with("Cannot parse claude-hooks.json")));
+}
+
+#[test]
+fn test_project_override() {
+    let projects = HashMap::from([
+        ("~/src/app/".to_string(), 1),
+        ("/work/other".to_string(), 2),
+    ]);
+    let home = Some("/Users/dev");
+    assert_eq!(project_override(&projects, "/Users/dev/src/app", home), Some(&1));
+    assert_eq!(project_override(&projects, "/work/other/", home), Some(&2));
+    assert_eq!(project_override(&projects, "/Users/dev/src/app", None), None);
+    assert_eq!(project_override(&projects, "/work/another", home), None);
+}
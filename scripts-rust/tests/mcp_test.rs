@@ -0,0 +1,79 @@
+use claude_hooks::mcp::{summarize_input, McpArgument, McpServer, McpServerRegistry, McpTool};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn arg(key: &str, value: &str) -> Option<McpArgument> {
+    Some(McpArgument {
+        key: key.to_string(),
+        value: value.to_string(),
+    })
+}
+
+#[test]
+fn test_parse_mcp_tool_name() {
+    let tool = McpTool::parse("mcp__pencil__batch_design").unwrap();
+    assert_eq!(tool.server, "pencil");
+    assert_eq!(tool.tool, "batch_design");
+    assert_eq!(tool.tool_label(), "batch design");
+
+    // サーバー名に単一のアンダースコアを含む場合
+    let tool = McpTool::parse("mcp__claude_in_chrome__navigate").unwrap();
+    assert_eq!(tool.server, "claude_in_chrome");
+    assert_eq!(tool.tool, "navigate");
+
+    // ツール名側に "__" を含む場合は最初の区切りで分ける
+    let tool = McpTool::parse("mcp__srv__a__b").unwrap();
+    assert_eq!((tool.server.as_str(), tool.tool.as_str()), ("srv", "a__b"));
+
+    assert_eq!(McpTool::parse("Bash"), None);
+    assert_eq!(McpTool::parse("mcp__pencil"), None);
+    assert_eq!(McpTool::parse("mcp____tool"), None);
+    assert_eq!(McpTool::parse("mcp__pencil__"), None);
+}
+
+#[test]
+fn test_server_registry_merges_user_settings() {
+    let mut user = HashMap::new();
+    user.insert(
+        "github".to_string(),
+        McpServer {
+            emoji: Some("🐙".to_string()),
+            description: Some("GitHub".to_string()),
+        },
+    );
+    user.insert(
+        "pencil".to_string(),
+        McpServer {
+            emoji: None,
+            description: Some("Pencil Design".to_string()),
+        },
+    );
+    let registry = McpServerRegistry::new(&user);
+
+    let describe = |name: &str| registry.describe(&McpTool::parse(name).unwrap());
+    assert_eq!(describe("mcp__github__create_issue"), ("🐙".to_string(), "GitHub › create issue".to_string()));
+    // 組み込みの絵文字はそのまま、表示名だけ上書き
+    assert_eq!(describe("mcp__pencil__get_style"), ("🎨".to_string(), "Pencil Design › get style".to_string()));
+    assert_eq!(describe("mcp__unknown__run"), ("🔌".to_string(), "unknown › run".to_string()));
+}
+
+#[test]
+fn test_summarize_input_picks_informative_argument() {
+    // 優先キーが他の文字列より優先される
+    assert_eq!(
+        summarize_input(&json!({"owner": "me", "repo": "x", "title": "Fix login"})),
+        arg("title", "Fix login")
+    );
+    assert_eq!(
+        summarize_input(&json!({"name": "frame", "url": "https://example.com"})),
+        arg("url", "https://example.com")
+    );
+    // 優先キーが無ければ最初の空でない文字列（キー順）
+    assert_eq!(summarize_input(&json!({"zeta": "z", "alpha": "", "beta": "b"})), arg("beta", "b"));
+    // 複数行の値は最初の空でない行
+    assert_eq!(summarize_input(&json!({"content": "\n  first line\nsecond"})), arg("content", "first line"));
+    // 文字列が無ければ数値・真偽値
+    assert_eq!(summarize_input(&json!({"items": [1, 2], "limit": 10})), arg("limit", "10"));
+    assert_eq!(summarize_input(&json!({"nested": {"a": "b"}})), None);
+    assert_eq!(summarize_input(&json!("text")), None);
+}
@@ -0,0 +1,187 @@
+use claude_hooks::decision::Permission;
+use claude_hooks::i18n::Locale;
+use claude_hooks::path_policy::{
+    resolve_symlinks, rules_from_settings, tool_paths, Access, PathContext, PathPolicy, PathPolicyConfig, PathRule,
+};
+use claude_hooks::sinks::RealSinks;
+use claude_hooks::system::RealSystem;
+use serde_json::json;
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// シンボリックリンクを実際のファイルシステムで解決する
+static SINKS: RealSinks<RealSystem> = RealSinks::new(RealSystem);
+
+/// tmp/{home/.ssh/id_rsa, project/src, outside/secret.pem} と project/link → outside
+fn setup() -> (TempDir, PathBuf, PathBuf) {
+    Locale::init(Locale::En);
+    let dir = TempDir::new().unwrap();
+    let root = resolve_symlinks(dir.path());
+    let home = root.join("home");
+    let project = root.join("project");
+    fs::create_dir_all(home.join(".ssh")).unwrap();
+    fs::write(home.join(".ssh/id_rsa"), "key").unwrap();
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::create_dir_all(root.join("outside")).unwrap();
+    fs::write(root.join("outside/secret.pem"), "cert").unwrap();
+    symlink(root.join("outside"), project.join("link")).unwrap();
+    (dir, project, home)
+}
+
+fn rule(path: &str, access: Access) -> PathRule {
+    PathRule {
+        path: path.to_string(),
+        access,
+        reason: None,
+    }
+}
+
+fn policy(project: &Path, home: &Path) -> PathPolicy {
+    let config = PathPolicyConfig {
+        deny_write_outside_cwd: true,
+        allow_write: vec!["/var/tmp/**".to_string()],
+        deny: vec![rule("~/.ssh", Access::Any), rule(".env", Access::Read), rule("*.pem", Access::Any)],
+    };
+    PathPolicy::new(&config, &[], PathContext::new(project, Some(home), &SINKS), &SINKS)
+}
+
+fn denied(policy: &PathPolicy, tool: &str, input: serde_json::Value) -> bool {
+    match policy.check_tool(tool, &input, &SINKS) {
+        Some(decision) => decision.permission == Permission::Deny,
+        None => false,
+    }
+}
+
+#[test]
+fn test_deny_rules_by_tool() {
+    let (_dir, project, home) = setup();
+    let policy = policy(&project, &home);
+
+    let cases = [
+        ("Read", json!({"file_path": "src/main.rs"}), false),
+        ("Read", json!({"file_path": "~/.ssh/id_rsa"}), true),
+        ("Read", json!({"file_path": home.join(".ssh").to_str().unwrap()}), true),
+        ("Read", json!({"file_path": ".env"}), true),
+        ("Read", json!({"file_path": "config/.env"}), true),
+        ("Read", json!({"file_path": ".env.example"}), false),
+        ("Edit", json!({"file_path": "src/main.rs", "old_string": "a", "new_string": "b"}), false),
+        ("Write", json!({"file_path": "certs/server.pem", "content": ""}), true),
+        ("NotebookEdit", json!({"notebook_path": "../other/nb.ipynb"}), true),
+        ("Grep", json!({"pattern": "TODO"}), false),
+        ("Grep", json!({"pattern": "BEGIN", "path": "~/.ssh"}), true),
+        ("Grep", json!({"pattern": "BEGIN", "glob": "*.pem"}), true),
+        ("Glob", json!({"pattern": "**/*.pem"}), true),
+        ("Glob", json!({"pattern": "**/*.rs", "path": "src"}), false),
+        ("Bash", json!({"command": "cat ~/.ssh/id_rsa"}), false),
+    ];
+    for (tool, input, expected) in cases {
+        assert_eq!(denied(&policy, tool, input.clone()), expected, "{} {}", tool, input);
+    }
+}
+
+#[test]
+fn test_grep_without_glob_over_denied_files_asks() {
+    let (_dir, project, home) = setup();
+    fs::write(project.join(".env"), "API_KEY=secret").unwrap();
+    let policy = policy(&project, &home);
+    let permission = |input: serde_json::Value| policy.check_tool("Grep", &input, &SINKS).map(|d| d.permission);
+
+    // `.env` は任意の階層にありうるので、globの無い検索は確認を求める
+    let decision = policy.check_tool("Grep", &json!({"pattern": "KEY", "path": "."}), &SINKS).unwrap();
+    assert_eq!(decision.permission, Permission::Ask);
+    assert_eq!(
+        decision.reason,
+        format!("Searching {} may read files blocked by path policy (.env)", project.display())
+    );
+    assert_eq!(permission(json!({"pattern": "KEY"})), Some(Permission::Ask));
+    // globで絞り込んだ検索は、globが拒否ルールに当たらなければそのまま
+    assert_eq!(permission(json!({"pattern": "KEY", "glob": "*.rs"})), None);
+
+    // 拒否するディレクトリの祖先を検索する場合も確認を求める
+    let config = PathPolicyConfig {
+        deny: vec![rule("~/.ssh", Access::Any), rule("./secrets/**", Access::Read), rule("./out", Access::Write)],
+        ..Default::default()
+    };
+    let policy = PathPolicy::new(&config, &[], PathContext::new(&project, Some(&home), &SINKS), &SINKS);
+    let permission = |input: serde_json::Value| policy.check_tool("Grep", &input, &SINKS).map(|d| d.permission);
+    assert_eq!(permission(json!({"pattern": "BEGIN", "path": "~"})), Some(Permission::Ask));
+    assert_eq!(permission(json!({"pattern": "x", "path": "."})), Some(Permission::Ask));
+    assert_eq!(permission(json!({"pattern": "x", "path": "secrets/db"})), Some(Permission::Deny));
+    assert_eq!(permission(json!({"pattern": "x", "path": "src"})), None);
+}
+
+#[test]
+fn test_symlinks_and_parent_dirs_are_resolved() {
+    let (_dir, project, home) = setup();
+    let policy = policy(&project, &home);
+
+    // cwd内のシンボリックリンク経由でもcwd外への書き込み・拒否ルールに当たる
+    assert!(denied(&policy, "Write", json!({"file_path": "link/new.txt", "content": ""})));
+    assert!(denied(&policy, "Read", json!({"file_path": "link/secret.pem"})));
+    assert!(denied(&policy, "Write", json!({"file_path": "src/../../escape.txt", "content": ""})));
+    // 存在しないディレクトリの `..` も解決する
+    assert!(!denied(&policy, "Write", json!({"file_path": "new/dir/../file.txt", "content": ""})));
+    assert!(!denied(&policy, "Write", json!({"file_path": "/var/tmp/scratch.txt", "content": ""})));
+
+    let context = PathContext::new(&project, Some(&home), &SINKS);
+    assert_eq!(context.resolve("link/secret.pem", &SINKS), project.parent().unwrap().join("outside/secret.pem"));
+    assert_eq!(context.resolve("~/.ssh/../x", &SINKS), home.join("x"));
+}
+
+#[test]
+fn test_reasons_and_hook_output() {
+    let (_dir, project, home) = setup();
+    let policy = policy(&project, &home);
+
+    let decision = policy.check_path(Access::Read, ".env", &SINKS).unwrap();
+    let path = project.join(".env");
+    assert_eq!(decision.reason, format!("Reading {} is blocked by path policy (.env)", path.display()));
+
+    let decision = policy.check_path(Access::Write, "/etc/hosts", &SINKS).unwrap();
+    assert!(decision.reason.starts_with("Writing /etc/hosts is blocked: outside the working directory"));
+
+    let output = decision.to_hook_output();
+    assert_eq!(output["hookSpecificOutput"]["hookEventName"], "PreToolUse");
+    assert_eq!(output["hookSpecificOutput"]["permissionDecision"], "deny");
+    assert_eq!(output["hookSpecificOutput"]["permissionDecisionReason"], decision.reason.as_str());
+
+    let config = PathPolicyConfig {
+        deny: vec![PathRule {
+            reason: Some("Production credentials".to_string()),
+            ..rule("secrets/**", Access::Any)
+        }],
+        ..Default::default()
+    };
+    let policy = PathPolicy::new(&config, &[], PathContext::new(&project, Some(&home), &SINKS), &SINKS);
+    let decision = policy.check_path(Access::Read, "secrets/db.yml", &SINKS).unwrap();
+    assert_eq!(decision.reason, format!("Production credentials: {}", project.join("secrets/db.yml").display()));
+    // 書き込みのみを制限しない設定では cwd 外も許可
+    assert_eq!(policy.check_path(Access::Write, "/etc/hosts", &SINKS), None);
+}
+
+#[test]
+fn test_rules_from_settings() {
+    let settings = json!({
+        "permissions": {
+            "deny": ["Bash(git config:*)", "Read(~/.aws/**)", "Edit(//etc/**)", "Write(/hooks/*.sh)", "Read(./.env)"]
+        }
+    });
+    let rules = rules_from_settings(&settings, Path::new("/home/me/.claude"));
+    assert_eq!(
+        rules,
+        vec![
+            rule("~/.aws/**", Access::Read),
+            rule("/etc/**", Access::Write),
+            rule("/home/me/.claude/hooks/*.sh", Access::Write),
+            rule("./.env", Access::Read),
+        ]
+    );
+    assert_eq!(rules_from_settings(&json!({}), Path::new("/")), vec![]);
+
+    assert_eq!(
+        tool_paths("Glob", &json!({"pattern": "*.rs", "path": "src/"})),
+        vec![(Access::Read, "src/".to_string()), (Access::Read, "src/*.rs".to_string())]
+    );
+}
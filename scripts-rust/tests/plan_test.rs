@@ -0,0 +1,99 @@
+mod common;
+
+use claude_hooks::plan::{load_previous_plan, plan_diff, resolve_plan, save_plan, PlanSource};
+use claude_hooks::sinks::FakeSinks;
+use common::{jsonl, tool_call};
+use serde_json::{json, Value};
+use std::path::Path;
+
+const TRANSCRIPT: &str = "/Users/dev/.claude/projects/app/sess-1.jsonl";
+const PLANS_DIR: &str = "/Users/dev/.claude/plans";
+
+fn with_transcript(lines: &[Value]) -> FakeSinks {
+    let sinks = FakeSinks::new();
+    sinks.add_file(TRANSCRIPT, &jsonl(lines));
+    sinks
+}
+
+#[test]
+fn test_resolve_plan_prefers_tool_input() {
+    let plan = resolve_plan(&json!({"plan": "# From input"}), None, Some(Path::new(PLANS_DIR)), &FakeSinks::new()).unwrap();
+    assert_eq!(plan.content, "# From input");
+    assert_eq!(plan.source, PlanSource::ToolInput);
+}
+
+#[test]
+fn test_resolve_plan_from_transcript_exit_plan_mode() {
+    let sinks = with_transcript(&[
+        tool_call("ExitPlanMode", json!({"plan": "first plan"})),
+        tool_call("ExitPlanMode", json!({"plan": "second plan"})),
+    ]);
+    let plan = resolve_plan(&json!({}), Some(TRANSCRIPT), Some(Path::new(PLANS_DIR)), &sinks).unwrap();
+    assert_eq!(plan.content, "second plan");
+    assert_eq!(plan.source, PlanSource::Transcript);
+}
+
+#[test]
+fn test_resolve_plan_from_transcript_plan_file_write() {
+    let mine = format!("{}/happy-fox.md", PLANS_DIR);
+    let sinks = with_transcript(&[tool_call("Write", json!({"file_path": mine, "content": "my plan"}))]);
+    sinks.add_file(&mine, "my plan (edited)");
+    // 別セッションのプランの方が新しくても無視される
+    sinks.add_file(format!("{}/other-session.md", PLANS_DIR), "other plan");
+
+    let plan = resolve_plan(&json!({}), Some(TRANSCRIPT), Some(Path::new(PLANS_DIR)), &sinks).unwrap();
+    assert_eq!(plan.content, "my plan (edited)");
+    assert_eq!(plan.source, PlanSource::Transcript);
+}
+
+#[test]
+fn test_resolve_plan_without_plans_dir() {
+    let source = "/Users/dev/src/app/main.rs";
+    // HOMEが分からない場合、セッションの最後のWriteをプランとして扱わない
+    let sinks = with_transcript(&[tool_call("Write", json!({"file_path": source, "content": "fn main() {}"}))]);
+    sinks.add_file(source, "fn main() {}");
+    assert_eq!(resolve_plan(&json!({}), Some(TRANSCRIPT), None, &sinks), None);
+
+    let sinks = with_transcript(&[tool_call("ExitPlanMode", json!({"plan": "the plan"}))]);
+    assert_eq!(resolve_plan(&json!({}), Some(TRANSCRIPT), None, &sinks).unwrap().content, "the plan");
+}
+
+#[test]
+fn test_resolve_plan_not_found() {
+    // plansディレクトリに他のファイルがあっても、トランスクリプトから辿れなければ使わない
+    let sinks = FakeSinks::new();
+    sinks.add_file(format!("{}/plan-session-a.md", PLANS_DIR), "plan A");
+    let plans_dir = Some(Path::new(PLANS_DIR));
+    assert_eq!(resolve_plan(&json!({}), None, plans_dir, &sinks), None);
+    assert_eq!(resolve_plan(&json!({"plan": "  "}), None, plans_dir, &sinks), None);
+    // トランスクリプトを読めない
+    assert_eq!(resolve_plan(&json!({}), Some(TRANSCRIPT), plans_dir, &sinks), None);
+}
+
+#[test]
+fn test_plan_history_round_trip() {
+    let sinks = FakeSinks::new();
+    let history = Path::new("/Users/dev/.claude/plan-history");
+    assert_eq!(load_previous_plan(history, "s1", &sinks), None);
+
+    save_plan(history, "s1", "v1", &sinks).unwrap();
+    save_plan(history, "s2", "other", &sinks).unwrap();
+    assert_eq!(load_previous_plan(history, "s1", &sinks).as_deref(), Some("v1"));
+
+    // パス区切りを含むセッションIDでも履歴ディレクトリ内に保存される
+    save_plan(history, "../escape", "x", &sinks).unwrap();
+    assert!(sinks.outputs().files.keys().all(|path| path.starts_with("/Users/dev/.claude/plan-history/")));
+    assert_eq!(load_previous_plan(history, "../escape", &sinks).as_deref(), Some("x"));
+}
+
+#[test]
+fn test_plan_diff() {
+    assert_eq!(plan_diff("same\n", "same\n"), None);
+
+    let diff = plan_diff("# Plan\n1. Add API\n2. Test\n", "# Plan\n1. Add API\n2. Add docs\n3. Test\n").unwrap();
+    assert!(diff.contains("--- previous"));
+    assert!(diff.contains("+++ revised"));
+    assert!(diff.contains("+2. Add docs"));
+    assert!(diff.contains("-2. Test"));
+    assert!(!diff.contains("# Plan"));
+}
@@ -0,0 +1,102 @@
+use claude_hooks::handlers::{self, HANDLERS};
+use claude_hooks::i18n::Locale;
+use claude_hooks::replay::{find_fixtures, record, Fixture};
+use claude_hooks::sinks::{FakeSinks, Sinks};
+use claude_hooks::GitContext;
+use std::env;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// `tests/fixtures/<handler>/*.json` をリプレイして `expect` と比較する
+///
+/// `UPDATE_FIXTURES=1 cargo test` で期待する出力を更新できる。
+#[test]
+fn test_replay_fixtures() {
+    Locale::init(Locale::En);
+    let paths = find_fixtures(&fixtures_dir()).unwrap();
+    assert!(!paths.is_empty());
+
+    for path in paths {
+        let mut fixture = Fixture::load(&path).unwrap();
+        let outputs = fixture.replay().unwrap();
+
+        if env::var("UPDATE_FIXTURES").is_ok() {
+            fixture.expect = Some(outputs);
+            fixture.save(&path).unwrap();
+            continue;
+        }
+
+        let expect = fixture
+            .expect
+            .as_ref()
+            .unwrap_or_else(|| panic!("{} has no expect; run with UPDATE_FIXTURES=1", path.display()));
+        assert_eq!(&outputs, expect, "fixture mismatch: {}", path.display());
+    }
+}
+
+#[test]
+fn test_every_handler_has_fixtures() {
+    for handler in HANDLERS {
+        let dir = fixtures_dir().join(handler);
+        let fixtures = find_fixtures(&dir).unwrap_or_default();
+        assert!(!fixtures.is_empty(), "no fixtures for {}", handler);
+        for path in fixtures {
+            assert_eq!(Fixture::load(&path).unwrap().handler, *handler, "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn test_record_captures_environment_for_replay() {
+    Locale::init(Locale::En);
+    let dir = TempDir::new().unwrap();
+    let transcript = "/Users/dev/.claude/projects/app/s.jsonl";
+    let mut sinks = FakeSinks::new();
+    sinks.home = Some(PathBuf::from("/Users/dev"));
+    sinks.git = Some(GitContext {
+        branch: Some("main".to_string()),
+        ..GitContext::default()
+    });
+    sinks.add_file(
+        transcript,
+        concat!(
+            r#"{"type":"user","message":{"role":"user","content":"Fix the build"}}"#,
+            "\n",
+            r#"{"type":"assistant","message":{"role":"assistant","content":"Fixed."}}"#,
+            "\n",
+        ),
+    );
+    let input = format!(
+        r#"{{"session_id":"s","cwd":"/Users/dev/app","hook_event_name":"Stop","transcript_path":"{}"}}"#,
+        transcript
+    );
+
+    let path = record(dir.path(), "task-complete-notification", &input, &sinks, None).unwrap();
+    assert!(path.starts_with(dir.path().join("task-complete-notification")));
+
+    // 記録したフィクスチャのリプレイは、記録時の環境で直接実行した結果と一致する
+    handlers::run("task-complete-notification", &input, &sinks).unwrap();
+    let fixture = Fixture::load(&path).unwrap();
+    assert_eq!(fixture.env.transcript.len(), 2);
+    assert_eq!(fixture.replay().unwrap(), sinks.outputs());
+
+    let outputs = sinks.outputs();
+    assert_eq!(outputs.desktop[0].subtitle, "[main] 📝 Fix the build");
+    assert_eq!(outputs.slack[0].markdown.as_deref(), Some("Fixed."));
+    assert!(outputs.files.contains_key("/Users/dev/.claude/task-complete.log"));
+}
+
+#[test]
+fn test_handler_errors_are_reported() {
+    let sinks = FakeSinks::new();
+    assert!(handlers::run("no-such-hook", "{}", &sinks).is_err());
+    // 通知のハンドラーは不正な入力をエラーにし、PreToolUseのハンドラーは何も出力せずに終了する
+    assert!(handlers::run("user-prompt-slack", "not json", &sinks).is_err());
+    handlers::run("path-policy-check", "not json", &sinks).unwrap();
+    assert_eq!(sinks.outputs(), Default::default());
+    assert!(sinks.read_file(Path::new("/missing")).is_err());
+}
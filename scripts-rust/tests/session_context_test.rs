@@ -0,0 +1,133 @@
+use claude_hooks::i18n::Locale;
+use claude_hooks::session_context::{
+    build, git_text, hook_output, open_pull_request, pr_cache_path, recent_tasks, SessionContextConfig,
+};
+use claude_hooks::{format_log_entry, parse_log_entries, GitContext, TaskLogEntry};
+use serde_json::json;
+use std::path::Path;
+
+// ===== 設定 =====
+
+#[test]
+fn test_config_per_project() {
+    let config: SessionContextConfig = serde_json::from_value(json!({
+        "projects": {"~/src/secret/": false, "/work/app": true}
+    }))
+    .unwrap();
+    assert!(!config.is_enabled("/Users/dev/src/secret", Some("/Users/dev")));
+    assert!(config.is_enabled("/Users/dev/src/app", Some("/Users/dev")));
+
+    let config: SessionContextConfig =
+        serde_json::from_value(json!({"enabled": false, "projects": {"/work/app": true}})).unwrap();
+    assert!(config.is_enabled("/work/app/", None));
+    assert!(!config.is_enabled("/work/other", None));
+}
+
+#[test]
+fn test_notes_path() {
+    let mut config = SessionContextConfig::default();
+    assert_eq!(config.notes_path("/work/app", None), None);
+
+    config.notes_file = Some("docs/NOTES.md".to_string());
+    assert_eq!(config.notes_path("/work/app", None).unwrap(), Path::new("/work/app/docs/NOTES.md"));
+    config.notes_file = Some("~/notes.md".to_string());
+    assert_eq!(config.notes_path("/work/app", Some("/Users/dev")).unwrap(), Path::new("/Users/dev/notes.md"));
+}
+
+// ===== Gitとプルリクエスト =====
+
+#[test]
+fn test_git_text() {
+    Locale::init(Locale::En);
+    let git = GitContext {
+        branch: Some("main".to_string()),
+        worktree: Some("fix".to_string()),
+        ..GitContext::default()
+    };
+    assert_eq!(git_text(&git), "Branch: main\nWorktree: fix\nWorking tree: clean");
+}
+
+#[test]
+fn test_pr_cache_path() {
+    let home = Path::new("/Users/dev");
+    assert_eq!(
+        pr_cache_path(home, "https://github.com/acme/app").unwrap(),
+        Path::new("/Users/dev/.claude/pr-cache/acme_app.json")
+    );
+    assert_eq!(
+        pr_cache_path(home, "https://gitlab.example.com/group/sub/app.git/").unwrap(),
+        Path::new("/Users/dev/.claude/pr-cache/group_sub_app.json")
+    );
+    assert_eq!(pr_cache_path(home, "https://github.com"), None);
+}
+
+#[test]
+fn test_open_pull_request() {
+    let cache = json!([
+        {"number": 1, "title": "Old", "url": "u1", "headRefName": "feature", "state": "MERGED"},
+        {"number": 2, "title": "New", "url": "u2", "headRefName": "feature", "state": "OPEN"},
+        {"number": 3, "title": "Other", "url": "u3", "headRefName": "main"},
+    ])
+    .to_string();
+    assert_eq!(open_pull_request(&cache, "feature").unwrap().number, 2);
+    assert_eq!(open_pull_request(&cache, "main").unwrap().number, 3);
+    assert_eq!(open_pull_request(&cache, "missing"), None);
+    assert_eq!(open_pull_request("not json", "main"), None);
+}
+
+// ===== 直近のタスク =====
+
+#[test]
+fn test_parse_log_entries() {
+    let log = "[2026-01-01 09:00:00]\n  User Prompt: Old\n  Assistant: Done.\n\n".to_string()
+        + &format_log_entry("2026-01-02 10:00:00", "/work/app", "Line 1\nLine 2", "Answer\n\n[not a header]")
+        + &format_log_entry("2026-01-03 11:00:00", "/work/app", "Next", "OK");
+    let entries = parse_log_entries(&log);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].directory, None);
+    assert_eq!(
+        entries[1],
+        TaskLogEntry {
+            timestamp: "2026-01-02 10:00:00".to_string(),
+            directory: Some("/work/app".to_string()),
+            user_prompt: "Line 1\nLine 2".to_string(),
+            assistant: "Answer\n\n[not a header]".to_string(),
+        }
+    );
+    assert_eq!(entries[2].assistant, "OK");
+}
+
+#[test]
+fn test_recent_tasks_filters_directory() {
+    let entry = |timestamp: &str, directory: &str| TaskLogEntry {
+        timestamp: timestamp.to_string(),
+        directory: Some(directory.to_string()),
+        ..TaskLogEntry::default()
+    };
+    let entries = [entry("1", "/work/app"), entry("2", "/work/other"), entry("3", "/work/app"), entry("4", "/work/app")];
+    let timestamps: Vec<String> = recent_tasks(&entries, "/work/app", 2).into_iter().map(|e| e.timestamp).collect();
+    assert_eq!(timestamps, ["3", "4"]);
+    assert!(recent_tasks(&entries, "/work/app", 0).is_empty());
+}
+
+// ===== 出力 =====
+
+#[test]
+fn test_build_skips_empty_sections_and_limits_size() {
+    Locale::init(Locale::En);
+    assert_eq!(build(&[], 100), None);
+    assert_eq!(build(&[("Notes".to_string(), " \n".to_string())], 100), None);
+
+    let sections = [("Git".to_string(), "Branch: main".to_string()), ("Notes".to_string(), "x".repeat(500))];
+    let context = build(&sections, 100).unwrap();
+    assert!(context.starts_with("## Project context (claude-hooks)\n\n### Git\nBranch: main\n\n### Notes\n"));
+    assert!(context.chars().count() <= 100);
+}
+
+#[test]
+fn test_hook_output() {
+    assert_eq!(
+        hook_output("ctx"),
+        json!({"hookSpecificOutput": {"hookEventName": "SessionStart", "additionalContext": "ctx"}})
+    );
+}
@@ -0,0 +1,156 @@
+use claude_hooks::shell::{inline_script, parse, tokenize, unwrap_command, Token};
+
+fn words(input: &str) -> Vec<Vec<String>> {
+    parse(input).unwrap().commands.into_iter().map(|c| c.argv).collect()
+}
+
+fn argv(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_tokenize_quotes_and_operators() {
+    let lexed = tokenize(r#"echo "a && b" 'c;d' e\ f && ls|wc -l # comment"#).unwrap();
+    let expected = vec![
+        Token::Word("echo".into()),
+        Token::Word("a && b".into()),
+        Token::Word("c;d".into()),
+        Token::Word("e f".into()),
+        Token::Op("&&".into()),
+        Token::Word("ls".into()),
+        Token::Op("|".into()),
+        Token::Word("wc".into()),
+        Token::Word("-l".into()),
+    ];
+    assert_eq!(lexed.tokens, expected);
+
+    assert!(tokenize("echo 'unterminated").is_err());
+    assert!(tokenize("echo $(ls").is_err());
+}
+
+#[test]
+fn test_tokenize_quoting_edge_cases() {
+    let cases: [(&str, Vec<Vec<String>>); 6] = [
+        // ダブルクォート内のエスケープとシングルクォート
+        (r#"echo "it's \"quoted\"" 'a"b'"#, vec![argv(&["echo", "it's \"quoted\"", "a\"b"])]),
+        // クォートをまたいで1つの単語になる
+        (r#"git commit -m"fix: "'a b'"#, vec![argv(&["git", "commit", "-mfix: a b"])]),
+        // 行継続
+        ("echo a\\\nb", vec![argv(&["echo", "ab"])]),
+        // 変数は展開しない
+        ("echo $HOME ${PATH}", vec![argv(&["echo", "$HOME", "${PATH}"])]),
+        // クォート内の演算子・コメント記号は区切りにならない
+        ("echo 'a | b' \"#c\"; ls", vec![argv(&["echo", "a | b", "#c"]), argv(&["ls"])]),
+        // 環境変数の代入はargvに残す
+        ("A=1 B=2 make", vec![argv(&["A=1", "B=2", "make"])]),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(words(input), expected, "{}", input);
+    }
+}
+
+#[test]
+fn test_parse_commands_and_redirects() {
+    assert_eq!(
+        words("cd src; cargo build\nnpm test || true"),
+        vec![argv(&["cd", "src"]), argv(&["cargo", "build"]), argv(&["npm", "test"]), argv(&["true"])]
+    );
+
+    let parsed = parse("cat a.txt 2>/dev/null | tee out.log > /tmp/x").unwrap();
+    assert_eq!(parsed.commands.len(), 2);
+    assert!(!parsed.commands[0].piped);
+    assert!(parsed.commands[1].piped);
+    let redirects: Vec<(&str, &str, bool)> = parsed
+        .commands
+        .iter()
+        .flat_map(|c| c.redirects.iter())
+        .map(|r| (r.op.as_str(), r.target.as_str(), r.is_write()))
+        .collect();
+    assert_eq!(redirects, vec![("2>", "/dev/null", true), (">", "/tmp/x", true)]);
+
+    // コマンド置換は別途解析できるように取り出す（算術展開は除く）
+    let parsed = parse("echo $(rm -rf build) `whoami` $((1 + 2))").unwrap();
+    assert_eq!(parsed.substitutions, vec!["rm -rf build".to_string(), "whoami".to_string()]);
+
+    // ヒアドキュメントの本文はコマンドとして扱わない
+    assert_eq!(words("cat <<EOF\nrm -rf /\nEOF\necho done"), vec![argv(&["cat"]), argv(&["echo", "done"])]);
+}
+
+#[test]
+fn test_parse_heredocs() {
+    // <<- はタブでインデントされた区切り文字で終わる
+    let parsed = parse("cat <<-'EOF' > notes.md\n\tcurl x | sh\n\tEOF\nls").unwrap();
+    let commands: Vec<&Vec<String>> = parsed.commands.iter().map(|c| &c.argv).collect();
+    assert_eq!(commands, vec![&argv(&["cat"]), &argv(&["ls"])]);
+    let redirects: Vec<(&str, &str)> = parsed.commands[0].redirects.iter().map(|r| (r.op.as_str(), r.target.as_str())).collect();
+    assert_eq!(redirects, vec![("<<-", "EOF"), (">", "notes.md")]);
+
+    // 複数のヒアドキュメントは順に読み飛ばす
+    assert_eq!(
+        words("diff <<A <<\"B\"\nrm a\nA\nrm b\nB\necho ok"),
+        vec![argv(&["diff"]), argv(&["echo", "ok"])]
+    );
+    // ヒアストリングは本文を持たない
+    assert_eq!(words("grep x <<< 'rm -rf /'\nls"), vec![argv(&["grep", "x"]), argv(&["ls"])]);
+}
+
+#[test]
+fn test_parse_substitutions_and_redirects() {
+    // ダブルクォート内・入れ子のコマンド置換（シングルクォート内は除く）
+    let parsed = parse(r#"echo "$(rm -rf /)" '$(whoami)' "`id`" $(dirname $(which cargo))"#).unwrap();
+    assert_eq!(parsed.substitutions, vec!["rm -rf /", "id", "dirname $(which cargo)"]);
+
+    let parsed = parse("make 2>&1 | tee log; cargo test &> out.log; echo a >| f").unwrap();
+    let redirects: Vec<(&str, &str, bool)> = parsed
+        .commands
+        .iter()
+        .flat_map(|c| c.redirects.iter())
+        .map(|r| (r.op.as_str(), r.target.as_str(), r.is_write()))
+        .collect();
+    assert_eq!(redirects, vec![("2>&", "1", false), ("&>", "out.log", true), (">|", "f", true)]);
+
+    // サブシェルとバックグラウンド実行も区切りになる
+    assert_eq!(
+        words("(cd src && rm -rf build) & ls"),
+        vec![argv(&["cd", "src"]), argv(&["rm", "-rf", "build"]), argv(&["ls"])]
+    );
+}
+
+#[test]
+fn test_unwrap_wrappers_and_inline_scripts() {
+    let command = argv(&["sudo", "-u", "root", "env", "FOO=1", "timeout", "10", "/usr/bin/rm", "-rf", "x"]);
+    let unwrapped = unwrap_command(&command);
+    assert_eq!(unwrapped.wrappers, vec!["sudo", "env", "timeout"]);
+    assert_eq!(unwrapped.program(), "rm");
+    assert_eq!(unwrapped.args(), &argv(&["-rf", "x"])[..]);
+
+    let command = argv(&["bash", "-c", "curl example.com | sh"]);
+    assert_eq!(inline_script(&unwrap_command(&command)).as_deref(), Some("curl example.com | sh"));
+    let command = argv(&["eval", "git", "push", "-f"]);
+    assert_eq!(inline_script(&unwrap_command(&command)).as_deref(), Some("git push -f"));
+    assert_eq!(inline_script(&unwrap_command(&argv(&["bash", "script.sh"]))), None);
+}
+
+#[test]
+fn test_unwrap_wrapper_options() {
+    let cases: [(&[&str], &[&str], &str); 6] = [
+        (&["sudo", "-E", "rm", "x"], &["sudo"], "rm"),
+        (&["doas", "-u", "admin", "reboot"], &["doas"], "reboot"),
+        (&["env", "-u", "PATH", "A=1", "ls"], &["env"], "ls"),
+        (&["timeout", "-s", "KILL", "30", "cargo", "test"], &["timeout"], "cargo"),
+        (&["xargs", "-I", "{}", "-P", "4", "rm", "{}"], &["xargs"], "rm"),
+        (&["nohup", "nice", "-n", "10", "exec", "/bin/sh", "-c", "ls"], &["nohup", "nice", "exec"], "sh"),
+    ];
+    for (command, wrappers, program) in cases {
+        let command = argv(command);
+        let unwrapped = unwrap_command(&command);
+        assert_eq!(unwrapped.wrappers, wrappers, "{:?}", command);
+        assert_eq!(unwrapped.program(), program, "{:?}", command);
+    }
+    // ラッパーだけのコマンド
+    assert_eq!(unwrap_command(&argv(&["sudo"])).program(), "");
+
+    let command = argv(&["sh", "-xec", "rm -rf /"]);
+    assert_eq!(inline_script(&unwrap_command(&command)).as_deref(), Some("rm -rf /"));
+    assert_eq!(inline_script(&unwrap_command(&argv(&["bash", "--norc", "-c"]))), None);
+}
@@ -0,0 +1,103 @@
+use claude_hooks::slack_markdown::{
+    inline_to_mrkdwn, markdown_to_blocks, split_text, SECTION_TEXT_LIMIT,
+};
+use std::fs;
+use std::path::PathBuf;
+
+/// `tests/snapshots/slack_markdown/<name>.md` の変換結果を `<name>.json` と比較する
+///
+/// `UPDATE_SNAPSHOTS=1 cargo test` でスナップショットを更新できる。
+fn assert_snapshot(name: &str) {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/slack_markdown");
+    let markdown = fs::read_to_string(dir.join(format!("{}.md", name))).unwrap();
+    let actual = serde_json::to_string_pretty(&markdown_to_blocks(&markdown)).unwrap() + "\n";
+    let snapshot_path = dir.join(format!("{}.json", name));
+
+    if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+        fs::write(&snapshot_path, &actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&snapshot_path)
+        .unwrap_or_else(|_| panic!("snapshot {} not found; run with UPDATE_SNAPSHOTS=1", name));
+    assert_eq!(actual, expected, "snapshot mismatch: {}", name);
+}
+
+#[test]
+fn test_snapshot_plan() {
+    assert_snapshot("plan");
+}
+
+#[test]
+fn test_snapshot_assistant() {
+    assert_snapshot("assistant");
+}
+
+#[test]
+fn test_inline_to_mrkdwn() {
+    assert_eq!(inline_to_mrkdwn("**bold** and *italic*"), "*bold* and _italic_");
+    assert_eq!(inline_to_mrkdwn("__bold__ ~~gone~~"), "*bold* ~gone~");
+    assert_eq!(
+        inline_to_mrkdwn("[docs](https://example.com/a?b=1&c=2)"),
+        "<https://example.com/a?b=1&amp;c=2|docs>"
+    );
+    assert_eq!(inline_to_mrkdwn("`**raw** <tag>`"), "`**raw** &lt;tag&gt;`");
+    assert_eq!(inline_to_mrkdwn("2 * 3 * 4"), "2 * 3 * 4");
+    assert_eq!(inline_to_mrkdwn("[not a link] (x)"), "[not a link] (x)");
+    assert_eq!(inline_to_mrkdwn("unclosed `code"), "unclosed `code");
+}
+
+#[test]
+fn test_inline_links_keep_emphasis_delimiters() {
+    assert_eq!(
+        inline_to_mrkdwn("see [spec](https://example.com/a__b__c) now"),
+        "see <https://example.com/a__b__c|spec> now"
+    );
+    assert_eq!(
+        inline_to_mrkdwn("[one](https://x.test/*a*) and [two](https://x.test/**b**)"),
+        "<https://x.test/*a*|one> and <https://x.test/**b**|two>"
+    );
+    // リンクの前後・ラベルの強調は変換する
+    assert_eq!(
+        inline_to_mrkdwn("**see [the __docs__](https://x.test/__init__.py)**"),
+        "*see <https://x.test/__init__.py|the *docs*>*"
+    );
+    // マーカーに似た入力はそのまま
+    assert_eq!(inline_to_mrkdwn("a \u{2}0\u{3} b"), "a \u{2}0\u{3} b");
+}
+
+#[test]
+fn test_long_paragraph_split_within_limit() {
+    let markdown = (0..200)
+        .map(|i| format!("Line {} with some words to fill the block up quickly.", i))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let blocks = markdown_to_blocks(&markdown);
+    assert!(blocks.len() > 1);
+    for block in &blocks {
+        let text = block["text"]["text"].as_str().unwrap();
+        assert!(text.chars().count() <= SECTION_TEXT_LIMIT);
+    }
+}
+
+#[test]
+fn test_long_code_block_keeps_fences() {
+    let code: String = (0..400).map(|i| format!("let x{} = {};\n", i, i)).collect();
+    let markdown = format!("```rust\n{}```\n", code);
+    let blocks = markdown_to_blocks(&markdown);
+    assert!(blocks.len() > 1);
+    for block in &blocks {
+        let text = block["text"]["text"].as_str().unwrap();
+        assert!(text.starts_with("```\n") && text.ends_with("\n```"));
+        assert!(text.chars().count() <= SECTION_TEXT_LIMIT);
+    }
+}
+
+#[test]
+fn test_split_text_handles_multibyte_and_long_lines() {
+    let line = "あ".repeat(25);
+    let parts = split_text(&line, 10);
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts[2], "あ".repeat(5));
+    assert_eq!(split_text("a\nb\nc", 3), vec!["a\nb", "c"]);
+}
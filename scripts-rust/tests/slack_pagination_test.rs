@@ -0,0 +1,124 @@
+use claude_hooks::slack::{build_pages, limit_pages, SlackMessage, FIELD_TEXT_LIMIT, MAX_SECTION_FIELDS};
+use claude_hooks::i18n::Locale;
+use claude_hooks::slack_markdown::{markdown_to_blocks, MAX_BLOCKS, SECTION_TEXT_LIMIT};
+use serde_json::{json, Value};
+
+fn message<'a>(fields: &'a [(&'a str, &'a str)], body: Vec<Value>, button_url: Option<&'a str>) -> SlackMessage<'a> {
+    SlackMessage {
+        title: "Title",
+        fields,
+        body,
+        button_url,
+        full_text: None,
+        thread: None,
+    }
+}
+
+fn texts(page: &[Value]) -> Vec<String> {
+    page.iter()
+        .filter(|b| b["type"] == "section")
+        .filter_map(|b| b["text"]["text"].as_str().map(|s| s.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_short_message_is_single_page() {
+    Locale::init(Locale::En);
+    let fields = [("Directory", "repo"), ("Branch", "main")];
+    let pages = build_pages(&message(&fields, Vec::new(), Some("x-claude-iterm://switch?guid=1")));
+    assert_eq!(pages.len(), 1);
+    let page = &pages[0];
+    assert_eq!(page[0]["type"], "header");
+    assert_eq!(page[1]["fields"].as_array().unwrap().len(), 2);
+    assert!(page[2]["text"]["text"].as_str().unwrap().contains("iTerm2"));
+}
+
+#[test]
+fn test_fields_are_split_into_sections_of_ten() {
+    Locale::init(Locale::En);
+    let labels: Vec<String> = (1..=11).map(|i| format!("Field {}", i)).collect();
+    let fields: Vec<(&str, &str)> = labels.iter().map(|label| (label.as_str(), "value")).collect();
+    let pages = build_pages(&message(&fields, Vec::new(), None));
+    assert_eq!(pages.len(), 1);
+
+    let page = &pages[0];
+    assert_eq!(page.len(), 3);
+    assert_eq!(page[1]["fields"].as_array().unwrap().len(), MAX_SECTION_FIELDS);
+    let rest = page[2]["fields"].as_array().unwrap();
+    assert_eq!(rest.len(), 1);
+    assert_eq!(rest[0]["text"], "*Field 11*\nvalue");
+}
+
+#[test]
+fn test_long_field_is_moved_to_body_without_truncation() {
+    Locale::init(Locale::En);
+    let long = "x".repeat(FIELD_TEXT_LIMIT * 2);
+    let fields = [("Directory", "repo"), ("Prompt", long.as_str())];
+    let pages = build_pages(&message(&fields, Vec::new(), None));
+    assert_eq!(pages.len(), 1);
+
+    let page = &pages[0];
+    assert_eq!(page[1]["fields"].as_array().unwrap().len(), 1);
+    let texts = texts(page);
+    assert_eq!(texts[0], "*Prompt*");
+    let joined: String = texts[1..].concat();
+    assert_eq!(joined, long);
+    assert!(texts.iter().all(|t| t.chars().count() <= SECTION_TEXT_LIMIT));
+}
+
+#[test]
+fn test_long_code_field_keeps_fences_when_split() {
+    Locale::init(Locale::En);
+    let code: String = (0..600).map(|i| format!("+line {}\n", i)).collect();
+    let value = format!("```\n{}\n```", code);
+    let fields = [("Diff", value.as_str())];
+    let pages = build_pages(&message(&fields, Vec::new(), None));
+    let texts = texts(&pages[0]);
+    assert!(texts.len() > 2);
+    for text in &texts[1..] {
+        assert!(text.starts_with("```\n") && text.ends_with("\n```"));
+    }
+}
+
+#[test]
+fn test_body_over_block_limit_continues_on_next_pages() {
+    Locale::init(Locale::En);
+    let markdown = (0..120).map(|i| format!("# Heading {}", i)).collect::<Vec<_>>().join("\n");
+    let body = markdown_to_blocks(&markdown);
+    let fields = [("Directory", "repo")];
+    let pages = build_pages(&message(&fields, body, Some("x-claude-iterm://switch?guid=1")));
+
+    assert_eq!(pages.len(), 3);
+    for page in &pages {
+        assert!(page.len() <= MAX_BLOCKS);
+    }
+    // リンクは1ページ目の末尾
+    assert!(pages[0].last().unwrap()["text"]["text"].as_str().unwrap().contains("iTerm2"));
+    assert_eq!(pages[1][0]["type"], "context");
+    assert_eq!(pages[1][0]["elements"][0]["text"], "_Title (continued 2/3)_");
+
+    // 本文は欠けずに順番通り全ページに分配される
+    let headings: Vec<String> = pages
+        .iter()
+        .flat_map(|p| texts(p))
+        .filter(|t| t.starts_with("*Heading"))
+        .collect();
+    assert_eq!(headings.len(), 120);
+    assert_eq!(headings[119], "*Heading 119*");
+}
+
+#[test]
+fn test_limit_pages_appends_truncation_notice() {
+    Locale::init(Locale::En);
+    let body: Vec<Value> = (0..300).map(|i| json!({"type": "section", "text": {"type": "mrkdwn", "text": i.to_string()}})).collect();
+    let pages = build_pages(&message(&[], body, None));
+    assert_eq!(pages.len(), 7);
+
+    let limited = limit_pages(pages, 2);
+    assert_eq!(limited.len(), 2);
+    let last = limited.last().unwrap();
+    assert!(last.len() <= MAX_BLOCKS);
+    let notice = last.last().unwrap()["elements"][0]["text"].as_str().unwrap();
+    // 1ページ目に49件、2ページ目に48件が残る
+    assert!(notice.starts_with("(truncated: 203 more blocks. Incoming Webhooks send at most 2 messages;"), "{}", notice);
+}
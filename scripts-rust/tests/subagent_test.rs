@@ -0,0 +1,265 @@
+mod common;
+
+use claude_hooks::handlers;
+use claude_hooks::i18n::Locale;
+use claude_hooks::sinks::{RealSinks, Sinks, SlackPost};
+use claude_hooks::subagent::{
+    format_duration, is_reported, pending_tasks, reported_path, reports_on_task, transcript_duration_ms, SubagentConfig,
+    SubagentReport, SUMMARY_LIMIT,
+};
+use claude_hooks::system::{FakeSystem, HttpBody};
+use common::{assistant, jsonl, timestamped, tool_result, tool_use, user};
+use serde_json::{json, Value};
+use std::path::Path;
+
+fn task_use(id: &str, subagent_type: &str, prompt: &str) -> Value {
+    tool_use(id, "Task", json!({
+        "description": format!("{} task", subagent_type),
+        "prompt": prompt,
+        "subagent_type": subagent_type,
+    }))
+}
+
+// ===== 設定 =====
+
+#[test]
+fn test_config_per_agent_type() {
+    let config: SubagentConfig =
+        serde_json::from_value(json!({"agents": {"Explore": false, "code-review-specialist": true}})).unwrap();
+    assert!(config.is_enabled("code-review-specialist"));
+    assert!(config.is_enabled("general-purpose"));
+    assert!(!config.is_enabled("Explore"));
+    assert!(!config.thread);
+
+    let config: SubagentConfig =
+        serde_json::from_value(json!({"enabled": false, "agents": {"rust-security-specialist": true}})).unwrap();
+    assert!(config.is_enabled("rust-security-specialist"));
+    assert!(!config.is_enabled("general-purpose"));
+}
+
+// ===== Task呼び出し =====
+
+#[test]
+fn test_pending_tasks_excludes_finished_calls() {
+    let transcript = jsonl(&[
+        assistant(json!([
+            task_use("t1", "Explore", "find config"),
+            task_use("t2", "code-review-specialist", "review diff"),
+        ])),
+        user(json!([tool_result("t1", json!("src/config.rs"))])),
+        assistant(json!([
+            tool_use("t3", "Read", json!({"file_path": "/a"})),
+            tool_use("t4", "Task", json!({"description": "d", "prompt": "p"})),
+        ])),
+    ]);
+    let pending = pending_tasks(&transcript);
+    let types: Vec<&str> = pending.iter().map(|c| c.subagent_type.as_str()).collect();
+    assert_eq!(types, ["code-review-specialist", "general-purpose"]);
+    assert_eq!(pending[0].prompt, "review diff");
+}
+
+#[test]
+fn test_transcript_duration() {
+    let transcript = jsonl(&[
+        timestamped(json!({"type": "user"}), "00:00:00.000"),
+        json!({"type": "summary"}),
+        timestamped(json!({"type": "assistant"}), "00:01:05.250"),
+    ]);
+    assert_eq!(transcript_duration_ms(&transcript), Some(65_250));
+    assert_eq!(transcript_duration_ms(""), None);
+}
+
+#[test]
+fn test_format_duration() {
+    assert_eq!(format_duration(999), "0s");
+    assert_eq!(format_duration(45_000), "45s");
+    assert_eq!(format_duration(125_400), "2m 05s");
+    assert_eq!(format_duration(3_725_000), "1h 02m");
+}
+
+// ===== 報告内容 =====
+
+#[test]
+fn test_report_from_tool_use() {
+    Locale::init(Locale::En);
+    let input = json!({"description": "Review", "prompt": "p", "subagent_type": "code-review-specialist"});
+
+    let report = SubagentReport::from_tool_use(&input, &json!({
+        "agentId": "a1",
+        "content": [{"type": "text", "text": "First"}, {"type": "image"}, {"type": "text", "text": "Second"}],
+        "totalDurationMs": 5000,
+        "totalToolUseCount": 3,
+        "totalTokens": 1234567,
+    }));
+    assert_eq!(report.agent_id.as_deref(), Some("a1"));
+    assert_eq!(report.output, "First\nSecond");
+    assert_eq!(report.stats_text(), "5s · 3 tool uses · 1,234,567 tokens");
+
+    let report = SubagentReport::from_tool_use(&input, &json!("plain text result"));
+    assert_eq!(report.output, "plain text result");
+    assert_eq!(report.agent_id, None);
+    assert_eq!(report.stats_text(), "N/A");
+}
+
+#[test]
+fn test_report_from_transcripts_matches_prompt() {
+    let parent = jsonl(&[assistant(json!([
+        task_use("t1", "rust-security-specialist", "Audit unsafe code"),
+        task_use("t2", "ux-design-specialist", "Review the settings screen"),
+    ]))]);
+    let agent = |prompt: &str| {
+        jsonl(&[
+            timestamped(user(json!(prompt)), "00:00:00"),
+            timestamped(assistant(json!("Done")), "00:00:30"),
+        ])
+    };
+
+    let report = SubagentReport::from_transcripts(Some("x"), &agent("Audit unsafe code"), Some(&parent));
+    assert_eq!(report.subagent_type, "rust-security-specialist");
+    assert_eq!(report.duration_ms, Some(30_000));
+    assert_eq!(report.output, "Done");
+
+    // プロンプトが一致しない場合は最後の呼び出し
+    let report = SubagentReport::from_transcripts(None, &agent("something else"), Some(&parent));
+    assert_eq!(report.subagent_type, "ux-design-specialist");
+
+    // 親のトランスクリプトが無い場合
+    let report = SubagentReport::from_transcripts(None, &agent("x"), None);
+    assert_eq!(report.subagent_type, "general-purpose");
+    assert_eq!(report.description, "");
+}
+
+#[test]
+fn test_summary_is_truncated() {
+    Locale::init(Locale::En);
+    let mut report = SubagentReport::from_tool_use(&json!({}), &json!({"content": "x".repeat(SUMMARY_LIMIT * 2)}));
+    assert!(report.summary().chars().count() <= SUMMARY_LIMIT);
+    report.output = "  \n".to_string();
+    assert_eq!(report.summary(), "(no output)");
+}
+
+#[test]
+fn test_reported_log() {
+    let path = reported_path(Path::new("/Users/dev"), "../sess");
+    assert_eq!(path, Path::new("/Users/dev/.claude/subagent-reports/___sess.log"));
+    assert!(is_reported("a1\nb2\n", "b2"));
+    assert!(!is_reported("a1\nb22\n", "b2"));
+}
+
+#[test]
+fn test_reports_on_task() {
+    let settings = |matcher: &str, command: &str| {
+        json!({"hooks": {"PostToolUse": [{"matcher": matcher, "hooks": [{"type": "command", "command": command}]}]}})
+    };
+    assert!(reports_on_task(&settings("Task", "~/.claude/bin/subagent-slack")));
+    assert!(reports_on_task(&settings("ExitPlanMode|Task", "/opt/hooks/subagent-slack --verbose")));
+    assert!(reports_on_task(&settings("*", "~/.claude/bin/subagent-slack")));
+    assert!(reports_on_task(&settings("", "~/.claude/bin/subagent-slack")));
+    assert!(!reports_on_task(&settings("TaskOutput", "~/.claude/bin/subagent-slack")));
+    assert!(!reports_on_task(&settings("Task", "~/.claude/bin/context-notification")));
+    assert!(!reports_on_task(&json!({"hooks": {"SubagentStop": [{"hooks": [{"command": "subagent-slack"}]}]}})));
+    assert!(!reports_on_task(&json!({})));
+}
+
+#[test]
+fn test_report_is_recorded_only_after_successful_post() {
+    Locale::init(Locale::En);
+    let system = FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_env("CLAUDE_CODE_SLACK_WEBHOOK_URL", "https://hooks.example/x");
+    let sinks = RealSinks::new(system);
+    let input = json!({
+        "session_id": "sess-1",
+        "cwd": "/nonexistent/app",
+        "permission_mode": "default",
+        "hook_event_name": "PostToolUse",
+        "tool_name": "Task",
+        "tool_input": {"subagent_type": "Explore", "description": "Find handlers"},
+        "tool_response": {"agentId": "a1", "content": [{"type": "text", "text": "Done."}]},
+        "tool_use_id": "toolu_01"
+    })
+    .to_string();
+    let log = "/Users/dev/.claude/subagent-reports/sess-1.log";
+
+    // 送信に失敗した場合は記録せず、次のイベントで再送する
+    sinks.system.push_http_response(Err("status code 500"));
+    handlers::subagent_slack(&input, &sinks).unwrap();
+    assert_eq!(sinks.system.file(log), None);
+
+    sinks.system.push_http_response(Ok("ok"));
+    handlers::subagent_slack(&input, &sinks).unwrap();
+    assert_eq!(sinks.system.file(log).as_deref(), Some("a1\n"));
+
+    handlers::subagent_slack(&input, &sinks).unwrap();
+    assert_eq!(sinks.system.requests().len(), 2);
+}
+
+// ===== セッションのスレッド =====
+
+fn bot_system() -> FakeSystem {
+    FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_env("CLAUDE_CODE_SLACK_BOT_TOKEN", "xoxb-1")
+        .with_env("CLAUDE_CODE_SLACK_CHANNEL", "#dev")
+}
+
+fn payload(system: &FakeSystem, index: usize) -> Value {
+    match &system.requests()[index].body {
+        HttpBody::Json(json) => json.clone(),
+        other => panic!("expected JSON body: {:?}", other),
+    }
+}
+
+#[test]
+fn test_subagent_report_replies_in_session_thread() {
+    let sinks = RealSinks::new(bot_system());
+    sinks.system.push_http_response(Ok(r#"{"ok":true,"channel":"C1","ts":"100.1"}"#));
+    sinks.system.push_http_response(Ok(r#"{"ok":true,"channel":"C1","ts":"100.2"}"#));
+    sinks.system.push_http_response(Ok(r#"{"ok":true,"channel":"C1","ts":"100.3"}"#));
+
+    // 最初のプロンプトがスレッドの親になる
+    let prompt = SlackPost::new("Prompt".to_string(), &[]).in_session_thread("sess-1", false);
+    sinks.post_slack(&prompt).unwrap();
+    assert_eq!(
+        sinks.system.file("/Users/dev/.claude/slack-threads/sess-1.json").as_deref(),
+        Some(r#"{"channel":"C1","ts":"100.1"}"#)
+    );
+
+    // 2回目のプロンプトは親を上書きしない
+    sinks.post_slack(&prompt).unwrap();
+    assert_eq!(payload(&sinks.system, 1).get("thread_ts"), None);
+
+    let report = SlackPost::new("Subagent".to_string(), &[]).in_session_thread("sess-1", true);
+    sinks.post_slack(&report).unwrap();
+    let reply = payload(&sinks.system, 2);
+    assert_eq!(reply["channel"], "C1");
+    assert_eq!(reply["thread_ts"], "100.1");
+    assert_eq!(
+        sinks.system.file("/Users/dev/.claude/slack-threads/sess-1.json").as_deref(),
+        Some(r#"{"channel":"C1","ts":"100.1"}"#)
+    );
+}
+
+#[test]
+fn test_reply_without_parent_becomes_parent() {
+    let sinks = RealSinks::new(bot_system());
+    sinks.system.push_http_response(Ok(r#"{"ok":true,"channel":"C1","ts":"200.1"}"#));
+
+    let report = SlackPost::new("Subagent".to_string(), &[]).in_session_thread("sess-2", true);
+    sinks.post_slack(&report).unwrap();
+    assert_eq!(payload(&sinks.system, 0).get("thread_ts"), None);
+    assert!(sinks.system.file("/Users/dev/.claude/slack-threads/sess-2.json").is_some());
+}
+
+#[test]
+fn test_webhook_ignores_session_thread() {
+    let system = FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_env("CLAUDE_CODE_SLACK_WEBHOOK_URL", "https://hooks.example/x");
+    let sinks = RealSinks::new(system);
+    sinks
+        .post_slack(&SlackPost::new("Subagent".to_string(), &[]).in_session_thread("sess-1", true))
+        .unwrap();
+    assert_eq!(sinks.system.requests().len(), 1);
+    assert!(sinks.system.file("/Users/dev/.claude/slack-threads/sess-1.json").is_none());
+}
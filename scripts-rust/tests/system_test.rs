@@ -0,0 +1,510 @@
+use chrono::DateTime;
+use claude_hooks::i18n::Locale;
+use claude_hooks::slack::{self, SlackMessage, SlackMode};
+use claude_hooks::system::{FakeSystem, HttpBody, ProcessOutput, System};
+use claude_hooks::{
+    build_iterm2_url_scheme, detect_ide_bundle_id, detect_terminal_bundle_id, extract_assistant_message,
+    extract_user_prompt, get_activation_bundle_id, get_git_branch, log_to_file, post_to_slack_rich,
+    send_notification,
+};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const LOCK_DIR: &str = "/Users/dev/.claude/ide";
+const VSCODE_COMM: &str = "/Applications/Visual Studio Code.app/Contents/MacOS/Electron\n";
+const VSCODE_MDLS: &str = "kMDItemCFBundleIdentifier = \"com.microsoft.VSCode\"\n";
+
+/// `~/.claude/ide/*.lock` の内容
+fn lock(pid: u32) -> String {
+    format!(r#"{{"pid": {}, "workspaceFolders": ["/Users/dev/src/app"], "ideName": "Visual Studio Code"}}"#, pid)
+}
+
+fn at(secs: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+/// HOMEと実行中のVSCode（PID 42）のlockファイルがある環境
+fn ide_system() -> FakeSystem {
+    FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_file(format!("{}/42.lock", LOCK_DIR), &lock(42))
+        .with_process("ps -p 42", ProcessOutput::ok("  PID TTY\n   42 ??\n"))
+        .with_process("ps -p 42 -o comm=", ProcessOutput::ok(VSCODE_COMM))
+        .with_process("mdls", ProcessOutput::ok(VSCODE_MDLS))
+}
+
+// ===== ターミナル検出 =====
+
+#[test]
+fn test_detect_terminal_bundle_id_branches() {
+    type Env = &'static [(&'static str, &'static str)];
+    let cases: &[(Env, Option<&str>)] = &[
+        // TERM_PROGRAM
+        (&[("TERM_PROGRAM", "iTerm.app")], Some("com.googlecode.iterm2")),
+        (&[("TERM_PROGRAM", "Apple_Terminal")], Some("com.apple.Terminal")),
+        (&[("TERM_PROGRAM", "WarpTerminal")], Some("dev.warp.Warp-Stable")),
+        (&[("TERM_PROGRAM", "Hyper")], Some("co.zeit.hyper")),
+        (&[("TERM_PROGRAM", "vscode")], None),
+        // ターミナル固有の環境変数（値は問わない）
+        (&[("ITERM_SESSION_ID", "")], Some("com.googlecode.iterm2")),
+        (&[("ALACRITTY_SOCKET", "/tmp/alacritty.sock")], Some("io.alacritty.Alacritty")),
+        (&[("KITTY_WINDOW_ID", "1")], Some("net.kovidgoyal.kitty")),
+        (&[("WARP_IS_LOCAL_SHELL_SESSION", "1")], Some("dev.warp.Warp-Stable")),
+        // LC_TERMINAL
+        (&[("LC_TERMINAL", "iTerm2")], Some("com.googlecode.iterm2")),
+        (&[("LC_TERMINAL", "Terminal")], Some("com.apple.Terminal")),
+        (&[("LC_TERMINAL", "tmux")], None),
+        // TERM
+        (&[("TERM", "xterm-kitty")], Some("net.kovidgoyal.kitty")),
+        (&[("TERM", "alacritty")], Some("io.alacritty.Alacritty")),
+        (&[("TERM", "xterm-256color")], None),
+        (&[], None),
+        // 優先順位: TERM_PROGRAM > 固有の環境変数 > LC_TERMINAL > TERM
+        (&[("TERM_PROGRAM", "Apple_Terminal"), ("ITERM_SESSION_ID", "w0t0p0:ABC")], Some("com.apple.Terminal")),
+        (&[("TERM_PROGRAM", "vscode"), ("KITTY_WINDOW_ID", "1")], Some("net.kovidgoyal.kitty")),
+        (&[("ALACRITTY_SOCKET", "/tmp/a.sock"), ("KITTY_WINDOW_ID", "1")], Some("io.alacritty.Alacritty")),
+        (&[("WARP_IS_LOCAL_SHELL_SESSION", "1"), ("LC_TERMINAL", "iTerm2")], Some("dev.warp.Warp-Stable")),
+        (&[("LC_TERMINAL", "Terminal"), ("TERM", "xterm-kitty")], Some("com.apple.Terminal")),
+        (&[("LC_TERMINAL", "tmux"), ("TERM", "alacritty")], Some("io.alacritty.Alacritty")),
+    ];
+
+    for (env, expected) in cases {
+        let mut system = FakeSystem::new();
+        for (key, value) in env.iter() {
+            system = system.with_env(key, value);
+        }
+        assert_eq!(
+            detect_terminal_bundle_id(&system).as_deref(),
+            *expected,
+            "env: {:?}",
+            env
+        );
+    }
+}
+
+// ===== IDE検出 =====
+
+#[test]
+fn test_detect_ide_bundle_id_running_ide() {
+    let system = ide_system();
+    assert_eq!(detect_ide_bundle_id(&system).as_deref(), Some("com.microsoft.VSCode"));
+    assert_eq!(
+        system.commands().last().unwrap(),
+        &["mdls", "-name", "kMDItemCFBundleIdentifier", "/Applications/Visual Studio Code.app"]
+    );
+}
+
+#[test]
+fn test_detect_ide_bundle_id_without_home() {
+    let system = FakeSystem::new().with_file(format!("{}/42.lock", LOCK_DIR), &lock(42));
+    assert_eq!(detect_ide_bundle_id(&system), None);
+    assert!(system.commands().is_empty());
+}
+
+#[test]
+fn test_detect_ide_bundle_id_without_lock_dir() {
+    let system = FakeSystem::new().with_env("HOME", "/Users/dev");
+    assert_eq!(detect_ide_bundle_id(&system), None);
+}
+
+#[test]
+fn test_detect_ide_bundle_id_ignores_non_lock_files() {
+    let system = FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_file(format!("{}/42.json", LOCK_DIR), &lock(42))
+        .with_process("ps", ProcessOutput::ok(VSCODE_COMM));
+    assert_eq!(detect_ide_bundle_id(&system), None);
+    assert!(system.commands().is_empty());
+}
+
+#[test]
+fn test_detect_ide_bundle_id_uses_latest_lock_file() {
+    let system = FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_file_modified(format!("{}/1.lock", LOCK_DIR), &lock(1), at(100))
+        .with_file_modified(format!("{}/42.lock", LOCK_DIR), &lock(42), at(300))
+        .with_file_modified(format!("{}/7.lock", LOCK_DIR), &lock(7), at(200))
+        .with_process("ps -p 42", ProcessOutput::ok(""))
+        .with_process("ps -p 42 -o comm=", ProcessOutput::ok(VSCODE_COMM))
+        .with_process("mdls", ProcessOutput::ok(VSCODE_MDLS));
+    assert_eq!(detect_ide_bundle_id(&system).as_deref(), Some("com.microsoft.VSCode"));
+    assert_eq!(system.commands()[0], ["ps", "-p", "42"]);
+}
+
+#[test]
+fn test_detect_ide_bundle_id_invalid_lock_json() {
+    let system = FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_file(format!("{}/42.lock", LOCK_DIR), "not json");
+    assert_eq!(detect_ide_bundle_id(&system), None);
+    assert!(system.commands().is_empty());
+}
+
+#[test]
+fn test_detect_ide_bundle_id_process_not_running() {
+    let system = ide_system().with_process("ps -p 42", ProcessOutput::failed());
+    assert_eq!(detect_ide_bundle_id(&system), None);
+    assert_eq!(system.commands().len(), 1);
+}
+
+#[test]
+fn test_detect_ide_bundle_id_ps_unavailable() {
+    let system = FakeSystem::new()
+        .with_env("HOME", "/Users/dev")
+        .with_file(format!("{}/42.lock", LOCK_DIR), &lock(42));
+    assert_eq!(detect_ide_bundle_id(&system), None);
+}
+
+#[test]
+fn test_detect_ide_bundle_id_empty_comm() {
+    for comm in ["", "\n", "  \n"] {
+        let system = ide_system().with_process("ps -p 42 -o comm=", ProcessOutput::ok(comm));
+        assert_eq!(detect_ide_bundle_id(&system), None, "comm: {:?}", comm);
+        assert!(system.commands().iter().all(|c| c[0] != "mdls"));
+    }
+}
+
+#[test]
+fn test_detect_ide_bundle_id_app_path_without_macos_dir() {
+    let system = ide_system().with_process("ps -p 42 -o comm=", ProcessOutput::ok("/usr/local/bin/zed\n"));
+    detect_ide_bundle_id(&system);
+    assert_eq!(
+        system.commands().last().unwrap(),
+        &["mdls", "-name", "kMDItemCFBundleIdentifier", "/usr/local/bin/zed"]
+    );
+}
+
+#[test]
+fn test_detect_ide_bundle_id_mdls_without_bundle_id() {
+    for mdls in ["kMDItemCFBundleIdentifier = (null)\n", ""] {
+        let system = ide_system().with_process("mdls", ProcessOutput::ok(mdls));
+        assert_eq!(detect_ide_bundle_id(&system), None, "mdls: {:?}", mdls);
+    }
+}
+
+#[test]
+fn test_detect_ide_bundle_id_mdls_unavailable() {
+    let mut system = ide_system();
+    system.processes.remove("mdls");
+    assert_eq!(detect_ide_bundle_id(&system), None);
+}
+
+// ===== 統合検出 =====
+
+#[test]
+fn test_get_activation_bundle_id_prefers_terminal() {
+    let system = ide_system().with_env("TERM_PROGRAM", "iTerm.app");
+    assert_eq!(get_activation_bundle_id(&system), "com.googlecode.iterm2");
+    assert!(system.commands().is_empty());
+}
+
+#[test]
+fn test_get_activation_bundle_id_falls_back_to_ide() {
+    assert_eq!(get_activation_bundle_id(&ide_system()), "com.microsoft.VSCode");
+}
+
+#[test]
+fn test_get_activation_bundle_id_default() {
+    assert_eq!(get_activation_bundle_id(&FakeSystem::new()), "com.apple.Terminal");
+}
+
+// ===== 通知送信 =====
+
+#[test]
+fn test_send_notification_args() {
+    let system = FakeSystem::new().with_process("terminal-notifier", ProcessOutput::ok(""));
+    send_notification(&system, "Title", "Message", "Subtitle", "com.apple.Terminal", "Glass").unwrap();
+    assert_eq!(
+        system.commands(),
+        vec![vec![
+            "terminal-notifier", "-title", "Title", "-message", "Message", "-subtitle", "Subtitle", "-sound",
+            "Glass", "-activate", "com.apple.Terminal",
+        ]]
+    );
+}
+
+#[test]
+fn test_send_notification_iterm2_selects_session() {
+    let system = FakeSystem::new()
+        .with_env("ITERM_SESSION_ID", "w0t1p0:ABC-123")
+        .with_process("terminal-notifier", ProcessOutput::ok(""));
+    send_notification(&system, "T", "M", "S", "com.googlecode.iterm2", "Glass").unwrap();
+    let args = &system.commands()[0];
+    let execute = args.iter().position(|a| a == "-execute").expect("-execute");
+    assert!(args[execute + 1].starts_with("osascript -e 'tell application \"iTerm2\"'"));
+    assert!(args[execute + 1].contains(r#"if id of s is "ABC-123" then"#));
+    assert_eq!(args[args.len() - 2..], ["-activate", "com.googlecode.iterm2"]);
+}
+
+#[test]
+fn test_send_notification_iterm2_without_session() {
+    let system = FakeSystem::new()
+        .with_env("ITERM_SESSION_ID", "w0t1p0")
+        .with_process("terminal-notifier", ProcessOutput::ok(""));
+    send_notification(&system, "T", "M", "S", "com.googlecode.iterm2", "Glass").unwrap();
+    assert!(!system.commands()[0].iter().any(|a| a == "-execute"));
+}
+
+#[test]
+fn test_send_notification_missing_notifier() {
+    let err = send_notification(&FakeSystem::new(), "T", "M", "S", "com.apple.Terminal", "Glass").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_build_iterm2_url_scheme() {
+    let cases = [
+        (Some("w0t0p0:ABC-123"), Some("x-claude-iterm://switch?guid=ABC-123")),
+        (Some("w0t0p0:"), None),
+        (Some("w0t0p0"), None),
+        (None, None),
+    ];
+    for (session_id, expected) in cases {
+        let mut system = FakeSystem::new();
+        if let Some(id) = session_id {
+            system = system.with_env("ITERM_SESSION_ID", id);
+        }
+        assert_eq!(build_iterm2_url_scheme(&system).as_deref(), expected, "{:?}", session_id);
+    }
+}
+
+#[test]
+fn test_get_git_branch() {
+    let system = FakeSystem::new().with_process(
+        "git -C /Users/dev/src/app rev-parse --abbrev-ref HEAD",
+        ProcessOutput::ok("main\n"),
+    );
+    assert_eq!(get_git_branch(&system, "/Users/dev/src/app").as_deref(), Some("main"));
+
+    let system = FakeSystem::new().with_process("git", ProcessOutput::failed());
+    assert_eq!(get_git_branch(&system, "/tmp"), None);
+}
+
+// ===== トランスクリプトとログ =====
+
+#[test]
+fn test_extract_messages_from_transcript() {
+    Locale::init(Locale::En);
+    let transcript = [
+        r#"{"type":"user","message":{"role":"user","content":"Fix the build"}}"#,
+        r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Done."}]}}"#,
+    ]
+    .join("\n");
+    let system = FakeSystem::new().with_file("/tmp/t.jsonl", &transcript);
+
+    assert_eq!(extract_user_prompt(&system, "/tmp/t.jsonl").unwrap(), "Fix the build");
+    assert_eq!(extract_assistant_message(&system, "/tmp/t.jsonl").unwrap(), "Done.");
+    assert!(extract_user_prompt(&system, "/tmp/missing.jsonl").is_err());
+}
+
+#[test]
+fn test_log_to_file_appends_with_clock() {
+    let mut system = FakeSystem::new().with_env("HOME", "/Users/dev");
+    system.now = DateTime::parse_from_rfc3339("2026-01-01T09:00:00+09:00").unwrap();
+
+    log_to_file(&system, "/Users/dev/app", "Fix the build", "Done.").unwrap();
+    log_to_file(&system, "/Users/dev/app", "Again", "OK").unwrap();
+    assert_eq!(
+        system.file("/Users/dev/.claude/task-complete.log").unwrap(),
+        "[2026-01-01 09:00:00]\n  Directory: /Users/dev/app\n  User Prompt: Fix the build\n  Assistant: Done.\n\n\
+         [2026-01-01 09:00:00]\n  Directory: /Users/dev/app\n  User Prompt: Again\n  Assistant: OK\n\n"
+    );
+
+    assert!(log_to_file(&FakeSystem::new(), "/tmp", "p", "m").is_err());
+}
+
+// ===== Slack =====
+
+#[test]
+fn test_slack_mode_from_env() {
+    assert_eq!(SlackMode::from_env(&FakeSystem::new()), SlackMode::Disabled);
+    assert_eq!(
+        SlackMode::from_env(&FakeSystem::new().with_env("CLAUDE_CODE_SLACK_WEBHOOK_URL", "https://hooks.example/x")),
+        SlackMode::Webhook {
+            url: "https://hooks.example/x".to_string()
+        }
+    );
+    let system = FakeSystem::new()
+        .with_env("CLAUDE_CODE_SLACK_WEBHOOK_URL", "https://hooks.example/x")
+        .with_env("CLAUDE_CODE_SLACK_BOT_TOKEN", "xoxb-1")
+        .with_env("CLAUDE_CODE_SLACK_CHANNEL", "#dev")
+        .with_env("CLAUDE_CODE_SLACK_UPLOAD_SNIPPETS", "true");
+    assert_eq!(
+        SlackMode::from_env(&system),
+        SlackMode::Bot {
+            token: "xoxb-1".to_string(),
+            channel: "#dev".to_string(),
+            upload_snippets: true,
+        }
+    );
+}
+
+#[test]
+fn test_post_to_slack_disabled_sends_nothing() {
+    let system = FakeSystem::new();
+    post_to_slack_rich(&system, "Title", &[("Project", "app")], None).unwrap();
+    assert!(system.requests().is_empty());
+}
+
+#[test]
+fn test_post_to_slack_webhook() {
+    let system = FakeSystem::new().with_env("CLAUDE_CODE_SLACK_WEBHOOK_URL", "https://hooks.example/x");
+    post_to_slack_rich(&system, "Title", &[("Project", "app")], Some("x-claude-iterm://switch?guid=A")).unwrap();
+
+    let requests = system.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url, "https://hooks.example/x");
+    let HttpBody::Json(payload) = &requests[0].body else {
+        panic!("expected JSON body");
+    };
+    let text = payload.to_string();
+    assert!(text.contains("Title"));
+    assert!(text.contains("x-claude-iterm://switch?guid=A"));
+}
+
+#[test]
+fn test_post_to_slack_webhook_error() {
+    let system = FakeSystem::new().with_env("CLAUDE_CODE_SLACK_WEBHOOK_URL", "https://hooks.example/x");
+    system.push_http_response(Err("status code 404"));
+    let err = post_to_slack_rich(&system, "Title", &[], None).unwrap_err();
+    assert_eq!(err, "Slack POST failed: status code 404");
+}
+
+#[test]
+fn test_slack_bot_threads_continuation_pages() {
+    let system = FakeSystem::new();
+    system.push_http_response(Ok(r#"{"ok":true,"channel":"C1","ts":"1.0"}"#));
+    system.push_http_response(Ok(r#"{"ok":true,"channel":"C1","ts":"1.1"}"#));
+    let mode = SlackMode::Bot {
+        token: "xoxb-1".to_string(),
+        channel: "#dev".to_string(),
+        upload_snippets: false,
+    };
+    let long = "x".repeat(2500);
+    let message = SlackMessage {
+        title: "Title",
+        fields: &[("Body", long.as_str())],
+        body: (0..60).map(|i| serde_json::json!({"type": "divider", "i": i})).collect(),
+        button_url: None,
+        full_text: None,
+        thread: None,
+    };
+    slack::send(&system, &mode, &message).unwrap();
+
+    let requests = system.requests();
+    assert!(requests.len() >= 2);
+    assert_eq!(requests[0].url, "https://slack.com/api/chat.postMessage");
+    assert!(requests[0]
+        .headers
+        .contains(&("Authorization".to_string(), "Bearer xoxb-1".to_string())));
+    let HttpBody::Json(first) = &requests[0].body else {
+        panic!("expected JSON body");
+    };
+    assert_eq!(first["channel"], "#dev");
+    assert!(first.get("thread_ts").is_none());
+    let HttpBody::Json(second) = &requests[1].body else {
+        panic!("expected JSON body");
+    };
+    assert_eq!(second["channel"], "C1");
+    assert_eq!(second["thread_ts"], "1.0");
+}
+
+#[test]
+fn test_slack_bot_uploads_full_text_instead_of_continuation_pages() {
+    let system = FakeSystem::new();
+    system.push_http_response(Ok(r#"{"ok":true,"channel":"C1","ts":"1.0"}"#));
+    system.push_http_response(Ok(r#"{"ok":true,"upload_url":"https://files.example/u1","file_id":"F1"}"#));
+    system.push_http_response(Ok("OK"));
+    system.push_http_response(Ok(r#"{"ok":true}"#));
+    let mode = SlackMode::Bot {
+        token: "xoxb-1".to_string(),
+        channel: "#dev".to_string(),
+        upload_snippets: true,
+    };
+    let full_text = "# Plan\n".repeat(500);
+    let message = SlackMessage {
+        title: "Title",
+        fields: &[],
+        body: (0..60).map(|i| serde_json::json!({"type": "divider", "i": i})).collect(),
+        button_url: None,
+        full_text: Some(&full_text),
+        thread: None,
+    };
+    slack::send(&system, &mode, &message).unwrap();
+
+    let requests = system.requests();
+    let urls: Vec<&str> = requests.iter().map(|r| r.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://slack.com/api/chat.postMessage",
+            "https://slack.com/api/files.getUploadURLExternal",
+            "https://files.example/u1",
+            "https://slack.com/api/files.completeUploadExternal",
+        ]
+    );
+    let HttpBody::Form(form) = &requests[1].body else {
+        panic!("expected form body");
+    };
+    assert!(form.contains(&("length".to_string(), full_text.len().to_string())));
+    assert_eq!(requests[2].body, HttpBody::Bytes(full_text.as_bytes().to_vec()));
+    let HttpBody::Json(complete) = &requests[3].body else {
+        panic!("expected JSON body");
+    };
+    assert_eq!(complete["channel_id"], "C1");
+    assert_eq!(complete["thread_ts"], "1.0");
+    assert_eq!(complete["files"][0]["id"], "F1");
+}
+
+#[test]
+fn test_slack_bot_upload_error() {
+    let system = FakeSystem::new();
+    system.push_http_response(Ok(r#"{"ok":true,"channel":"C1","ts":"1.0"}"#));
+    system.push_http_response(Ok(r#"{"ok":false,"error":"missing_scope"}"#));
+    let mode = SlackMode::Bot {
+        token: "xoxb-1".to_string(),
+        channel: "#dev".to_string(),
+        upload_snippets: true,
+    };
+    let message = SlackMessage {
+        title: "Title",
+        fields: &[],
+        body: (0..60).map(|i| serde_json::json!({"type": "divider", "i": i})).collect(),
+        button_url: None,
+        full_text: Some("full"),
+        thread: None,
+    };
+    let err = slack::send(&system, &mode, &message).unwrap_err();
+    assert_eq!(err, "Slack files.getUploadURLExternal error: missing_scope");
+    assert_eq!(system.requests().len(), 2);
+}
+
+#[test]
+fn test_slack_bot_api_error() {
+    let system = FakeSystem::new();
+    system.push_http_response(Ok(r#"{"ok":false,"error":"channel_not_found"}"#));
+    let mode = SlackMode::Bot {
+        token: "xoxb-1".to_string(),
+        channel: "#nope".to_string(),
+        upload_snippets: false,
+    };
+    let message = SlackMessage {
+        title: "Title",
+        fields: &[],
+        body: Vec::new(),
+        button_url: None,
+        full_text: None,
+        thread: None,
+    };
+    let err = slack::send(&system, &mode, &message).unwrap_err();
+    assert_eq!(err, "Slack chat.postMessage error: channel_not_found");
+}
+
+#[test]
+fn test_fake_system_read_dir_and_write() {
+    let system = FakeSystem::new().with_file("/a/b.txt", "1").with_file("/a/c/d.txt", "2");
+    let entries = system.read_dir(Path::new("/a")).unwrap();
+    assert_eq!(entries, vec![PathBuf::from("/a/b.txt"), PathBuf::from("/a/c")]);
+    system.write_file(Path::new("/a/e.txt"), "3").unwrap();
+    assert_eq!(system.file("/a/e.txt").as_deref(), Some("3"));
+}
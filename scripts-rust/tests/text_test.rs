@@ -0,0 +1,116 @@
+use claude_hooks::slack::{build_pages, SlackMessage, HEADER_TEXT_LIMIT};
+use claude_hooks::system::{FakeSystem, ProcessOutput};
+use claude_hooks::text::{display_width, grapheme_count, shorten_path, truncate_chars, truncate_middle, truncate_width};
+use claude_hooks::{send_notification, truncate_content, NOTIFICATION_BODY_WIDTH, NOTIFICATION_TITLE_WIDTH};
+use proptest::prelude::*;
+
+#[test]
+fn test_truncate_chars_japanese_and_emoji() {
+    assert_eq!(truncate_chars("日本語のプロンプト", 20), "日本語のプロンプト");
+    assert_eq!(truncate_chars("日本語のプロンプト", 6), "日本語...");
+    // 肌の色付き絵文字や家族絵文字は1文字として扱い、途中で分割しない
+    assert_eq!(truncate_chars("👍🏽👨‍👩‍👧abcdef", 5), "👍🏽👨‍👩‍👧...");
+}
+
+#[test]
+fn test_truncate_width_counts_wide_chars() {
+    assert_eq!(display_width("あいう"), 6);
+    assert_eq!(truncate_width("あいうえおかきくけこ", 10), "あいう...");
+    // 全角文字が境界をまたぐ場合は手前で止める
+    assert_eq!(truncate_width("aあいうえお", 8), "aあい...");
+    assert_eq!(truncate_width("short", 10), "short");
+}
+
+#[test]
+fn test_truncate_middle_keeps_head_and_tail() {
+    let cmd = "cargo test --workspace --all-features -- --nocapture integration";
+    let short = truncate_middle(cmd, 30);
+    assert_eq!(short, "cargo test --w...e integration");
+    assert_eq!(display_width(&short), 30);
+    assert_eq!(truncate_middle("あいうえおかきくけこ", 11), "あい...けこ");
+    assert_eq!(truncate_middle("abcdef", 2), "..");
+}
+
+#[test]
+fn test_shorten_path_keeps_file_name() {
+    let path = "/Users/me/projects/very/deep/nested/src/main.rs";
+    assert_eq!(shorten_path(path, 24), "/Users/me/pro.../main.rs");
+    assert_eq!(shorten_path(path, 100), path);
+    // ファイル名だけで上限を超える場合は中央省略
+    assert_eq!(shorten_path("/tmp/日本語のファイル名.md", 12), "/tmp/....md");
+}
+
+#[test]
+fn test_notification_and_header_limits() {
+    let long = "あ".repeat(500);
+    let system = FakeSystem::new().with_process("terminal-notifier", ProcessOutput::ok(""));
+    send_notification(&system, &long, &long, "ok", "com.apple.Terminal", "Glass").unwrap();
+    let args = &system.commands()[0];
+    let arg = |name: &str| args[args.iter().position(|a| a == name).unwrap() + 1].clone();
+    assert!(display_width(&arg("-title")) <= NOTIFICATION_TITLE_WIDTH);
+    assert!(display_width(&arg("-message")) <= NOTIFICATION_BODY_WIDTH);
+    assert_eq!(arg("-subtitle"), "ok");
+
+    let message = SlackMessage {
+        title: &long,
+        fields: &[],
+        body: Vec::new(),
+        button_url: None,
+        full_text: None,
+        thread: None,
+    };
+    let header = build_pages(&message)[0][0]["text"]["text"].as_str().unwrap().to_string();
+    assert_eq!(grapheme_count(&header), HEADER_TEXT_LIMIT);
+}
+
+#[test]
+fn test_truncate_content_multibyte() {
+    let content = "あ".repeat(3000);
+    let result = truncate_content(&content);
+    assert!(result.starts_with(&"あ".repeat(2800)));
+    assert!(result.ends_with("...\n\n(truncated)"));
+}
+
+proptest! {
+    #[test]
+    fn prop_truncate_chars_never_exceeds(s in any::<String>(), max in 0usize..64) {
+        let result = truncate_chars(&s, max);
+        prop_assert!(grapheme_count(&result) <= max);
+        if grapheme_count(&s) <= max {
+            prop_assert_eq!(result, s);
+        }
+    }
+
+    #[test]
+    fn prop_truncate_width_never_exceeds(s in any::<String>(), max in 0usize..64) {
+        let result = truncate_width(&s, max);
+        if display_width(&s) <= max {
+            prop_assert_eq!(&result, &s);
+        } else {
+            prop_assert!(display_width(&result) <= max);
+            let head = result.trim_end_matches('.');
+            prop_assert!(s.starts_with(head));
+        }
+    }
+
+    #[test]
+    fn prop_truncate_middle_never_exceeds(s in any::<String>(), max in 0usize..64) {
+        let result = truncate_middle(&s, max);
+        prop_assert!(display_width(&result) <= max || display_width(&s) <= max);
+    }
+
+    #[test]
+    fn prop_shorten_path_never_exceeds(
+        parts in proptest::collection::vec("[^/]{0,12}", 0..6),
+        max in 0usize..64,
+    ) {
+        let path = parts.join("/");
+        let result = shorten_path(&path, max);
+        prop_assert!(display_width(&result) <= max || display_width(&path) <= max);
+    }
+
+    #[test]
+    fn prop_truncate_content_never_panics(s in any::<String>()) {
+        let _ = truncate_content(&s);
+    }
+}
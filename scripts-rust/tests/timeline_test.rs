@@ -0,0 +1,118 @@
+mod common;
+
+use chrono::FixedOffset;
+use claude_hooks::i18n::Locale;
+use claude_hooks::mcp::McpServerRegistry;
+use claude_hooks::timeline::{
+    events_path, reason_text, recorded_events, timeline_path, transcript_events, EventKind, Timeline, TimelineEvent,
+};
+use common::{assistant, at, jsonl, timestamped, tool_result, tool_use, user};
+use serde_json::json;
+use std::path::Path;
+
+fn event(time: &str, kind: EventKind, label: &str) -> TimelineEvent {
+    TimelineEvent::new(at(time), kind, label)
+}
+
+fn kinds(events: &[TimelineEvent]) -> Vec<EventKind> {
+    events.iter().map(|e| e.kind).collect()
+}
+
+// ===== トランスクリプトから =====
+
+#[test]
+fn test_transcript_events() {
+    Locale::init(Locale::En);
+    let transcript = jsonl(&[
+        timestamped(user(json!("Fix\nthe build")), "00:00:00"),
+        timestamped(
+            assistant(json!([
+                {"type": "text", "text": "Looking"},
+                tool_use("t1", "Bash", json!({"command": "cargo build"})),
+            ])),
+            "00:00:05",
+        ),
+        timestamped(user(json!([tool_result("t1", json!("ok"))])), "00:00:06"),
+        timestamped(
+            assistant(json!([tool_use("t2", "ExitPlanMode", json!({"plan": "Intro\n\n## Fix the build\n- step"}))])),
+            "00:00:10",
+        ),
+        timestamped(assistant(json!("Fixed.")), "00:00:20"),
+        // timestampの無いエントリは除く
+        user(json!("no timestamp")),
+        timestamped(user(json!("Next")), "00:01:00"),
+    ]);
+    let events = transcript_events(&transcript, "/repo", &McpServerRegistry::default());
+    assert_eq!(
+        kinds(&events),
+        [EventKind::Prompt, EventKind::ToolCall, EventKind::Plan, EventKind::Completion, EventKind::Prompt]
+    );
+    assert_eq!(events[0].label, "Fix the build");
+    assert_eq!(events[2].label, "Fix the build");
+    assert_eq!(events[3].label, "Fixed.");
+    assert_eq!(events[3].timestamp, "2026-01-01T00:00:20+09:00");
+}
+
+#[test]
+fn test_recorded_events() {
+    let log = format!(
+        "{}\nbroken\n",
+        serde_json::to_string(&event("00:00:01", EventKind::Permission, "Edit")).unwrap()
+    );
+    assert_eq!(recorded_events(&log), [event("00:00:01", EventKind::Permission, "Edit")]);
+    assert_eq!(
+        events_path(Path::new("/Users/dev"), "a/b"),
+        Path::new("/Users/dev/.claude/session-events/a_b.jsonl")
+    );
+    assert_eq!(
+        timeline_path(Path::new("/Users/dev"), "a/b"),
+        Path::new("/Users/dev/.claude/session-timelines/a_b.json")
+    );
+}
+
+// ===== タイムライン =====
+
+#[test]
+fn test_timeline_sorts_and_counts() {
+    Locale::init(Locale::En);
+    let timeline = Timeline::new(
+        "s",
+        "/repo",
+        Some("clear"),
+        vec![
+            event("00:00:00", EventKind::Prompt, "Go"),
+            event("00:02:00", EventKind::Completion, "Done"),
+            event("00:00:30", EventKind::ToolCall, "Bash"),
+            event("00:00:30", EventKind::Permission, "Bash"),
+        ],
+    );
+    assert_eq!(
+        kinds(&timeline.events),
+        [EventKind::Prompt, EventKind::ToolCall, EventKind::Permission, EventKind::Completion]
+    );
+    assert_eq!(timeline.duration_ms, Some(120_000));
+    assert_eq!(timeline.stats_text(), "1 prompt(s) · 1 tool call(s) · 1 permission request(s)");
+    assert_eq!(reason_text(timeline.reason.as_deref()), "Cleared with /clear");
+    assert_eq!(reason_text(Some("other")), "other");
+}
+
+#[test]
+fn test_markdown_collapses_long_tool_runs() {
+    Locale::init(Locale::En);
+    let mut events = vec![event("00:00:00", EventKind::Prompt, "Go")];
+    for i in 1..=7 {
+        events.push(event(&format!("00:00:0{}", i), EventKind::ToolCall, &format!("tool {}", i)));
+    }
+    events.push(event("00:01:30", EventKind::Completion, "Done"));
+    let timeline = Timeline::new("s", "/repo", None, events);
+
+    // 表示するタイムゾーンに変換する
+    let markdown = timeline.markdown(FixedOffset::east_opt(0).unwrap());
+    let lines: Vec<&str> = markdown.lines().collect();
+    assert_eq!(lines[0], "`15:00:00` 💬 Go");
+    assert_eq!(lines[1], "`15:00:01` tool 1 _(+1s)_");
+    assert_eq!(lines[4], "`15:00:04` tool 4 _(+1s)_");
+    assert_eq!(lines[5], "… 3 more tool calls");
+    assert_eq!(lines[6], "`15:01:30` ✅ Done _(+1m 23s)_");
+    assert_eq!(lines.len(), 7);
+}
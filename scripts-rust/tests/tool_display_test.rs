@@ -0,0 +1,82 @@
+use claude_hooks::i18n::Locale;
+use claude_hooks::mcp::McpServerRegistry;
+use claude_hooks::tool_display::{glob_match, render_template, ToolDisplayRegistry};
+use claude_hooks::HooksConfig;
+use serde_json::json;
+use std::collections::HashMap;
+
+fn registry() -> ToolDisplayRegistry {
+    Locale::init(Locale::En);
+    ToolDisplayRegistry::new(&[], McpServerRegistry::new(&HashMap::new()))
+}
+
+#[test]
+fn test_tools_without_template_use_tool_summary() {
+    let registry = registry();
+    let input = json!({"file_path": "/repo/src/main.rs"});
+    assert_eq!(registry.format("Write", Some(&input)), "✍️ Create file: main.rs");
+    assert_eq!(registry.format("Read", None), "📖 Read file");
+    assert_eq!(registry.format("mcp__pencil__batch_design", Some(&json!({}))), "🎨 Pencil › batch design");
+    assert_eq!(registry.format("UnknownTool", Some(&json!({}))), "▶️ UnknownTool");
+}
+
+#[test]
+fn test_user_templates_take_precedence() {
+    Locale::init(Locale::En);
+    let config: HooksConfig = serde_json::from_value(json!({
+        "tool_display": [
+            {"tool": "mcp__pencil__*", "emoji": "🎨", "label": "Pencil", "templates": ["{label} {tool}: {/shape/kind}"]},
+            {"tool": "Bash", "emoji": "💻", "templates": ["{/command|middle:10}"]}
+        ]
+    }))
+    .unwrap();
+    let registry = ToolDisplayRegistry::from_config(&config);
+
+    assert_eq!(
+        registry.format("mcp__pencil__draw", Some(&json!({"shape": {"kind": "circle"}}))),
+        "🎨 Pencil mcp__pencil__draw: circle"
+    );
+    // 参照した値が無ければラベルだけを表示
+    assert_eq!(registry.format("mcp__pencil__clear", Some(&json!({}))), "🎨 Pencil");
+    assert_eq!(registry.format("Bash", Some(&json!({"command": "echo hello world"}))), "💻 echo...rld");
+    // labelを省略した場合はツール名
+    assert_eq!(registry.format("Bash", None), "💻 Bash");
+    assert_eq!(registry.format("Edit", Some(&json!({"file_path": "a/b.rs"}))), "✏️ Edit file: b.rs");
+}
+
+#[test]
+fn test_render_template_placeholders_and_filters() {
+    let input = json!({
+        "url": "https://example.com/a/b",
+        "edits": [{"old_string": "foo"}],
+        "count": 3,
+        "flag": null
+    });
+    let render = |template: &str| render_template(template, "Label", "Tool", Some(&input));
+
+    assert_eq!(render("{label}/{tool}").as_deref(), Some("Label/Tool"));
+    assert_eq!(render("{/url|domain}").as_deref(), Some("example.com"));
+    assert_eq!(render("{/url|basename}").as_deref(), Some("b"));
+    assert_eq!(render("{/edits/0/old_string}").as_deref(), Some("foo"));
+    assert_eq!(render("n={/count}").as_deref(), Some("n=3"));
+    assert_eq!(render("{/url|width:10}").as_deref(), Some("https:/..."));
+    assert_eq!(render("{/flag}"), None);
+    assert_eq!(render("{/missing}"), None);
+    assert_eq!(render("{unknown}"), None);
+    assert_eq!(render("{/url").as_deref(), None);
+    assert_eq!(render_template("{/url}", "L", "T", None), None);
+}
+
+#[test]
+fn test_glob_match() {
+    assert!(glob_match("Bash", "Bash"));
+    assert!(!glob_match("Bash", "BashOutput"));
+    assert!(glob_match("mcp__pencil__*", "mcp__pencil__draw"));
+    assert!(glob_match("mcp__pencil__*", "mcp__pencil__"));
+    assert!(!glob_match("mcp__pencil__*", "mcp__other__draw"));
+    assert!(glob_match("mcp__*__draw", "mcp__pencil__draw"));
+    assert!(glob_match("*", "anything"));
+    assert!(glob_match("Bash?", "Bash2"));
+    assert!(!glob_match("Bash?", "Bash"));
+    assert!(glob_match("*Output", "BashOutput"));
+}
@@ -0,0 +1,107 @@
+use claude_hooks::i18n::Locale;
+use claude_hooks::{McpServerRegistry, ToolSummary};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const CWD: &str = "/repo";
+
+fn summarize(tool: &str, input: Value) -> ToolSummary {
+    Locale::init(Locale::En);
+    ToolSummary::build(tool, Some(&input), CWD, &McpServerRegistry::new(&HashMap::new()))
+}
+
+#[test]
+fn test_every_tool_has_short_and_long_description() {
+    // (ツール名, tool_input, 1行表示, 通知本文)
+    let cases = [
+        (
+            "AskUserQuestion",
+            json!({"questions": [{"question": "Which database?"}]}),
+            "💬 Asking the user",
+            "Which database?",
+        ),
+        ("ExitPlanMode", json!({"plan": "\n# Add caching\n\n1. ..."}), "📋 Presenting plan", "Add caching"),
+        ("Bash", json!({"command": "ls -la", "description": "List files"}), "🔧 Run command: List files", "List files\nls -la"),
+        ("Bash", json!({"command": "ls -la"}), "🔧 Run command: ls -la", "ls -la"),
+        (
+            "Bash",
+            json!({"command": "cargo test --workspace --all-targets -- --nocapture integration"}),
+            "🔧 Run command: cargo test --workspace -...--nocapture integration",
+            "cargo test --workspace --all-targets -- --nocapture integration",
+        ),
+        ("Read", json!({"file_path": "/repo/src/lib.rs"}), "📖 Read file: lib.rs", "src/lib.rs"),
+        ("Write", json!({"file_path": "/repo/new.txt"}), "✍️ Create file: new.txt", "new.txt"),
+        ("Edit", json!({"file_path": "/repo/src/main.rs"}), "✏️ Edit file: main.rs", "src/main.rs"),
+        ("MultiEdit", json!({"file_path": "/other/x.rs", "edits": []}), "✏️ Edit file: x.rs", "x.rs"),
+        (
+            "NotebookEdit",
+            json!({"notebook_path": "/repo/nb/analysis.ipynb"}),
+            "📓 Edit notebook: analysis.ipynb",
+            "nb/analysis.ipynb",
+        ),
+        ("Grep", json!({"pattern": "fn main"}), "🔍 Search code: fn main", "Pattern: fn main"),
+        ("Glob", json!({"pattern": "**/*.rs"}), "🔍 Find files: **/*.rs", "Pattern: **/*.rs"),
+        (
+            "Task",
+            json!({"description": "Explore repo", "subagent_type": "Explore"}),
+            "🤖 Run agent: Explore repo",
+            "Explore repo\nType: Explore",
+        ),
+        (
+            "WebFetch",
+            json!({"url": "https://docs.rs/serde/latest"}),
+            "🌐 Fetch web page: docs.rs",
+            "https://docs.rs/serde/latest",
+        ),
+        ("WebSearch", json!({"query": "rust glob"}), "🔎 Web search: rust glob", "rust glob"),
+        (
+            "TodoWrite",
+            json!({"todos": [{"content": "a", "status": "completed"}, {"content": "b", "status": "in_progress"}]}),
+            "✅ Update TODOs",
+            "b",
+        ),
+        ("Skill", json!({"skill": "pdf"}), "⚡ Run skill: pdf", "pdf"),
+        ("SlashCommand", json!({"command": "/review"}), "⚙️ Run command: /review", "/review"),
+        ("BashOutput", json!({"bash_id": "shell_1"}), "📤 Read output", "shell_1"),
+        ("KillShell", json!({"shell_id": "shell_1"}), "⛔ Kill shell", "shell_1"),
+        (
+            "mcp__pencil__batch_design",
+            json!({"filePath": "/repo/design.pen", "operations": []}),
+            "🎨 Pencil › batch design: /repo/design.pen",
+            "filePath: /repo/design.pen",
+        ),
+        ("mcp__github__list_issues", json!({}), "🔌 github › list issues", "mcp__github__list_issues"),
+        ("UnknownTool", json!({"x": 1}), "▶️ UnknownTool", "UnknownTool"),
+    ];
+
+    for (tool, input, short_line, long_text) in cases {
+        let summary = summarize(tool, input);
+        assert_eq!(summary.short_line(), short_line, "{}", tool);
+        assert_eq!(summary.long_text(tool), long_text, "{}", tool);
+    }
+}
+
+#[test]
+fn test_missing_input_falls_back_to_label() {
+    for tool in ["Bash", "Read", "Write", "Edit", "Grep", "Glob", "Task", "WebFetch", "WebSearch", "Skill"] {
+        let summary = summarize(tool, json!({}));
+        assert_eq!(summary.short_line(), summary.title(), "{}", tool);
+        assert_eq!(summary.long_text(tool), tool, "{}", tool);
+    }
+    // 空文字列は未指定と同じ扱い
+    let summary = summarize("Bash", json!({"command": "ls", "description": ""}));
+    assert_eq!(summary.short_line(), "🔧 Run command: ls");
+}
+
+#[test]
+fn test_slack_text_wraps_command_in_code_block() {
+    let summary = summarize("Bash", json!({"command": "rm -rf target", "description": "Clean build"}));
+    assert_eq!(summary.title(), "🔧 Run command");
+    assert_eq!(summary.slack_text("Bash"), "Clean build\n```\nrm -rf target\n```");
+
+    let summary = summarize("Bash", json!({"command": "echo '```'"}));
+    assert_eq!(summary.slack_text("Bash"), "```\necho '` ` `'\n```");
+
+    let summary = summarize("Edit", json!({"file_path": "/repo/a.rs"}));
+    assert_eq!(summary.slack_text("Edit"), "a.rs");
+}
@@ -2,7 +2,7 @@
 
 const fs = require('fs');
 const path = require('path');
-const { execSync } = require('child_process');
+const { execSync, execFileSync } = require('child_process');
 
 // 標準入力からJSONを読み込む
 let inputData = '';
@@ -23,11 +23,19 @@ process.stdin.on('end', () => {
     // 現在のディレクトリを取得
     const cwd = input.workspace?.current_dir || input.cwd || process.cwd();
 
-    // Gitブランチを取得
+    // Gitコンテキストを取得（git-contextバイナリがあればブランチ・変更状態・ahead/behindを表示）
     let branch = 'no-git';
+    const gitContextBin = path.join(process.env.HOME || '', '.claude/bin/git-context');
     try {
-      process.chdir(cwd);
-      branch = execSync('git rev-parse --abbrev-ref HEAD 2>/dev/null', { encoding: 'utf8' }).trim();
+      if (fs.existsSync(gitContextBin)) {
+        const ctx = JSON.parse(execFileSync(gitContextBin, [cwd], { encoding: 'utf8' }));
+        if (ctx) {
+          branch = ctx.display;
+        }
+      } else {
+        process.chdir(cwd);
+        branch = execSync('git rev-parse --abbrev-ref HEAD 2>/dev/null', { encoding: 'utf8' }).trim();
+      }
     } catch (e) {
       // Gitリポジトリではない場合
     }